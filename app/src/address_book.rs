@@ -0,0 +1,156 @@
+use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
+use ledger_device_sdk::NVMData;
+use protocol::address::Address;
+use utils::types::Hash;
+
+use crate::error_code::ErrorCode;
+
+pub const MAX_LABEL_LENGTH: usize = 20;
+pub const MAX_ADDRESS_BOOK_ENTRIES: usize = 10;
+// The encoded entry: address type (1 byte), hash (32 bytes), label length (1 byte) and label
+const ENTRY_SIZE: usize = 1 + Hash::ENCODED_LENGTH + 1 + MAX_LABEL_LENGTH;
+// The first byte is the number of entries
+const ADDRESS_BOOK_SIZE: usize = 1 + MAX_ADDRESS_BOOK_ENTRIES * ENTRY_SIZE;
+
+const P2PKH_TYPE: u8 = 0;
+const P2SH_TYPE: u8 = 2;
+
+#[link_section = ".nvm_data"]
+static mut ADDRESS_BOOK_DATA: NVMData<AtomicStorage<[u8; ADDRESS_BOOK_SIZE]>> =
+    NVMData::new(AtomicStorage::new(&[0u8; ADDRESS_BOOK_SIZE]));
+
+// A trusted recipient saved by the user, the address is stored as the lockup script type and hash
+pub struct AddressBookEntry<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> AddressBookEntry<'a> {
+    #[inline]
+    pub fn address_type(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    #[inline]
+    pub fn hash(&self) -> &'a [u8; 32] {
+        self.bytes[1..(1 + Hash::ENCODED_LENGTH)]
+            .try_into()
+            .unwrap()
+    }
+
+    #[inline]
+    pub fn address(&self) -> Address<'static> {
+        Address::Hash {
+            lockup_type: self.address_type(),
+            hash: *self.hash(),
+        }
+    }
+
+    #[inline]
+    pub fn label(&self) -> &'a [u8] {
+        let label_length = self.bytes[1 + Hash::ENCODED_LENGTH] as usize;
+        let from_index = 2 + Hash::ENCODED_LENGTH;
+        &self.bytes[from_index..(from_index + label_length)]
+    }
+
+    #[inline]
+    fn is(&self, address_type: u8, hash: &[u8; 32]) -> bool {
+        self.address_type() == address_type && self.hash() == hash
+    }
+}
+
+#[inline]
+fn get_data() -> &'static [u8; ADDRESS_BOOK_SIZE] {
+    unsafe { ADDRESS_BOOK_DATA.get_mut().get_ref() }
+}
+
+#[inline]
+fn update_data(data: &[u8; ADDRESS_BOOK_SIZE]) {
+    unsafe { ADDRESS_BOOK_DATA.get_mut().update(data) }
+}
+
+// The number of saved entries
+pub fn size() -> usize {
+    let size = get_data()[0] as usize;
+    // The storage is corrupted if the size is too large, we treat it as empty
+    if size > MAX_ADDRESS_BOOK_ENTRIES {
+        0
+    } else {
+        size
+    }
+}
+
+pub fn get_entry(index: usize) -> Option<AddressBookEntry<'static>> {
+    if index >= size() {
+        return None;
+    }
+    let from_index = 1 + index * ENTRY_SIZE;
+    Some(AddressBookEntry {
+        bytes: &get_data()[from_index..(from_index + ENTRY_SIZE)],
+    })
+}
+
+pub fn find_index(address_type: u8, hash: &[u8; 32]) -> Option<usize> {
+    (0..size()).find(|&index| get_entry(index).unwrap().is(address_type, hash))
+}
+
+// Returns the label if the address has been saved by the user
+pub fn find_label(address_type: u8, hash: &[u8; 32]) -> Option<&'static [u8]> {
+    find_index(address_type, hash).map(|index| get_entry(index).unwrap().label())
+}
+
+// Validate the entry before asking the user to confirm it
+// Only P2PKH and P2SH addresses can be saved, and the label must be printable ASCII
+pub fn check_entry(address_type: u8, label: &[u8]) -> Result<(), ErrorCode> {
+    if address_type != P2PKH_TYPE && address_type != P2SH_TYPE {
+        return Err(ErrorCode::InvalidAddressBookEntry);
+    }
+    if label.is_empty() || label.len() > MAX_LABEL_LENGTH {
+        return Err(ErrorCode::InvalidAddressBookEntry);
+    }
+    if !label.iter().all(|c| (0x20..0x7f).contains(c)) {
+        return Err(ErrorCode::InvalidAddressBookEntry);
+    }
+    Ok(())
+}
+
+// Save the entry, the label is replaced if the address has already been saved
+pub fn add_entry(address_type: u8, hash: &[u8; 32], label: &[u8]) -> Result<(), ErrorCode> {
+    check_entry(address_type, label)?;
+    let current_size = size();
+    let index = match find_index(address_type, hash) {
+        Some(index) => index,
+        None if current_size < MAX_ADDRESS_BOOK_ENTRIES => current_size,
+        None => return Err(ErrorCode::AddressBookFull),
+    };
+
+    let mut updated_data: [u8; ADDRESS_BOOK_SIZE] = *get_data();
+    let from_index = 1 + index * ENTRY_SIZE;
+    let entry = &mut updated_data[from_index..(from_index + ENTRY_SIZE)];
+    entry.fill(0);
+    entry[0] = address_type;
+    entry[1..(1 + Hash::ENCODED_LENGTH)].copy_from_slice(hash);
+    entry[1 + Hash::ENCODED_LENGTH] = label.len() as u8;
+    entry[(2 + Hash::ENCODED_LENGTH)..(2 + Hash::ENCODED_LENGTH + label.len())]
+        .copy_from_slice(label);
+    if index == current_size {
+        updated_data[0] = (current_size + 1) as u8;
+    }
+    update_data(&updated_data);
+    Ok(())
+}
+
+pub fn remove_entry(index: usize) -> Result<(), ErrorCode> {
+    let current_size = size();
+    if index >= current_size {
+        return Err(ErrorCode::InvalidAddressBookEntry);
+    }
+    let mut updated_data: [u8; ADDRESS_BOOK_SIZE] = *get_data();
+    let from_index = 1 + index * ENTRY_SIZE;
+    let to_index = 1 + current_size * ENTRY_SIZE;
+    // Shift the following entries to keep the entries contiguous
+    updated_data.copy_within((from_index + ENTRY_SIZE)..to_index, from_index);
+    updated_data[(to_index - ENTRY_SIZE)..to_index].fill(0);
+    updated_data[0] = (current_size - 1) as u8;
+    update_data(&updated_data);
+    Ok(())
+}
@@ -1,29 +1,24 @@
-use crate::error_code::ErrorCode;
 use ledger_secure_sdk_sys::*;
+use protocol::{
+    platform::{Blake2bHash, BLAKE2B_HASH_SIZE},
+    ErrorCode, Hasher,
+};
 
-pub const BLAKE2B_HASH_SIZE: usize = 32;
-pub type Blake2bHash = [u8; BLAKE2B_HASH_SIZE];
 pub struct Blake2bHasher(cx_blake2b_s);
 
 // A wrapper around the Ledger SDK's blake2b implementation
-impl Blake2bHasher {
-    pub fn new() -> Self {
+impl Hasher for Blake2bHasher {
+    fn new() -> Self {
         let mut v = cx_blake2b_t::default();
         unsafe { cx_blake2b_init_no_throw(&mut v, BLAKE2B_HASH_SIZE * 8) };
         Self(v)
     }
 
-    pub fn hash(input: &[u8]) -> Result<Blake2bHash, ErrorCode> {
-        let mut hasher = Blake2bHasher::new();
-        hasher.update(input)?;
-        hasher.finalize()
-    }
-
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         unsafe { cx_blake2b_init_no_throw(&mut self.0, BLAKE2B_HASH_SIZE * 8) };
     }
 
-    pub fn update(&mut self, input: &[u8]) -> Result<(), ErrorCode> {
+    fn update(&mut self, input: &[u8]) -> Result<(), ErrorCode> {
         let rc = unsafe {
             cx_hash_update(
                 &mut self.0 as *mut cx_blake2b_s as *mut cx_hash_t,
@@ -38,7 +33,7 @@ impl Blake2bHasher {
         }
     }
 
-    pub fn finalize(&mut self) -> Result<Blake2bHash, ErrorCode> {
+    fn finalize(&mut self) -> Result<Blake2bHash, ErrorCode> {
         let mut result = [0u8; BLAKE2B_HASH_SIZE];
         let rc = unsafe {
             cx_hash_final(
@@ -2,20 +2,27 @@ use ledger_device_sdk::io::{self, ApduHeader};
 use utils::deserialize_path;
 
 use crate::{
+    address_book,
+    blake2b_hasher::Blake2bHasher,
     debug::print::{println, println_slice},
-    error_code::ErrorCode,
-    public_key::{derive_pub_key, Address},
+    error_code::{to_reply, ErrorCode},
+    public_key::derive_pub_key,
+    settings::{self, Setting},
     sign_tx_context::SignTxContext,
-    ui::{bytes_to_string, review_address, sign_hash_ui, tx_reviewer::TxReviewer},
+    ui::{
+        bytes_to_string, review_address, review_address_book_entry, sign_hash_ui,
+        tx_reviewer::TxReviewer,
+    },
+};
+use protocol::{
+    address::{Address, HASH_ADDRESS_SIZE},
+    sign_tx::{handle_sign_tx, reset, PATH_LENGTH},
 };
 
-const MAX_TOKEN_SIZE: u8 = 5;
-const PATH_LENGTH: usize = 20;
 const HASH_LENGTH: usize = 32;
 const PATH_HEX_LENGTH: usize = PATH_LENGTH * 2;
-const CALL_CONTRACT_FLAG: u8 = 0x01;
-const SCRIPT_OFFSET: usize = 3; // the encoded script offset in the tx
-pub const TOKEN_METADATA_SIZE: usize = 46;
+const MAX_DECODE_PATH_LENGTH: usize = 64;
+const ADDRESS_BOOK_ENTRY_PREFIX_LENGTH: usize = 1 + HASH_LENGTH; // address type and hash
 
 #[repr(u8)]
 pub enum Ins {
@@ -23,17 +30,21 @@ pub enum Ins {
     GetPubKey,
     SignHash,
     SignTx,
+    AddressBook,
+    GetLastError,
 }
 
 impl TryFrom<io::ApduHeader> for Ins {
-    type Error = ErrorCode;
+    type Error = io::Reply;
     fn try_from(header: io::ApduHeader) -> Result<Self, Self::Error> {
         match header.ins {
             0 => Ok(Ins::GetVersion),
             1 => Ok(Ins::GetPubKey),
             2 => Ok(Ins::SignHash),
             3 => Ok(Ins::SignTx),
-            _ => Err(ErrorCode::BadIns),
+            4 => Ok(Ins::AddressBook),
+            5 => Ok(Ins::GetLastError),
+            _ => Err(to_reply(ErrorCode::BadIns)),
         }
     }
 }
@@ -45,13 +56,13 @@ pub fn handle_apdu(
     tx_reviewer: &mut TxReviewer,
 ) -> Result<bool, io::Reply> {
     if comm.rx == 0 {
-        return Err(ErrorCode::BadLen.into());
+        return Err(to_reply(ErrorCode::BadLen));
     }
 
     let mut path: [u32; 5] = [0; 5];
     let apdu_header = comm.get_apdu_metadata();
     if apdu_header.cla != 0x80 {
-        return Err(ErrorCode::BadCla.into());
+        return Err(to_reply(ErrorCode::BadCla));
     }
 
     // Common instructions
@@ -66,13 +77,13 @@ pub fn handle_apdu(
             let data = comm.get_data()?;
             // 1 byte flag indicating whether address verification is needed
             if data.len() != PATH_LENGTH + 1 {
-                return Err(ErrorCode::BadLen.into());
+                return Err(to_reply(ErrorCode::BadLen));
             }
             let raw_path = &data[..PATH_LENGTH];
             deserialize_path::<io::Reply>(
                 raw_path,
                 &mut path,
-                ErrorCode::HDPathDecodingFailed.into(),
+                to_reply(ErrorCode::HDPathDecodingFailed),
             )?;
 
             println("raw path");
@@ -83,29 +94,34 @@ pub fn handle_apdu(
 
             let need_to_display = data[PATH_LENGTH] != 0;
             if need_to_display {
-                let address = Address::from_pub_key(&pk)?;
-                let address_str = bytes_to_string(address.get_address_bytes())?;
-                review_address(address_str)?;
+                let address = Address::from_pub_key::<Blake2bHasher>(pk.as_ref());
+                let mut address_bytes = [0u8; HASH_ADDRESS_SIZE];
+                let address_str = address.render(&mut address_bytes).map_err(to_reply)?;
+                review_address(bytes_to_string(address_str).map_err(to_reply)?)
+                    .map_err(to_reply)?;
             }
 
             comm.append(pk.as_ref());
             comm.append(hd_index.to_be_bytes().as_slice());
         }
         Ins::SignHash => {
+            if !settings::is_enabled(Setting::HashSigning) {
+                return Err(to_reply(ErrorCode::HashSigningDisabled));
+            }
             let data = comm.get_data()?;
             if data.len() != PATH_LENGTH + HASH_LENGTH {
-                return Err(ErrorCode::BadLen.into());
+                return Err(to_reply(ErrorCode::BadLen));
             }
             // This check can be removed, but we keep it for double checking
             deserialize_path::<io::Reply>(
                 &data[..PATH_LENGTH],
                 &mut path,
-                ErrorCode::HDPathDecodingFailed.into(),
+                to_reply(ErrorCode::HDPathDecodingFailed),
             )?;
 
             match sign_hash_ui(&path, &data[PATH_LENGTH..]) {
                 Ok((signature_buf, length, _)) => comm.append(&signature_buf[..length as usize]),
-                Err(code) => return Err(code.into()),
+                Err(code) => return Err(to_reply(code)),
             }
         }
         Ins::SignTx => {
@@ -116,96 +132,80 @@ pub fn handle_apdu(
                     return Err(code.into());
                 }
             };
-            match handle_sign_tx(apdu_header, data, sign_tx_context, tx_reviewer) {
-                Ok(()) if !sign_tx_context.is_complete() => {
-                    return Ok(false);
-                }
-                Ok(()) => {
-                    // The transaction is signed when all the data is processed
-                    // The signature is returned in the response
-                    let sign_result = tx_reviewer
-                        .approve_tx()
-                        .and_then(|_| sign_tx_context.sign_tx());
-                    let result = match sign_result {
-                        Ok((signature_buf, length, _)) => {
-                            comm.append(&signature_buf[..length as usize]);
-                            Ok(true)
-                        }
-                        Err(code) => Err(code.into()),
-                    };
-                    reset(sign_tx_context, tx_reviewer);
-                    return result;
-                }
-                Err(code) => {
-                    reset(sign_tx_context, tx_reviewer);
-                    return Err(code.into());
+            let (p1, p2) = (apdu_header.p1, apdu_header.p2);
+            match handle_sign_tx(p1, p2, data, sign_tx_context, tx_reviewer) {
+                Ok(None) => return Ok(false),
+                // The signature is returned in the response, preceded by the tx ID if requested
+                Ok(Some(signed_tx)) => {
+                    if let Some(tx_id) = signed_tx.tx_id {
+                        comm.append(tx_id.as_slice());
+                    }
+                    comm.append(signed_tx.signature())
                 }
+                Err(code) => return Err(to_reply(code)),
+            }
+        }
+        Ins::AddressBook => {
+            let data = comm.get_data()?;
+            handle_address_book(apdu_header, data).map_err(to_reply)?;
+        }
+        Ins::GetLastError => {
+            // The response is empty if no tx decoding error has occurred in the last signing session
+            if let Some(error_info) = sign_tx_context.get_last_error() {
+                let mut path_bytes = [0u8; MAX_DECODE_PATH_LENGTH];
+                let path = error_info.path.to_str(&mut path_bytes).unwrap_or(&[]);
+                comm.append((error_info.code as u16).to_be_bytes().as_slice());
+                comm.append(error_info.offset.to_be_bytes().as_slice());
+                comm.append(path);
             }
         }
     }
     Ok(true)
 }
 
-// The transaction is split into multiple APDU commands, consisting of token metadata APDU and tx APDU commands
-// We use `p1` and `p2` to distinguish between APDUs:
-// * `p1` = 0 and `p2` = 0 indicates the first token metadata APDU frame
-// * `p1` = 0 and `p2` = 1 indicates a new token metadata APDU frame
-// * `p1` = 0 and `p2` = 2 indicates the remaining token proof APDU frame
-// * `p1` = 1 and `p2` = 0 indicates the first tx APDU frame
-// * `p1` = 1 and `p2` = 1 indicates subsequent tx APDU frames
-fn handle_sign_tx(
-    apdu_header: &ApduHeader,
-    data: &[u8],
-    sign_tx_context: &mut SignTxContext,
-    tx_reviewer: &mut TxReviewer,
-) -> Result<(), ErrorCode> {
-    match (apdu_header.p1, apdu_header.p2) {
-        (0, 0) => {
-            // the first frame
-            if data.is_empty() {
-                return Err(ErrorCode::BadLen);
-            }
-            let token_size = data[0]; // the first byte is the token size
-            check_token_size(token_size)?;
-            tx_reviewer.init(token_size)?;
-            if token_size == 0 {
-                return Ok(());
-            }
-            tx_reviewer.handle_token_metadata(&data[1..])
+// The address book entries are saved or deleted only after the user confirms on the device
+// * `p1` = 0 saves the address with a label, the payload is the address type, hash and label
+// * `p1` = 1 deletes the saved address, the payload is the address type and hash
+fn handle_address_book(apdu_header: &ApduHeader, data: &[u8]) -> Result<(), ErrorCode> {
+    if data.len() < ADDRESS_BOOK_ENTRY_PREFIX_LENGTH {
+        return Err(ErrorCode::BadLen);
+    }
+    let address_type = data[0];
+    let hash: &[u8; HASH_LENGTH] = data[1..ADDRESS_BOOK_ENTRY_PREFIX_LENGTH]
+        .try_into()
+        .unwrap();
+    let address = Address::Hash {
+        lockup_type: address_type,
+        hash: *hash,
+    };
+    let mut address_bytes = [0u8; HASH_ADDRESS_SIZE];
+    match apdu_header.p1 {
+        0 => {
+            let label = &data[ADDRESS_BOOK_ENTRY_PREFIX_LENGTH..];
+            address_book::check_entry(address_type, label)?;
+            let address_str = address.render(&mut address_bytes)?;
+            review_address_book_entry(
+                bytes_to_string(address_str)?,
+                bytes_to_string(label)?,
+                false,
+            )?;
+            address_book::add_entry(address_type, hash, label)
         }
-        (0, 1) => tx_reviewer.handle_token_metadata(data), // token metadata and proof frame
-        (0, 2) => tx_reviewer.handle_token_proof(data),    // the following token proof frame
-        (1, 0) => {
-            // the first unsigned tx frame
-            if data.len() < PATH_LENGTH + SCRIPT_OFFSET {
+        1 => {
+            if data.len() != ADDRESS_BOOK_ENTRY_PREFIX_LENGTH {
                 return Err(ErrorCode::BadLen);
             }
-            let tx_data = &data[PATH_LENGTH..];
-            let is_tx_execute_script = tx_data[SCRIPT_OFFSET - 1] == CALL_CONTRACT_FLAG;
-            if is_tx_execute_script {
-                tx_reviewer.check_blind_signing()?;
-            }
-            tx_reviewer.set_tx_execute_script(is_tx_execute_script);
-
-            sign_tx_context.init(&data[..PATH_LENGTH])?;
-            sign_tx_context.handle_tx_data(apdu_header, tx_data, tx_reviewer)
+            let index = address_book::find_index(address_type, hash)
+                .ok_or(ErrorCode::InvalidAddressBookEntry)?;
+            let entry = address_book::get_entry(index).unwrap();
+            let address_str = address.render(&mut address_bytes)?;
+            review_address_book_entry(
+                bytes_to_string(address_str)?,
+                bytes_to_string(entry.label())?,
+                true,
+            )?;
+            address_book::remove_entry(index)
         }
-        (1, 1) => sign_tx_context.handle_tx_data(apdu_header, data, tx_reviewer), // the following unsigned tx frame
         _ => Err(ErrorCode::BadP1P2),
     }
 }
-
-#[inline]
-fn check_token_size(size: u8) -> Result<(), ErrorCode> {
-    if size > MAX_TOKEN_SIZE {
-        Err(ErrorCode::InvalidTokenSize)
-    } else {
-        Ok(())
-    }
-}
-
-#[inline]
-fn reset(sign_tx_context: &mut SignTxContext, tx_reviewer: &mut TxReviewer) {
-    sign_tx_context.reset();
-    tx_reviewer.reset();
-}
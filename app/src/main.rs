@@ -1,11 +1,12 @@
 #![no_std]
 #![no_main]
 
-use crate::ui::tx_reviewer::TxReviewer;
+use crate::ui::tx_reviewer::new_tx_reviewer;
 use handler::handle_apdu;
 use ledger_device_sdk::io;
-use sign_tx_context::SignTxContext;
+use sign_tx_context::new_sign_tx_context;
 
+mod address_book;
 mod blake2b_hasher;
 mod debug;
 mod error_code;
@@ -14,7 +15,7 @@ mod nvm;
 mod public_key;
 mod settings;
 mod sign_tx_context;
-mod token_verifier;
+mod storage;
 mod ui;
 
 ledger_device_sdk::set_panic!(ledger_device_sdk::exiting_panic);
@@ -23,10 +24,11 @@ ledger_device_sdk::set_panic!(ledger_device_sdk::exiting_panic);
 #[no_mangle]
 extern "C" fn sample_main() {
     let mut comm = io::Comm::new();
+    settings::migrate();
 
     // Initialize the sign tx context and tx reviewer
-    let mut sign_tx_context: SignTxContext = SignTxContext::new();
-    let mut tx_reviewer: TxReviewer = TxReviewer::new();
+    let mut sign_tx_context = new_sign_tx_context();
+    let mut tx_reviewer = new_tx_reviewer();
 
     #[cfg(not(any(target_os = "stax", target_os = "flex")))]
     {
@@ -49,24 +51,13 @@ extern "C" fn sample_main() {
 
     #[cfg(any(target_os = "stax", target_os = "flex"))]
     {
-        use crate::settings::SETTINGS_DATA;
-        use include_gif::include_gif;
+        use crate::ui::nbgl::home::{take_address_book_request, HomeAndSettings};
         use ledger_device_sdk::nbgl::init_comm;
-        use ledger_device_sdk::nbgl::{NbglGlyph, NbglHomeAndSettings, PageIndex};
 
-        const APP_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("alph_64x64.gif", NBGL));
-        let settings_strings: &[[&str; 2]] = &[["Blind signing", "Enable blind signing"]];
-        let mut home_and_settings = NbglHomeAndSettings::new()
-            .glyph(&APP_ICON)
-            .settings(unsafe { SETTINGS_DATA.get_mut() }, settings_strings)
-            .infos(
-                "Alephium",
-                env!("CARGO_PKG_VERSION"),
-                env!("CARGO_PKG_AUTHORS"),
-            );
+        let mut home_and_settings = HomeAndSettings::new();
 
         init_comm(&mut comm);
-        home_and_settings.show_and_return();
+        home_and_settings.show_home();
 
         loop {
             if let io::Event::Command(ins) = comm.next_event() {
@@ -81,13 +72,19 @@ extern "C" fn sample_main() {
                             true
                         }
                     };
-                if tx_reviewer.display_settings() {
-                    tx_reviewer.reset_display_settings();
-                    home_and_settings = home_and_settings.set_start_page(PageIndex::Settings(0));
-                    home_and_settings.show_and_return();
+                if tx_reviewer.ui().display_settings {
+                    tx_reviewer.ui_mut().reset_display_settings();
+                    home_and_settings.show_settings();
                 } else if display_home {
-                    home_and_settings = home_and_settings.set_start_page(PageIndex::Home);
-                    home_and_settings.show_and_return();
+                    home_and_settings.show_home();
+                }
+            }
+            // The address book button of the home page is handled once its callback has returned
+            if take_address_book_request() {
+                if ui::nbgl::show_address_book() {
+                    home_and_settings.show_home();
+                } else {
+                    home_and_settings.show_settings();
                 }
             }
         }
@@ -1,16 +1,10 @@
-#[cfg(target_os = "nanos")]
-use crate::nvm::nvm_data::NVMData;
-#[cfg(not(target_os = "nanos"))]
-use ledger_device_sdk::NVMData;
 use ledger_secure_sdk_sys::nvm_write;
-
-use crate::error_code::ErrorCode;
+use protocol::{ErrorCode, NvmStorage};
 
 #[cfg(target_os = "nanos")]
 pub mod nvm_data;
-pub mod swapping_buffer;
 
-pub const NVM_DATA_SIZE: usize = 2048;
+pub use protocol::swapping_buffer::NVM_DATA_SIZE;
 
 #[allow(clippy::upper_case_acronyms)]
 #[repr(align(64))]
@@ -38,15 +32,17 @@ impl<const N: usize> NVM<N> {
     }
 }
 
-pub fn write_from<const N: usize>(
-    nvm_data: &mut NVMData<NVM<N>>,
-    from_index: usize,
-    bytes: &[u8],
-) -> Result<(), ErrorCode> {
-    let data = nvm_data.get_mut();
-    if data.write(from_index, bytes) {
-        Ok(())
-    } else {
-        Err(ErrorCode::Overflow)
+impl<const N: usize> NvmStorage<N> for NVM<N> {
+    #[inline]
+    fn get_ref(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    fn write_from(&mut self, from_index: usize, bytes: &[u8]) -> Result<(), ErrorCode> {
+        if self.write(from_index, bytes) {
+            Ok(())
+        } else {
+            Err(ErrorCode::Overflow)
+        }
     }
 }
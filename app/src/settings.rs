@@ -1,5 +1,6 @@
 use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
 use ledger_device_sdk::NVMData;
+use utils::types::{AlphUnit, AmountFormat};
 
 // Keep the size consistent with the settings defined in the ledger sdk
 pub const SETTINGS_SIZE: usize = 10;
@@ -7,15 +8,183 @@ pub const SETTINGS_SIZE: usize = 10;
 pub static mut SETTINGS_DATA: NVMData<AtomicStorage<[u8; SETTINGS_SIZE]>> =
     NVMData::new(AtomicStorage::new(&[0u8; SETTINGS_SIZE]));
 
-pub fn is_blind_signing_enabled() -> bool {
+// The last byte of the settings data is the layout version, bump the version and
+// add a migration to `migrate` whenever the meaning of the setting bytes changes
+const VERSION_INDEX: usize = SETTINGS_SIZE - 1;
+const SETTINGS_VERSION: u8 = 1;
+
+pub const SETTING_NUM: usize = 8;
+
+// The settings saved in the device NVM. The discriminant is the index of the setting byte,
+// which is also the index of the switch in the nbgl settings page
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setting {
+    BlindSigning = 0,
+    TxIdDisplay = 1,
+    ExpertMode = 2,
+    HashSigning = 3,
+    NetworkRestriction = 4,
+    ReadableAmounts = 5,
+    AttoAlphUnit = 6,
+    OutputSummary = 7,
+}
+
+// How a setting is displayed on the device
+pub struct SettingInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    // The labels displayed on the nano devices, indexed by the value of the setting byte
+    pub value_labels: [&'static str; 2],
+}
+
+impl Setting {
+    pub const ALL: [Setting; SETTING_NUM] = [
+        Setting::BlindSigning,
+        Setting::TxIdDisplay,
+        Setting::ExpertMode,
+        Setting::HashSigning,
+        Setting::NetworkRestriction,
+        Setting::ReadableAmounts,
+        Setting::AttoAlphUnit,
+        Setting::OutputSummary,
+    ];
+
+    pub const fn info(self) -> SettingInfo {
+        match self {
+            Setting::BlindSigning => SettingInfo {
+                name: "Blind signing",
+                description: "Enable blind signing",
+                value_labels: ["disabled", "enabled"],
+            },
+            Setting::TxIdDisplay => SettingInfo {
+                name: "Transaction ID",
+                description: "Show the transaction ID before signing",
+                value_labels: ["hidden", "shown"],
+            },
+            Setting::ExpertMode => SettingInfo {
+                name: "Expert mode",
+                description: "Show the gas details and the script before signing",
+                value_labels: ["disabled", "enabled"],
+            },
+            Setting::HashSigning => SettingInfo {
+                name: "Hash signing",
+                description: "Allow signing raw hashes",
+                value_labels: ["disabled", "enabled"],
+            },
+            Setting::NetworkRestriction => SettingInfo {
+                name: "Mainnet only",
+                description: "Reject transactions of other networks",
+                value_labels: ["any network", "mainnet only"],
+            },
+            Setting::ReadableAmounts => SettingInfo {
+                name: "Readable amounts",
+                description: "Group the digits and round long decimals",
+                value_labels: ["exact", "readable"],
+            },
+            Setting::AttoAlphUnit => SettingInfo {
+                name: "attoALPH unit",
+                description: "Show ALPH amounts in attoALPH",
+                value_labels: ["ALPH", "attoALPH"],
+            },
+            Setting::OutputSummary => SettingInfo {
+                name: "Output summary",
+                description: "Offer to review the totals of transactions with many outputs",
+                value_labels: ["disabled", "enabled"],
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum NetworkRestriction {
+    AnyNetwork,
+    MainnetOnly,
+}
+
+// The settings of a fresh install, hash signing is allowed as in the first layout version
+const DEFAULT_SETTINGS: [u8; SETTINGS_SIZE] = {
+    let mut data = [0u8; SETTINGS_SIZE];
+    data[Setting::HashSigning as usize] = 1;
+    data[VERSION_INDEX] = SETTINGS_VERSION;
+    data
+};
+
+// Upgrade the settings saved by a previous app version to the current layout,
+// this must be called before the settings are read
+pub fn migrate() {
+    let data: [u8; SETTINGS_SIZE] = unsafe { *SETTINGS_DATA.get_mut().get_ref() };
+    let version = data[VERSION_INDEX];
+    if version == SETTINGS_VERSION {
+        return;
+    }
+    let updated_data = match version {
+        // Version 0 is the released layout, which only has the blind signing setting in the first
+        // byte, the other bytes are zero and hash signing is always allowed
+        0 => {
+            let mut updated_data = data;
+            updated_data[Setting::HashSigning as usize] = 1;
+            updated_data[VERSION_INDEX] = SETTINGS_VERSION;
+            updated_data
+        }
+        // The layout of a newer app version is unknown, restore the default settings
+        _ => DEFAULT_SETTINGS,
+    };
+    unsafe { SETTINGS_DATA.get_mut().update(&updated_data) }
+}
+
+pub fn is_enabled(setting: Setting) -> bool {
     let settings = unsafe { SETTINGS_DATA.get_mut() };
-    settings.get_ref()[0] != 0
+    settings.get_ref()[setting as usize] != 0
 }
 
+// The label of the current value of the setting
 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
-pub fn toggle_blind_signing_setting() {
-    let settings = unsafe { SETTINGS_DATA.get_mut() };
+pub fn value_label(setting: Setting) -> &'static str {
+    setting.info().value_labels[is_enabled(setting) as usize]
+}
+
+pub fn toggle(setting: Setting) {
+    let index = setting as usize;
     let mut updated_data: [u8; SETTINGS_SIZE] = unsafe { *SETTINGS_DATA.get_mut().get_ref() };
-    updated_data[0] = if settings.get_ref()[0] != 0 { 0 } else { 1 };
+    updated_data[index] = if updated_data[index] != 0 { 0 } else { 1 };
     unsafe { SETTINGS_DATA.get_mut().update(&updated_data) }
 }
+
+pub fn network_restriction() -> NetworkRestriction {
+    if is_enabled(Setting::NetworkRestriction) {
+        NetworkRestriction::MainnetOnly
+    } else {
+        NetworkRestriction::AnyNetwork
+    }
+}
+
+// The readable amounts keep at most 4 significant decimals
+const READABLE_AMOUNT_DECIMALS: usize = 4;
+
+pub fn amount_format() -> AmountFormat {
+    let is_readable = is_enabled(Setting::ReadableAmounts);
+    AmountFormat {
+        thousands_separator: is_readable,
+        max_significant_decimals: if is_readable {
+            Some(READABLE_AMOUNT_DECIMALS)
+        } else {
+            None
+        },
+        alph_unit: if is_enabled(Setting::AttoAlphUnit) {
+            AlphUnit::AttoAlph
+        } else {
+            AlphUnit::Alph
+        },
+    }
+}
+
+// The user is offered the output summary if the tx has more outputs than the threshold
+const OUTPUT_SUMMARY_THRESHOLD: usize = 10;
+
+pub fn output_summary_threshold() -> Option<usize> {
+    if is_enabled(Setting::OutputSummary) {
+        Some(OUTPUT_SUMMARY_THRESHOLD)
+    } else {
+        None
+    }
+}
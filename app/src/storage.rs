@@ -0,0 +1,53 @@
+use protocol::Storage;
+use utils::types::AmountFormat;
+
+use crate::{
+    address_book,
+    settings::{self, NetworkRestriction, Setting},
+};
+
+// The network ID of the Alephium mainnet
+const MAINNET_NETWORK_ID: u8 = 0;
+
+// The settings and the address book saved in the device NVM
+pub struct DeviceStorage;
+
+impl Storage for DeviceStorage {
+    #[inline]
+    fn is_blind_signing_enabled(&self) -> bool {
+        settings::is_enabled(Setting::BlindSigning)
+    }
+
+    #[inline]
+    fn is_tx_id_display_enabled(&self) -> bool {
+        settings::is_enabled(Setting::TxIdDisplay)
+    }
+
+    #[inline]
+    fn is_expert_mode_enabled(&self) -> bool {
+        settings::is_enabled(Setting::ExpertMode)
+    }
+
+    #[inline]
+    fn allowed_network_id(&self) -> Option<u8> {
+        match settings::network_restriction() {
+            NetworkRestriction::AnyNetwork => None,
+            NetworkRestriction::MainnetOnly => Some(MAINNET_NETWORK_ID),
+        }
+    }
+
+    #[inline]
+    fn amount_format(&self) -> AmountFormat {
+        settings::amount_format()
+    }
+
+    #[inline]
+    fn output_summary_threshold(&self) -> Option<usize> {
+        settings::output_summary_threshold()
+    }
+
+    #[inline]
+    fn find_address_label(&self, address_type: u8, hash: &[u8; 32]) -> Option<&[u8]> {
+        address_book::find_label(address_type, hash)
+    }
+}
@@ -2,29 +2,41 @@ use include_gif::include_gif;
 use ledger_device_sdk::io::{self, ApduHeader, Reply};
 use ledger_device_sdk::ui::{
     bagls,
-    bitmaps::{Glyph, DASHBOARD_X},
+    bitmaps::{Glyph, BACK, DASHBOARD_X},
     gadgets,
     layout::Draw,
     screen_util,
 };
 use ledger_secure_sdk_sys::buttons::ButtonEvent;
 
-use crate::settings::{is_blind_signing_enabled, toggle_blind_signing_setting};
+use crate::address_book;
+use crate::settings::{self, Setting, SETTING_NUM};
+use crate::ui::{bytes_to_string, review_address_book_entry};
+use protocol::address::HASH_ADDRESS_SIZE;
 
-const UI_PAGE_NUM: u8 = 4;
+const UI_PAGE_NUM: u8 = 5;
+const SETTINGS_PAGE_INDEX: u8 = 2;
+const ADDRESS_BOOK_PAGE_INDEX: u8 = 3;
+const QUIT_PAGE_INDEX: u8 = 4;
 
 fn show_ui_welcome() {
     const APP_ICON: Glyph = Glyph::from_include(include_gif!("alph_14x14.gif"));
     gadgets::Page::from((["Alephium ", "is ready"], &APP_ICON)).place();
 }
 
-fn show_ui_blind_signing() {
-    let label = if is_blind_signing_enabled() {
-        "enabled"
-    } else {
-        "disabled"
-    };
-    gadgets::Page::from((["Blind Signing", label], false)).place();
+fn show_ui_settings() {
+    gadgets::Page::from((["Settings", "Press both to open"], false)).place();
+}
+
+// The settings submenu shows one page per setting, followed by the back page
+fn show_ui_setting(index: usize) {
+    match Setting::ALL.get(index) {
+        Some(setting) => {
+            let label = settings::value_label(*setting);
+            gadgets::Page::from(([setting.info().name, label], false)).place();
+        }
+        None => gadgets::Page::from(("Back", &BACK)).place(),
+    }
 }
 
 fn show_ui_version() {
@@ -32,6 +44,31 @@ fn show_ui_version() {
     gadgets::Page::from((["Version", VERSION], false)).place();
 }
 
+fn show_ui_address_book() {
+    let mut output = [0u8; 13];
+    let size = address_book::size();
+    let label = match utils::types::I32::unsafe_from(size).to_str(&mut output[..2]) {
+        Some(num_str) => {
+            let length = num_str.len();
+            output[length..(length + 6)].copy_from_slice(b" saved");
+            bytes_to_string(&output[..(length + 6)]).unwrap_or("")
+        }
+        None => "",
+    };
+    gadgets::Page::from((["Address book", label], false)).place();
+}
+
+// The address book submenu shows one page per saved address, followed by the back page
+fn show_ui_address_book_entry(index: usize) {
+    match address_book::get_entry(index) {
+        Some(entry) => {
+            let label = bytes_to_string(entry.label()).unwrap_or("");
+            gadgets::Page::from(([label, "Press both to review"], false)).place();
+        }
+        None => gadgets::Page::from(("Back", &BACK)).place(),
+    }
+}
+
 fn show_ui_quit() {
     gadgets::Page::from(("Quit", &DASHBOARD_X)).place();
 }
@@ -44,38 +81,149 @@ fn show_ui(index: u8) {
     match index {
         0 => show_ui_welcome(),
         1 => show_ui_version(),
-        2 => show_ui_blind_signing(),
-        3 => show_ui_quit(),
+        SETTINGS_PAGE_INDEX => show_ui_settings(),
+        ADDRESS_BOOK_PAGE_INDEX => show_ui_address_book(),
+        QUIT_PAGE_INDEX => show_ui_quit(),
         _ => panic!("Invalid ui index"),
     }
 
     screen_util::screen_update();
 }
 
+fn show_sub_menu_ui(sub_menu: SubMenu, index: usize) {
+    gadgets::clear_screen();
+    bagls::LEFT_ARROW.display();
+    bagls::RIGHT_ARROW.display();
+    match sub_menu {
+        SubMenu::Settings => show_ui_setting(index),
+        SubMenu::AddressBook => show_ui_address_book_entry(index),
+    }
+    screen_util::screen_update();
+}
+
+// Review the saved address, the user can delete it after reviewing
+fn review_address_book_entry_at(index: usize) {
+    let entry = match address_book::get_entry(index) {
+        Some(entry) => entry,
+        None => return,
+    };
+    let mut address_bytes = [0u8; HASH_ADDRESS_SIZE];
+    let address = match entry.address().render(&mut address_bytes) {
+        Ok(address) => address,
+        Err(_) => return,
+    };
+    if let (Ok(address), Ok(label)) = (bytes_to_string(address), bytes_to_string(entry.label())) {
+        if review_address_book_entry(address, label, true).is_ok() {
+            let _ = address_book::remove_entry(index);
+        }
+    }
+}
+
+// The submenus of the main pages, each of them ends with the back page
+#[derive(Clone, Copy)]
+enum SubMenu {
+    Settings,
+    AddressBook,
+}
+
+impl SubMenu {
+    // The number of entries, the back page is not included
+    fn entry_num(self) -> usize {
+        match self {
+            SubMenu::Settings => SETTING_NUM,
+            SubMenu::AddressBook => address_book::size(),
+        }
+    }
+
+    #[inline]
+    fn page_num(self) -> usize {
+        self.entry_num() + 1
+    }
+}
+
 pub struct MainPages {
     ui_index: u8,
+    // The submenu and the index of its page if a submenu is displayed
+    sub_menu: Option<(SubMenu, usize)>,
 }
 
 impl MainPages {
     pub fn new() -> Self {
         show_ui(0);
-        MainPages { ui_index: 0 }
+        MainPages {
+            ui_index: 0,
+            sub_menu: None,
+        }
     }
 
     pub fn show_ui(&mut self) {
-        show_ui(self.ui_index);
+        match self.sub_menu {
+            Some((sub_menu, index)) => show_sub_menu_ui(sub_menu, index),
+            None => show_ui(self.ui_index),
+        }
     }
 
     #[inline]
     fn right_page(&mut self) {
-        self.ui_index = (self.ui_index + 1) % UI_PAGE_NUM;
-        show_ui(self.ui_index);
+        match self.sub_menu {
+            Some((sub_menu, index)) => {
+                self.sub_menu = Some((sub_menu, (index + 1) % sub_menu.page_num()));
+            }
+            None => self.ui_index = (self.ui_index + 1) % UI_PAGE_NUM,
+        }
+        self.show_ui();
     }
 
     #[inline]
     fn left_page(&mut self) {
-        self.ui_index = (self.ui_index - 1) % UI_PAGE_NUM;
-        show_ui(self.ui_index);
+        match self.sub_menu {
+            Some((sub_menu, index)) => {
+                let page_num = sub_menu.page_num();
+                self.sub_menu = Some((sub_menu, (index + page_num - 1) % page_num));
+            }
+            None => self.ui_index = (self.ui_index + UI_PAGE_NUM - 1) % UI_PAGE_NUM,
+        }
+        self.show_ui();
+    }
+
+    fn on_both_buttons(&mut self) {
+        match self.sub_menu {
+            Some((sub_menu, index)) if index < sub_menu.entry_num() => {
+                self.on_sub_menu_entry(sub_menu, index);
+                self.show_ui();
+            }
+            Some(_) => {
+                // The back page
+                self.sub_menu = None;
+                self.show_ui();
+            }
+            None => match self.ui_index {
+                SETTINGS_PAGE_INDEX => {
+                    self.sub_menu = Some((SubMenu::Settings, 0));
+                    self.show_ui();
+                }
+                ADDRESS_BOOK_PAGE_INDEX => {
+                    self.sub_menu = Some((SubMenu::AddressBook, 0));
+                    self.show_ui();
+                }
+                QUIT_PAGE_INDEX => ledger_device_sdk::exit_app(0),
+                _ => (),
+            },
+        }
+    }
+
+    // Toggle the setting or review the saved address at the index of the submenu
+    fn on_sub_menu_entry(&mut self, sub_menu: SubMenu, index: usize) {
+        match sub_menu {
+            SubMenu::Settings => settings::toggle(Setting::ALL[index]),
+            SubMenu::AddressBook => {
+                review_address_book_entry_at(index);
+                // The entry might have been deleted, go back to the first page if it was the last one
+                if index >= address_book::size() {
+                    self.sub_menu = Some((sub_menu, 0));
+                }
+            }
+        }
     }
 
     pub fn show<T>(&mut self, comm: &mut io::Comm) -> io::Event<T>
@@ -98,12 +246,7 @@ impl MainPages {
                     self.left_page();
                 }
                 io::Event::Button(ButtonEvent::BothButtonsRelease) => {
-                    if self.ui_index == 2 {
-                        toggle_blind_signing_setting();
-                        show_ui_blind_signing();
-                    } else if self.ui_index == 3 {
-                        ledger_device_sdk::exit_app(0);
-                    }
+                    self.on_both_buttons();
                 }
                 event => return event,
             }
@@ -52,3 +52,40 @@ pub fn review_address(address: &str) -> Result<(), ErrorCode> {
         Err(ErrorCode::UserCancelled)
     }
 }
+
+// Review the address book entry before saving or deleting it
+pub fn review_address_book_entry(
+    address: &str,
+    label: &str,
+    is_deletion: bool,
+) -> Result<(), ErrorCode> {
+    let (review_messages, validation_message) = if is_deletion {
+        (["Delete ", "Saved address "], "Delete address")
+    } else {
+        (["Save ", "Address "], "Save address")
+    };
+    let fields = [
+        Field {
+            name: "Label",
+            value: label,
+        },
+        Field {
+            name: "Address",
+            value: address,
+        },
+    ];
+    let review = MultiFieldReview::new(
+        &fields,
+        &review_messages,
+        Some(&EYE),
+        validation_message,
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
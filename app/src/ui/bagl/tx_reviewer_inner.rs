@@ -1,15 +1,15 @@
-use crate::error_code::ErrorCode;
-use crate::settings::is_blind_signing_enabled;
+use crate::ui::to_device_fields;
 use ledger_device_sdk::{
     buttons::{ButtonEvent, ButtonsState},
     ui::bitmaps::{Glyph, CHECKMARK, CROSS, CROSSMARK, EYE, WARNING},
     ui::gadgets::{clear_screen, get_event, Field, MultiFieldReview, Page, PageStyle},
     ui::screen_util::screen_update,
 };
+use protocol::{ErrorCode, ReviewUi};
 
 // Different Ledger devices use different UI libraries, so we've introduced the
 // `TxReviewInner` to facilitate the display of tx details across different devices.
-// The `TxReviewInner` here is for Ledger Nanos/Nanosp/Nanox.
+// The `TxReviewInner` here is for Ledger Nanos/Nanosp/Nanox, it implements the `ReviewUi` of the protocol.
 pub struct TxReviewerInner {
     is_tx_execute_script: bool,
 }
@@ -21,20 +21,70 @@ impl TxReviewerInner {
         }
     }
 
+    fn finish_review_inner<'a>(
+        &self,
+        fields: &'a [Field<'a>],
+        review_message: &'a [&'a str],
+        review_glyph: Option<&'a Glyph<'a>>,
+    ) -> Result<(), ErrorCode> {
+        #[cfg(target_os = "nanos")]
+        let validation_messages = ["Accept and sign", ""];
+
+        #[cfg(not(target_os = "nanos"))]
+        let validation_messages = if !self.is_tx_execute_script {
+            ["Accept", "and sign"]
+        } else {
+            ["Accept risk", "and sign"]
+        };
+
+        let review = MultiFieldReview::new_with_validation_messages(
+            fields,
+            review_message,
+            review_glyph,
+            validation_messages,
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+}
+
+impl ReviewUi for TxReviewerInner {
+    #[inline]
+    fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
+        self.is_tx_execute_script = is_tx_execute_script;
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.is_tx_execute_script = false;
+    }
+
+    #[inline]
+    fn output_index_as_field(&self) -> bool {
+        false
+    }
+
     // Start review tx details
     #[inline]
-    pub fn start_review(&self) -> Result<(), ErrorCode> {
+    fn start_review(&mut self) -> Result<(), ErrorCode> {
         Ok(())
     }
 
-    pub fn review_fields<'a>(
-        &self,
-        fields: &'a [Field<'a>],
+    fn review_fields(
+        &mut self,
+        fields: &[protocol::Field],
         review_message: &str,
     ) -> Result<(), ErrorCode> {
+        let device_fields = to_device_fields(fields);
         let review_messages = ["Review ", review_message];
         let review = MultiFieldReview::new(
-            fields,
+            &device_fields[..fields.len()],
             &review_messages,
             Some(&EYE),
             "Continue",
@@ -50,8 +100,9 @@ impl TxReviewerInner {
     }
 
     // Review transfer that sends to self
-    pub fn review_self_transfer(&self, fee_field: Field) -> Result<(), ErrorCode> {
-        let fields = &[fee_field];
+    fn review_self_transfer(&mut self, fields: &[protocol::Field]) -> Result<(), ErrorCode> {
+        let device_fields = to_device_fields(fields);
+        let fields = &device_fields[..fields.len()];
         if self.is_tx_execute_script {
             self.finish_review_inner(fields, &["Blind Signing"], Some(&WARNING))
         } else {
@@ -60,7 +111,7 @@ impl TxReviewerInner {
     }
 
     // Review the warning for external inputs, i.e. inputs that are not from the device address
-    pub fn warning_external_inputs(&self) -> Result<(), ErrorCode> {
+    fn warning_external_inputs(&mut self) -> Result<(), ErrorCode> {
         let review_messages = ["There are ", "external inputs"];
         let review = MultiFieldReview::new(
             &[],
@@ -78,31 +129,61 @@ impl TxReviewerInner {
         }
     }
 
-    pub fn finish_review<'a>(&self, fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
-        self.finish_review_inner(fields, &[], None)
+    fn finish_review(&mut self, fields: &[protocol::Field]) -> Result<(), ErrorCode> {
+        let device_fields = to_device_fields(fields);
+        self.finish_review_inner(&device_fields[..fields.len()], &[], None)
     }
 
-    fn finish_review_inner<'a>(
-        &self,
-        fields: &'a [Field<'a>],
-        review_message: &'a [&'a str],
-        review_glyph: Option<&'a Glyph<'a>>,
-    ) -> Result<(), ErrorCode> {
-        #[cfg(target_os = "nanos")]
-        let validation_messages = ["Accept and sign", ""];
+    fn warning_blind_signing_disabled(&mut self) {
+        show_warning_page(["Blind signing", "must be enabled"]);
+    }
 
-        #[cfg(not(target_os = "nanos"))]
-        let validation_messages = if !self.is_tx_execute_script {
-            ["Accept", "and sign"]
-        } else {
-            ["Accept risk", "and sign"]
-        };
+    fn warning_invalid_output(&mut self) {
+        show_warning_page(["Invalid output", "amount"]);
+    }
 
-        let review = MultiFieldReview::new_with_validation_messages(
-            fields,
-            review_message,
-            review_glyph,
-            validation_messages,
+    fn warning_tx_rejected(&mut self, reason: &str) {
+        show_warning_page(["Tx rejected", reason]);
+    }
+
+    // Ask the user whether to review the outputs one by one or the totals of the outputs
+    fn ask_review_outputs(&mut self, _output_size: usize) -> Result<bool, ErrorCode> {
+        let review_messages = ["Review outputs", "one by one?"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&EYE),
+            "Review outputs",
+            Some(&CHECKMARK),
+            "Show totals",
+            Some(&CROSS),
+        );
+        Ok(review.show())
+    }
+
+    // Ask the user whether to display the disassembled script
+    fn ask_show_script(&mut self) -> Result<bool, ErrorCode> {
+        let review_messages = ["Show script", "before signing?"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&EYE),
+            "Show script",
+            Some(&CHECKMARK),
+            "Skip",
+            Some(&CROSS),
+        );
+        Ok(review.show())
+    }
+
+    // Tell the user that only the script hash is reviewed, the user can still reject the tx
+    fn warning_script_too_large(&mut self) -> Result<(), ErrorCode> {
+        let review_messages = ["Script too large", "to display"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&WARNING),
+            "Continue",
             Some(&CHECKMARK),
             "Reject",
             Some(&CROSS),
@@ -114,39 +195,26 @@ impl TxReviewerInner {
         }
     }
 
-    #[inline]
-    pub fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
-        self.is_tx_execute_script = is_tx_execute_script;
-    }
-
-    #[inline]
-    pub fn reset(&mut self) {
-        self.is_tx_execute_script = false;
+    fn review_script_page(
+        &mut self,
+        fields: &[protocol::Field],
+        review_message: &str,
+    ) -> Result<(), ErrorCode> {
+        self.review_fields(fields, review_message)
     }
+}
 
-    #[inline]
-    pub fn output_index_as_field(&self) -> bool {
-        false
-    }
+// Show a warning page until the user presses both buttons
+fn show_warning_page(label: [&str; 2]) {
+    let page = Page::new(PageStyle::PictureNormal, label, Some(&CROSSMARK));
+    clear_screen();
+    page.place();
+    screen_update();
+    let mut buttons = ButtonsState::new();
 
-    pub fn check_blind_signing(&self) -> Result<(), ErrorCode> {
-        if is_blind_signing_enabled() {
-            return Ok(());
-        }
-        let page = Page::new(
-            PageStyle::PictureNormal,
-            ["Blind signing", "must be enabled"],
-            Some(&CROSSMARK),
-        );
-        clear_screen();
-        page.place();
-        screen_update();
-        let mut buttons = ButtonsState::new();
-
-        loop {
-            if let Some(ButtonEvent::BothButtonsRelease) = get_event(&mut buttons) {
-                return Err(ErrorCode::BlindSigningDisabled);
-            }
+    loop {
+        if let Some(ButtonEvent::BothButtonsRelease) = get_event(&mut buttons) {
+            return;
         }
     }
 }
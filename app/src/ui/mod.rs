@@ -4,34 +4,34 @@ pub mod bagl;
 pub mod nbgl;
 
 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
-pub use bagl::{review_address, sign_hash_ui, tx_reviewer_inner::TxReviewerInner};
+pub use bagl::{
+    review_address, review_address_book_entry, sign_hash_ui, tx_reviewer_inner::TxReviewerInner,
+};
 #[cfg(any(target_os = "stax", target_os = "flex"))]
-pub use nbgl::{review_address, sign_hash_ui, tx_reviewer_inner::TxReviewerInner};
+pub use nbgl::{
+    review_address, review_address_book_entry, sign_hash_ui, tx_reviewer_inner::TxReviewerInner,
+};
 
-use crate::error_code::ErrorCode;
-use core::str::from_utf8;
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+use ledger_device_sdk::nbgl::Field;
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+use ledger_device_sdk::ui::gadgets::Field;
+pub use protocol::bytes_to_string;
+use protocol::tx_reviewer::MAX_OUTPUT_FIELD_SIZE;
 pub mod tx_reviewer;
 
-#[inline]
-pub fn bytes_to_string(bytes: &[u8]) -> Result<&str, ErrorCode> {
-    #[cfg(not(target_os = "stax"))]
-    {
-        match from_utf8(bytes) {
-            Ok(str) => Ok(str),
-            Err(_) => Err(ErrorCode::InternalError),
-        }
-    }
-
-    // We encountered a strange bug on Ledger Stax where Speculos exits immediately after
-    // loading the app (`syscall: os_sched_exit(0)[*] exit called (0)`), even though we haven't
-    // run any tests yet. This may be a bug in Speculos, although this bug might not be related
-    // to this function, the issue was resolved after changing to the following code,
-    // so we implemented this workaround to address the issue.
-    #[cfg(target_os = "stax")]
-    {
-        match from_utf8(bytes) {
-            Ok(_) => Ok(from_utf8(bytes).unwrap()),
-            Err(_) => Err(ErrorCode::InternalError),
-        }
+// Convert the tx details to the fields of the device UI library
+pub fn to_device_fields<'a>(fields: &[protocol::Field<'a>]) -> [Field<'a>; MAX_OUTPUT_FIELD_SIZE] {
+    assert!(fields.len() <= MAX_OUTPUT_FIELD_SIZE);
+    let mut device_fields: [Field; MAX_OUTPUT_FIELD_SIZE] = core::array::from_fn(|_| Field {
+        name: "",
+        value: "",
+    });
+    for (device_field, field) in device_fields.iter_mut().zip(fields) {
+        *device_field = Field {
+            name: field.name,
+            value: field.value,
+        };
     }
+    device_fields
 }
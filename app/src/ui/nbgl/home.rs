@@ -0,0 +1,139 @@
+extern crate alloc;
+
+use super::APP_ICON;
+use crate::settings::{self, Setting, SETTING_NUM};
+use alloc::ffi::CString;
+use core::ffi::{c_char, c_int};
+use core::mem::zeroed;
+use ledger_secure_sdk_sys::*;
+
+// The home page and the settings page of the nbgl devices. The settings page only has the switches
+// of the settings saved in NVM, the address book is opened by the action button of the home page.
+// The pages keep pointers to the fields, so the struct must not be moved after it has been shown
+pub struct HomeAndSettings {
+    app_name: CString,
+    infos: [CString; 2],
+    setting_strings: [[CString; 2]; SETTING_NUM],
+    switches: [nbgl_contentSwitch_t; SETTING_NUM],
+    content: nbgl_content_t,
+    setting_contents: nbgl_genericContents_t,
+    info_types: [*const c_char; 2],
+    info_contents: [*const c_char; 2],
+    info_list: nbgl_contentInfoList_t,
+    icon: nbgl_icon_details_t,
+    action: nbgl_homeAction_t,
+}
+
+// Set by the home action callback, the address book is shown once the callback has returned
+static mut ADDRESS_BOOK_REQUESTED: bool = false;
+
+unsafe extern "C" fn request_address_book() {
+    ADDRESS_BOOK_REQUESTED = true;
+}
+
+// Returns true if the user has pressed the address book button since the last call
+pub fn take_address_book_request() -> bool {
+    unsafe {
+        let requested = ADDRESS_BOOK_REQUESTED;
+        ADDRESS_BOOK_REQUESTED = false;
+        requested
+    }
+}
+
+// The token of a switch is the index of its setting after the first user token
+unsafe extern "C" fn toggle_setting(token: c_int, _index: u8, _page: c_int) {
+    let index = token - FIRST_USER_TOKEN as c_int;
+    if index >= 0 {
+        if let Some(setting) = Setting::ALL.get(index as usize) {
+            settings::toggle(*setting);
+        }
+    }
+}
+
+unsafe extern "C" fn quit_app() {
+    ledger_device_sdk::exit_app(0);
+}
+
+impl HomeAndSettings {
+    pub fn new() -> Self {
+        let setting_strings = core::array::from_fn(|index| {
+            let info = Setting::ALL[index].info();
+            [
+                CString::new(info.name).unwrap(),
+                CString::new(info.description).unwrap(),
+            ]
+        });
+        Self {
+            app_name: CString::new("Alephium").unwrap(),
+            infos: [
+                CString::new(env!("CARGO_PKG_VERSION")).unwrap(),
+                CString::new(env!("CARGO_PKG_AUTHORS")).unwrap(),
+            ],
+            setting_strings,
+            switches: unsafe { zeroed() },
+            content: unsafe { zeroed() },
+            setting_contents: unsafe { zeroed() },
+            info_types: [
+                b"Version\0".as_ptr() as *const c_char,
+                b"Developer\0".as_ptr() as *const c_char,
+            ],
+            info_contents: [core::ptr::null(); 2],
+            info_list: unsafe { zeroed() },
+            icon: (&APP_ICON).into(),
+            action: unsafe { zeroed() },
+        }
+    }
+
+    pub fn show_home(&mut self) {
+        self.show(INIT_HOME_PAGE as u8);
+    }
+
+    pub fn show_settings(&mut self) {
+        self.show(0);
+    }
+
+    // The switches are rebuilt from the saved settings every time the pages are shown
+    fn show(&mut self, start_page: u8) {
+        for (index, switch) in self.switches.iter_mut().enumerate() {
+            let strings = &self.setting_strings[index];
+            switch.text = strings[0].as_ptr();
+            switch.subText = strings[1].as_ptr();
+            switch.initState = if settings::is_enabled(Setting::ALL[index]) {
+                ON_STATE
+            } else {
+                OFF_STATE
+            };
+            switch.token = (FIRST_USER_TOKEN as usize + index) as u8;
+        }
+
+        self.content.type_ = SWITCHES_LIST;
+        self.content.content.switchesList.switches = self.switches.as_ptr();
+        self.content.content.switchesList.nbSwitches = SETTING_NUM as u8;
+        self.content.contentActionCallback = Some(toggle_setting);
+
+        self.setting_contents.callbackCallNeeded = false;
+        self.setting_contents.__bindgen_anon_1.contentsList = &self.content;
+        self.setting_contents.nbContents = 1;
+
+        self.info_contents = [self.infos[0].as_ptr(), self.infos[1].as_ptr()];
+        self.info_list.infoTypes = self.info_types.as_ptr();
+        self.info_list.infoContents = self.info_contents.as_ptr();
+        self.info_list.nbInfos = 2;
+
+        self.action.text = b"Address book\0".as_ptr() as *const c_char;
+        self.action.callback = Some(request_address_book);
+
+        unsafe {
+            nbgl_useCaseHomeAndSettings(
+                self.app_name.as_ptr(),
+                &self.icon,
+                core::ptr::null(),
+                start_page,
+                &self.setting_contents,
+                &self.info_list,
+                &self.action,
+                Some(quit_app),
+            );
+        }
+    }
+}
@@ -1,12 +1,14 @@
+pub mod home;
 pub mod tx_reviewer_inner;
 
-use crate::{error_code::ErrorCode, public_key::sign_hash};
+use crate::{address_book, error_code::ErrorCode, public_key::sign_hash, ui::bytes_to_string};
 use core::str::from_utf8;
 use include_gif::include_gif;
 use ledger_device_sdk::nbgl::{
     Field, NbglAddressReview, NbglChoice, NbglGlyph, NbglReviewStatus, NbglStreamingReview,
     TransactionType,
 };
+use protocol::address::HASH_ADDRESS_SIZE;
 
 pub static APP_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("alph_64x64.gif", NBGL));
 
@@ -73,3 +75,74 @@ pub fn review_address(address: &str) -> Result<(), ErrorCode> {
         Err(ErrorCode::UserCancelled)
     }
 }
+
+// Review the address book entry before saving or deleting it
+pub fn review_address_book_entry(
+    address: &str,
+    label: &str,
+    is_deletion: bool,
+) -> Result<(), ErrorCode> {
+    let (review_message, finish_message) = if is_deletion {
+        ("Delete saved address", "Delete address")
+    } else {
+        ("Save address", "Save address")
+    };
+    let reviewer = new_nbgl_review(TransactionType::Operation, false);
+    let fields = [
+        Field {
+            name: "Label",
+            value: label,
+        },
+        Field {
+            name: "Address",
+            value: address,
+        },
+    ];
+    let approved = reviewer.start(review_message, "")
+        && reviewer.continue_review(&fields)
+        && reviewer.finish(finish_message);
+    NbglReviewStatus::new().show(approved);
+    if approved {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+// The saved addresses are shown one by one from the home page, the user can delete each of them
+// Returns true if the user chooses to go back to the home page instead of the settings page
+pub fn show_address_book() -> bool {
+    if address_book::size() == 0 {
+        return !NbglChoice::new().glyph(&APP_ICON).show(
+            "No saved addresses",
+            "The addresses of trusted recipients can be saved from the wallet.",
+            "Back to settings",
+            "Back to home",
+        );
+    }
+    let mut index = 0;
+    while let Some(entry) = address_book::get_entry(index) {
+        let mut address_bytes = [0u8; HASH_ADDRESS_SIZE];
+        let address = entry
+            .address()
+            .render(&mut address_bytes)
+            .and_then(bytes_to_string);
+        let mut is_deleted = false;
+        if let (Ok(address), Ok(label)) = (address, bytes_to_string(entry.label())) {
+            let keep = NbglChoice::new().glyph(&APP_ICON).show(
+                label,
+                address,
+                "Keep address",
+                "Delete address",
+            );
+            is_deleted = !keep
+                && review_address_book_entry(address, label, true).is_ok()
+                && address_book::remove_entry(index).is_ok();
+        }
+        // The following entries are shifted after the deletion
+        if !is_deleted {
+            index += 1;
+        }
+    }
+    false
+}
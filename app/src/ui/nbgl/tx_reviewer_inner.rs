@@ -1,16 +1,17 @@
-use crate::{
-    error_code::ErrorCode,
-    settings::is_blind_signing_enabled,
-    ui::nbgl::{nbgl_review_warning, new_nbgl_review},
+use crate::ui::{
+    nbgl::{nbgl_review_warning, new_nbgl_review},
+    to_device_fields,
 };
 use ledger_device_sdk::nbgl::{Field, NbglReviewStatus, NbglStreamingReview, TransactionType};
+use protocol::{tx_reviewer::MAX_APPROVE_FIELD_SIZE, ErrorCode, ReviewUi};
 
 // Different Ledger devices use different UI libraries, so we've introduced the
 // `TxReviewInner` to facilitate the display of tx details across different devices.
-// The `TxReviewInner` here is for Ledger Stax/Flex.
+// The `TxReviewInner` here is for Ledger Stax/Flex, it implements the `ReviewUi` of the protocol.
 pub struct TxReviewerInner {
     pub display_settings: bool,
     is_tx_execute_script: bool,
+    // The review is started before the outputs if the user chooses to display the script
     reviewer: Option<NbglStreamingReview>,
 }
 
@@ -29,7 +30,41 @@ impl TxReviewerInner {
         self.reviewer.as_ref().unwrap()
     }
 
-    pub fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
+    fn continue_review<'a>(&self, fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
+        if self.get_reviewer().continue_review(fields) {
+            Ok(())
+        } else {
+            NbglReviewStatus::new().show(false);
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    fn finish_review_inner<'a>(&mut self, fee_fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
+        assert!(!fee_fields.is_empty());
+        self.reset_display_settings();
+        self.continue_review(fee_fields)?;
+        let message = if self.is_tx_execute_script {
+            "Accept risk and sign transaction?"
+        } else {
+            "Sign transaction to send assets?"
+        };
+        if self.get_reviewer().finish(message) {
+            NbglReviewStatus::new().show(true);
+            Ok(())
+        } else {
+            NbglReviewStatus::new().show(false);
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    #[inline]
+    pub fn reset_display_settings(&mut self) {
+        self.display_settings = false;
+    }
+}
+
+impl ReviewUi for TxReviewerInner {
+    fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
         assert!(self.reviewer.is_none());
         self.is_tx_execute_script = is_tx_execute_script;
         self.reviewer = Some(new_nbgl_review(
@@ -38,8 +73,22 @@ impl TxReviewerInner {
         ));
     }
 
+    #[inline]
+    fn reset(&mut self) {
+        // Since `reset` is called when blind signing checks fails,
+        // we cannot reset the `display_settings` within the reset function.
+        // Instead, we will reset the `display_settings` in the `finish_review` function.
+        self.reviewer = None;
+        self.is_tx_execute_script = false;
+    }
+
+    #[inline]
+    fn output_index_as_field(&self) -> bool {
+        true
+    }
+
     // Start review tx details
-    pub fn start_review(&mut self) -> Result<(), ErrorCode> {
+    fn start_review(&mut self) -> Result<(), ErrorCode> {
         let message = if self.is_tx_execute_script {
             "Review transaction"
         } else {
@@ -53,37 +102,37 @@ impl TxReviewerInner {
         }
     }
 
-    pub fn review_fields<'a>(
-        &self,
-        fields: &'a [Field<'a>],
+    fn review_fields(
+        &mut self,
+        fields: &[protocol::Field],
         _message: &str,
     ) -> Result<(), ErrorCode> {
-        if self.get_reviewer().continue_review(fields) {
-            Ok(())
-        } else {
-            NbglReviewStatus::new().show(false);
-            Err(ErrorCode::UserCancelled)
-        }
+        let device_fields = to_device_fields(fields);
+        self.continue_review(&device_fields[..fields.len()])
     }
 
     // Review transfer that sends to self
-    pub fn review_self_transfer(&mut self, fee_field: Field) -> Result<(), ErrorCode> {
+    fn review_self_transfer(&mut self, fields: &[protocol::Field]) -> Result<(), ErrorCode> {
         if self.is_tx_execute_script {
-            self.finish_review(&[fee_field])
+            let device_fields = to_device_fields(fields);
+            self.finish_review_inner(&device_fields[..fields.len()])
         } else {
-            let fields = &[
-                Field {
-                    name: "Amount",
-                    value: "Self-transfer",
-                },
-                fee_field,
-            ];
-            self.finish_review(fields)
+            // The fees and the tx ID follow the self-transfer amount
+            let mut all_fields = [protocol::Field {
+                name: "Amount",
+                value: "Self-transfer",
+            }; MAX_APPROVE_FIELD_SIZE + 1];
+            if fields.len() >= all_fields.len() {
+                return Err(ErrorCode::InternalError);
+            }
+            all_fields[1..=fields.len()].copy_from_slice(fields);
+            let device_fields = to_device_fields(&all_fields[..=fields.len()]);
+            self.finish_review_inner(&device_fields[..=fields.len()])
         }
     }
 
     // Review the warning for external inputs, i.e. inputs that are not from the device address
-    pub fn warning_external_inputs(&self) -> Result<(), ErrorCode> {
+    fn warning_external_inputs(&mut self) -> Result<(), ErrorCode> {
         let approved = nbgl_review_warning(
             "External inputs",
             "This transaction has inputs from addresses not associated with this device.",
@@ -97,42 +146,12 @@ impl TxReviewerInner {
         }
     }
 
-    pub fn finish_review<'a>(&mut self, fee_fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
-        assert!(!fee_fields.is_empty());
-        self.reset_display_settings();
-        self.review_fields(fee_fields, "Fees")?;
-        let message = if self.is_tx_execute_script {
-            "Accept risk and sign transaction?"
-        } else {
-            "Sign transaction to send assets?"
-        };
-        if self.get_reviewer().finish(message) {
-            NbglReviewStatus::new().show(true);
-            Ok(())
-        } else {
-            NbglReviewStatus::new().show(false);
-            Err(ErrorCode::UserCancelled)
-        }
-    }
-
-    #[inline]
-    pub fn reset(&mut self) {
-        // Since `reset` is called when blind signing checks fails,
-        // we cannot reset the `display_settings` within the reset function.
-        // Instead, we will reset the `display_settings` in the `finish_review` function.
-        self.reviewer = None;
-        self.is_tx_execute_script = false;
+    fn finish_review(&mut self, fee_fields: &[protocol::Field]) -> Result<(), ErrorCode> {
+        let device_fields = to_device_fields(fee_fields);
+        self.finish_review_inner(&device_fields[..fee_fields.len()])
     }
 
-    #[inline]
-    pub fn output_index_as_field(&self) -> bool {
-        true
-    }
-
-    pub fn check_blind_signing(&mut self) -> Result<(), ErrorCode> {
-        if is_blind_signing_enabled() {
-            return Ok(());
-        }
+    fn warning_blind_signing_disabled(&mut self) {
         let go_to_settings = nbgl_review_warning(
             "This transaction cannot be clear-signed",
             "Enable blind signing in the settings to sign this transaction.",
@@ -142,11 +161,69 @@ impl TxReviewerInner {
         if go_to_settings {
             self.display_settings = true;
         }
-        Err(ErrorCode::BlindSigningDisabled)
     }
 
-    #[inline]
-    pub fn reset_display_settings(&mut self) {
-        self.display_settings = false;
+    fn warning_invalid_output(&mut self) {
+        // Both choices reject the tx
+        let _ = nbgl_review_warning(
+            "This transaction has an invalid output",
+            "An output amount is zero or below the dust amount, the network would reject it.",
+            "Reject transaction",
+            "Close",
+        );
+    }
+
+    fn warning_tx_rejected(&mut self, reason: &str) {
+        // Both choices close the warning, the tx has already been rejected
+        let _ = nbgl_review_warning(
+            "This transaction was rejected",
+            reason,
+            "Close",
+            "Back to home",
+        );
+    }
+
+    // Ask the user whether to review the outputs one by one or the totals of the outputs
+    fn ask_review_outputs(&mut self, _output_size: usize) -> Result<bool, ErrorCode> {
+        Ok(nbgl_review_warning(
+            "Review outputs one by one?",
+            "This transaction has many outputs, the totals per group and per token can be reviewed instead.",
+            "Review outputs",
+            "Show totals",
+        ))
+    }
+
+    // Ask the user whether to display the disassembled script
+    fn ask_show_script(&mut self) -> Result<bool, ErrorCode> {
+        Ok(nbgl_review_warning(
+            "Show script?",
+            "Review the instructions of the script before signing the transaction.",
+            "Show script",
+            "Skip",
+        ))
+    }
+
+    // Tell the user that only the script hash is reviewed, the user can still reject the tx
+    fn warning_script_too_large(&mut self) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning(
+            "Script too large",
+            "The script is too large to be displayed, only the script hash can be reviewed.",
+            "Continue",
+            "Reject",
+        );
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    fn review_script_page(
+        &mut self,
+        fields: &[protocol::Field],
+        _message: &str,
+    ) -> Result<(), ErrorCode> {
+        let device_fields = to_device_fields(fields);
+        self.continue_review(&device_fields[..fields.len()])
     }
 }
@@ -1,4 +1,4 @@
-import { Account, KeyType, addressFromPublicKey, binToHex, codec, encodeHexSignature, groupOfAddress } from '@alephium/web3'
+import { Account, KeyType, addressFromPublicKey, binToHex, bs58, codec, encodeHexSignature, groupOfAddress } from '@alephium/web3'
 import Transport, { StatusCodes } from '@ledgerhq/hw-transport'
 import * as serde from './serde'
 import { ec as EC } from 'elliptic'
@@ -13,11 +13,20 @@ export enum INS {
   GET_VERSION = 0x00,
   GET_PUBLIC_KEY = 0x01,
   SIGN_HASH = 0x02,
-  SIGN_TX = 0x03
+  SIGN_TX = 0x03,
+  ADDRESS_BOOK = 0x04,
+  GET_LAST_ERROR = 0x05
+}
+
+export interface DecodeErrorInfo {
+  code: number
+  offset: number
+  path: string
 }
 
 export const GROUP_NUM = 4
 export const HASH_LEN = 32
+export const MAX_ADDRESS_LABEL_LENGTH = 20
 
 export class AlephiumApp {
   readonly transport: Transport
@@ -71,19 +80,66 @@ export class AlephiumApp {
   }
 
   async signUnsignedTx(path: string, unsignedTx: Buffer): Promise<string> {
+    const response = await this.sendUnsignedTx(path, unsignedTx, false)
+    return decodeSignature(response)
+  }
+
+  // The app returns the tx ID before the signature, so that it can be checked against the explorer
+  async signUnsignedTxWithTxId(path: string, unsignedTx: Buffer): Promise<{ txId: string; signature: string }> {
+    const response = await this.sendUnsignedTx(path, unsignedTx, true)
+    return {
+      txId: binToHex(response.slice(0, HASH_LEN)),
+      signature: decodeSignature(response.slice(HASH_LEN))
+    }
+  }
+
+  private async sendUnsignedTx(path: string, unsignedTx: Buffer, returnTxId: boolean): Promise<Buffer> {
     console.log(`unsigned tx size: ${unsignedTx.length}`)
     const tokenMetadata = getTokenMetadata(unsignedTx)
     serde.checkTokenMetadata(tokenMetadata)
     const tokenMetadataFrames = encodeTokenMetadata(tokenMetadata)
-    const txFrames = encodeUnsignedTx(path, unsignedTx)
+    const txFrames = encodeUnsignedTx(path, unsignedTx, returnTxId)
     const allFrames = [...tokenMetadataFrames, ...txFrames]
 
     let response: Buffer | undefined = undefined
     for (const frame of allFrames) {
       response = await this.transport.send(CLA, INS.SIGN_TX, frame.p1, frame.p2, frame.data, [StatusCodes.OK])
     }
-    return decodeSignature(response!)
+    return response!
+  }
+
+  async saveAddress(address: string, label: string): Promise<void> {
+    if (label.length === 0 || label.length > MAX_ADDRESS_LABEL_LENGTH) {
+      throw new Error(`Invalid label length: ${label.length}`)
+    }
+    const data = Buffer.concat([encodeAddress(address), Buffer.from(label, 'ascii')])
+    await this.transport.send(CLA, INS.ADDRESS_BOOK, 0x00, 0x00, data, [StatusCodes.OK])
+  }
+
+  async deleteAddress(address: string): Promise<void> {
+    await this.transport.send(CLA, INS.ADDRESS_BOOK, 0x01, 0x00, encodeAddress(address), [StatusCodes.OK])
+  }
+
+  async getLastError(): Promise<DecodeErrorInfo | undefined> {
+    const response = await this.transport.send(CLA, INS.GET_LAST_ERROR, 0x00, 0x00)
+    // The response only contains the status word if no decoding error has occurred
+    if (response.length <= 2) {
+      return undefined
+    }
+    return {
+      code: response.readUInt16BE(0),
+      offset: response.readUInt32BE(2),
+      path: response.slice(6, response.length - 2).toString('ascii')
+    }
+  }
+}
+
+function encodeAddress(address: string): Buffer {
+  const bytes = Buffer.from(bs58.decode(address))
+  if (bytes.length !== HASH_LEN + 1 || (bytes[0] !== 0x00 && bytes[0] !== 0x02)) {
+    throw new Error(`Only P2PKH and P2SH addresses can be saved: ${address}`)
   }
+  return bytes
 }
 
 function getTokenMetadata(unsignedTx: Buffer): TokenMetadata[] {
@@ -66,15 +66,19 @@ export function encodeProofLength(length: number): Uint8Array {
   return buffer;
 }
 
-export function encodeUnsignedTx(path: string, unsignedTx: Buffer): Frame[] {
+// The first tx frame with `p2` = 2 requests the tx ID to be returned along with the signature
+export const RETURN_TX_ID_FLAG = 0x02
+
+export function encodeUnsignedTx(path: string, unsignedTx: Buffer, returnTxId = false): Frame[] {
   const encodedPath = serializePath(path)
+  const firstFrameP2 = returnTxId ? RETURN_TX_ID_FLAG : 0
   const firstFrameTxLength = MAX_PAYLOAD_SIZE - 20;
   if (firstFrameTxLength >= unsignedTx.length) {
-    return [{ p1: 1, p2: 0, data: Buffer.concat([encodedPath, unsignedTx]) }]
+    return [{ p1: 1, p2: firstFrameP2, data: Buffer.concat([encodedPath, unsignedTx]) }]
   }
 
   const firstFrameTxData = unsignedTx.slice(0, firstFrameTxLength)
-  const frames: Frame[] = [{ p1: 1, p2: 0, data: Buffer.concat([encodedPath, firstFrameTxData]) }]
+  const frames: Frame[] = [{ p1: 1, p2: firstFrameP2, data: Buffer.concat([encodedPath, firstFrameTxData]) }]
   let fromIndex = firstFrameTxLength
   while (fromIndex < unsignedTx.length) {
     const remain = unsignedTx.length - fromIndex
@@ -76,5 +76,11 @@ describe('TxEncoder', () => {
       { p1: 1, p2: 0, data: Buffer.concat([encodedPath, unsignedTx1.slice(0, MAX_PAYLOAD_SIZE - 20)]) },
       { p1: 1, p2: 1, data: unsignedTx1.slice( MAX_PAYLOAD_SIZE - 20) },
     ])
+
+    const frames2 = encodeUnsignedTx(path, unsignedTx1, true)
+    expect(frames2).toEqual([
+      { p1: 1, p2: 2, data: Buffer.concat([encodedPath, unsignedTx1.slice(0, MAX_PAYLOAD_SIZE - 20)]) },
+      { p1: 1, p2: 1, data: unsignedTx1.slice( MAX_PAYLOAD_SIZE - 20) },
+    ])
   })
 })
\ No newline at end of file
@@ -12,6 +12,9 @@ async function pressButton(button: 'left' | 'right' | 'both') {
   })
 }
 
+// The number of settings in the settings menu of the nano devices
+const SETTING_NUM = 8
+
 async function clickAndApprove(times: number) {
   for (let i = 0; i < times; i++) {
     await pressButton('right')
@@ -34,46 +37,56 @@ export enum OutputType {
   MultisigAndToken
 }
 
+// The clicks of each review page of an output, the keys of a multisig output are reviewed
+// on separate pages after the output page, the multisig address of the tests has 3 keys
 const NanosClickTable = new Map([
-  [OutputType.Base, 5],
-  [OutputType.Multisig, 10],
-  [OutputType.Nanos10, 10],
-  [OutputType.Nanos11, 11],
-  [OutputType.Token, 11],
-  [OutputType.BaseAndToken, 12],
-  [OutputType.MultisigAndToken, 16],
+  [OutputType.Base, [5]],
+  [OutputType.Multisig, [11, 4, 4, 4]],
+  [OutputType.Nanos10, [10]],
+  [OutputType.Nanos11, [11]],
+  [OutputType.Token, [11]],
+  [OutputType.BaseAndToken, [12]],
+  [OutputType.MultisigAndToken, [17, 4, 4, 4]],
 ])
 
 const NanospClickTable = new Map([
-  [OutputType.Base, 3],
-  [OutputType.Multisig, 5],
-  [OutputType.Token, 6],
-  [OutputType.BaseAndToken, 6],
-  [OutputType.MultisigAndToken, 8],
+  [OutputType.Base, [3]],
+  [OutputType.Multisig, [6, 2, 2, 2]],
+  [OutputType.Token, [6]],
+  [OutputType.BaseAndToken, [6]],
+  [OutputType.MultisigAndToken, [9, 2, 2, 2]],
 ])
 
+// The review pages of the stax and flex devices are displayed in one flow
 const StaxClickTable = new Map([
   [OutputType.Base, 1],
-  [OutputType.Multisig, 2],
+  [OutputType.Multisig, 5],
   [OutputType.Token, 2],
   [OutputType.BaseAndToken, 2],
-  [OutputType.MultisigAndToken, 2],
+  [OutputType.MultisigAndToken, 5],
 ])
 
 const FlexClickTable = new Map([
   [OutputType.Base, 1],
-  [OutputType.Multisig, 2],
+  [OutputType.Multisig, 5],
   [OutputType.Token, 2],
   [OutputType.BaseAndToken, 2],
-  [OutputType.MultisigAndToken, 3],
+  [OutputType.MultisigAndToken, 6],
 ])
 
-function getOutputClickSize(outputType: OutputType) {
+function getOutputClickSizes(outputType: OutputType) {
   const model = getModel()
   switch (model) {
     case 'nanos': return NanosClickTable.get(outputType)!
     case 'nanosp':
     case 'nanox': return NanospClickTable.get(outputType)!
+    default: throw new Error(`Unknown model ${model}`)
+  }
+}
+
+function getOutputTouchSize(outputType: OutputType) {
+  const model = getModel()
+  switch (model) {
     case 'stax': return StaxClickTable.get(outputType)!
     case 'flex': return FlexClickTable.get(outputType)!
     default: throw new Error(`Unknown model ${model}`)
@@ -87,7 +100,9 @@ async function click(outputs: OutputType[], hasExternalInputs: boolean) {
   }
 
   for (let index = 0; index < outputs.length; index += 1) {
-    await clickAndApprove(getOutputClickSize(outputs[index]))
+    for (const clickSize of getOutputClickSizes(outputs[index])) {
+      await clickAndApprove(clickSize)
+    }
   }
 
   await clickAndApprove(1) // fees
@@ -160,7 +175,7 @@ async function touch(outputs: OutputType[], hasExternalInputs: boolean) {
   await sleep(1000)
 
   for (let index = 0; index < outputs.length; index += 1) {
-    await _touch(getOutputClickSize(outputs[index]))
+    await _touch(getOutputTouchSize(outputs[index]))
   }
 
   await _touch(1, true) // fees
@@ -231,6 +246,56 @@ export async function skipBlindSigningWarning() {
   }
 }
 
+// Close the warning displayed when the app rejects the tx
+export async function skipTxRejectedWarning() {
+  if (!needToAutoApprove()) return
+  if (isStaxOrFlex()) {
+    await sleep(3000)
+    // The confirm button of the warning is at the same position as the blind signing warning
+    const closePos = getModel() === 'stax' ? STAX_GO_TO_SETTINGS : FLEX_GO_TO_SETTINGS
+    await touchPosition(closePos)
+  } else {
+    await sleep(1000)
+    await pressButton('both')
+  }
+}
+
+// Skip the prompt of showing the script, which is displayed for the script txs in expert mode
+export async function skipShowScript() {
+  if (!needToAutoApprove()) return
+  if (isStaxOrFlex()) {
+    await sleep(3000)
+    // The skip button of the prompt is at the same position as the blind signing warning
+    const skipPos = getModel() === 'stax' ? STAX_GO_TO_SETTINGS : FLEX_GO_TO_SETTINGS
+    await touchPosition(skipPos)
+  } else {
+    await clickAndApprove(2)
+  }
+}
+
+// Approve a script tx which only sends the change back to the device address,
+// the script hash is reviewed before the fees
+export async function approveScriptTx(expertMode: boolean = false) {
+  if (!needToAutoApprove()) return
+  await sleep(2000)
+  if (isStaxOrFlex()) {
+    await _touch(2) // the first review page and the script hash
+    if (expertMode) {
+      await skipShowScript()
+    }
+    await _touch(1, true) // fees
+    return
+  }
+
+  await clickAndApprove(isNanos() ? 5 : 3) // the script hash
+  if (expertMode) {
+    await skipShowScript()
+    await clickAndApprove(4) // fees, gas amount and gas price
+  } else {
+    await clickAndApprove(2) // fees
+  }
+}
+
 export async function staxFlexAcceptRisk() {
   if (!needToAutoApprove()) return
   await sleep(3000)
@@ -251,10 +316,22 @@ export async function enableBlindSigning() {
     await touchPosition(blindSettingPos)
     await touchPosition(settingsPos)
   } else {
-    await clickAndApprove(2)
+    await toggleNanoSetting(0) // the first setting is blind signing
   }
 }
 
+// Toggle the setting at the index of the settings menu of the nano devices
+async function toggleNanoSetting(index: number) {
+  await clickAndApprove(2) // open the settings
+  await clickAndApprove(index)
+  await clickAndApprove(SETTING_NUM - index) // the back page
+}
+
+export async function toggleNanoExpertMode() {
+  if (!needToAutoApprove()) return
+  await toggleNanoSetting(2)
+}
+
 export function getRandomInt(min: number, max: number) {
   min = Math.ceil(min)
   max = Math.floor(max)
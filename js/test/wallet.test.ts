@@ -3,7 +3,7 @@ import { ALPH_TOKEN_ID, Address, DUST_AMOUNT, NodeProvider, ONE_ALPH, binToHex,
 import { getSigner, mintToken, transfer } from '@alephium/web3-test'
 import { PrivateKeyWallet } from '@alephium/web3-wallet'
 import blake from 'blakejs'
-import { approveAddress, approveHash, approveTx, createTransport, enableBlindSigning, getRandomInt, isNanos, isStaxOrFlex, needToAutoApprove, OutputType, skipBlindSigningWarning, staxFlexAcceptRisk, staxFlexApproveOnce } from './utils'
+import { approveAddress, approveHash, approveScriptTx, approveTx, createTransport, enableBlindSigning, getRandomInt, isNanos, isStaxOrFlex, needToAutoApprove, OutputType, skipBlindSigningWarning, skipTxRejectedWarning, staxFlexAcceptRisk, staxFlexApproveOnce, toggleNanoExpertMode } from './utils'
 import { TokenMetadata } from '../src/types'
 import { randomBytes } from 'crypto'
 import { merkleTokens, tokenMerkleProofs } from '../src/merkle'
@@ -352,6 +352,7 @@ describe('ledger wallet', () => {
     const originalProof = tokenMerkleProofs[selectedToken.tokenId]
     const invalidProof = originalProof.slice(0, originalProof.length - 64)
     tokenMerkleProofs[selectedToken.tokenId] = invalidProof
+    setTimeout(() => skipTxRejectedWarning(), 1000)
     await expect(app.signUnsignedTx(path, Buffer.from(encodedUnsignedTx))).rejects.toThrow()
     tokenMerkleProofs[selectedToken.tokenId] = originalProof
 
@@ -387,6 +388,7 @@ describe('ledger wallet', () => {
     }
     const encodedUnsignedTx = codec.unsignedTxCodec.encodeApiUnsignedTx(unsignedTx)
     merkleTokens[tokenIndex] = { ...selectedToken, version: 1 }
+    setTimeout(() => skipTxRejectedWarning(), 1000)
     await expect(app.signUnsignedTx(path, Buffer.from(encodedUnsignedTx))).rejects.toThrow()
     merkleTokens[tokenIndex] = selectedToken
 
@@ -548,15 +550,20 @@ describe('ledger wallet', () => {
     await enableBlindSigning()
     if (needToAutoApprove()) {
       if (isStaxOrFlex()) {
-        staxFlexAcceptRisk().then(() => approveTx([]))
+        staxFlexAcceptRisk().then(() => approveScriptTx())
       } else {
-        approveTx([])
+        // the user is asked whether to show the script in expert mode
+        await toggleNanoExpertMode()
+        approveScriptTx(true)
       }
     } else {
       // waiting for blind signing setting to be enabled
       await sleep(20000)
     }
     const signature = await app.signUnsignedTx(path, Buffer.from(buildTxResult.unsignedTx, 'hex'))
+    if (needToAutoApprove() && !isStaxOrFlex()) {
+      await toggleNanoExpertMode()
+    }
     const submitResult = await nodeProvider.transactions.postTransactionsSubmit({
       unsignedTx: buildTxResult.unsignedTx,
       signature: signature
@@ -0,0 +1,307 @@
+use utils::base58::{base58_encode, base58_encode_inputs, limb_size, LimbStore};
+use utils::buffer::{Buffer, SkipTempData};
+use utils::decode::{new_decoder, Decoder};
+use utils::types::{LockupScript, UnlockScript, U16};
+use utils::{djb_hash, xor_bytes};
+
+use crate::error_code::ErrorCode;
+use crate::platform::{Blake2bHash, Hasher, KeyDerivation, RAW_PUBKEY_SIZE};
+
+const COMPRESSED_PUBKEY_SIZE: usize = 33;
+// The output size that fits the base58 P2PKH, P2SH and P2C addresses
+pub const HASH_ADDRESS_SIZE: usize = 46;
+// The number of address groups of the Alephium network
+pub const GROUP_NUM: u8 = 4;
+
+const P2PKH_TYPE: u8 = 0;
+const P2MPKH_TYPE: u8 = 1;
+const P2SH_TYPE: u8 = 2;
+const P2C_TYPE: u8 = 3;
+
+pub fn hash_of_public_key<H: Hasher>(pub_key: &[u8]) -> Blake2bHash {
+    assert!(pub_key.len() == RAW_PUBKEY_SIZE);
+    let mut compressed = [0_u8; COMPRESSED_PUBKEY_SIZE];
+    compressed[1..COMPRESSED_PUBKEY_SIZE].copy_from_slice(&pub_key[1..COMPRESSED_PUBKEY_SIZE]);
+    if pub_key.last().unwrap().is_multiple_of(2) {
+        compressed[0] = 0x02
+    } else {
+        compressed[0] = 0x03
+    }
+
+    H::hash(&compressed).unwrap()
+}
+
+// The address of a lockup script, which is compared by the lockup script type and hash
+// The base58 string is only rendered when it is displayed
+#[derive(Clone, PartialEq)]
+pub enum Address<'a> {
+    // The P2PKH, P2SH and P2C addresses
+    Hash {
+        lockup_type: u8,
+        hash: [u8; 32],
+    },
+    // The P2MPKH address, the lockup script does not keep the public key hashes,
+    // so the encoded lockup script is borrowed from the temp data of the decoder
+    MultiSig {
+        size: u16,
+        m: u16,
+        encoded: &'a [u8],
+    },
+}
+
+impl Address<'static> {
+    pub fn from_path<H: Hasher, K: KeyDerivation>(
+        keys: &K,
+        path: &[u32],
+    ) -> Result<Self, ErrorCode> {
+        let device_public_key = keys
+            .derive_pub_key(path)
+            .map_err(|_| ErrorCode::DerivingPublicKeyFailed)?;
+        Ok(Self::from_pub_key::<H>(&device_public_key))
+    }
+
+    pub fn from_pub_key<H: Hasher>(pub_key: &[u8]) -> Self {
+        Self::p2pkh(hash_of_public_key::<H>(pub_key))
+    }
+
+    pub fn p2pkh(public_key_hash: [u8; 32]) -> Self {
+        Self::Hash {
+            lockup_type: P2PKH_TYPE,
+            hash: public_key_hash,
+        }
+    }
+
+    pub fn p2sh(script_hash: [u8; 32]) -> Self {
+        Self::Hash {
+            lockup_type: P2SH_TYPE,
+            hash: script_hash,
+        }
+    }
+
+    // The address of the unlocked input, the P2SH decoder writes the revealed script to the temp data
+    // Returns None if the unlock script does not reveal the address
+    pub fn from_unlock_script<H: Hasher>(
+        unlock_script: &UnlockScript,
+        temp_data: &[u8],
+    ) -> Result<Option<Self>, ErrorCode> {
+        match unlock_script {
+            UnlockScript::P2PKH(public_key) => Ok(Some(Self::p2pkh(H::hash(&public_key.0)?))),
+            UnlockScript::P2SH(_) => Ok(Some(Self::p2sh(H::hash(temp_data)?))),
+            UnlockScript::P2MPKH(_) | UnlockScript::SameAsPrevious => Ok(None),
+            UnlockScript::Unknown => Err(ErrorCode::InternalError),
+        }
+    }
+}
+
+impl<'a> Address<'a> {
+    // The P2MPKH decoder writes the encoded lockup script to the temp data
+    pub fn from_lockup_script(
+        lockup_script: &LockupScript,
+        temp_data: &'a [u8],
+    ) -> Result<Self, ErrorCode> {
+        match lockup_script {
+            LockupScript::P2PKH(hash) | LockupScript::P2SH(hash) | LockupScript::P2C(hash) => {
+                Ok(Self::Hash {
+                    lockup_type: lockup_script.get_type(),
+                    hash: hash.0,
+                })
+            }
+            LockupScript::P2MPKH(multi_sig) => Ok(Self::MultiSig {
+                size: multi_sig.inner.size.inner,
+                m: multi_sig.inner.m.inner,
+                encoded: temp_data,
+            }),
+            LockupScript::Unknown => Err(ErrorCode::InternalError),
+        }
+    }
+
+    pub fn get_type(&self) -> u8 {
+        match self {
+            Self::Hash { lockup_type, .. } => *lockup_type,
+            Self::MultiSig { .. } => P2MPKH_TYPE,
+        }
+    }
+
+    // The hash of the P2PKH, P2SH and P2C addresses
+    pub fn hash(&self) -> Option<&[u8; 32]> {
+        match self {
+            Self::Hash { hash, .. } => Some(hash),
+            Self::MultiSig { .. } => None,
+        }
+    }
+
+    // The group of a contract address is decided by the last byte of the contract ID, and
+    // the other addresses by the script hint, a P2MPKH address belongs to the group of its first key
+    pub fn group(&self) -> Result<u8, ErrorCode> {
+        let hash = match self {
+            Self::Hash { lockup_type, hash } if *lockup_type == P2C_TYPE => {
+                return Ok(hash[hash.len() - 1] % GROUP_NUM)
+            }
+            Self::Hash { hash, .. } => hash,
+            Self::MultiSig { encoded, .. } => {
+                // Skip the lockup script type and the number of keys
+                let mut temp_data = SkipTempData;
+                let mut buffer = Buffer::new(&encoded[1..], &mut temp_data);
+                if new_decoder::<U16>().decode(&mut buffer)?.is_none() {
+                    return Err(ErrorCode::TxDecodingInvalidSize);
+                }
+                let from_index = 1 + buffer.get_index();
+                encoded
+                    .get(from_index..(from_index + 32))
+                    .ok_or(ErrorCode::TxDecodingInvalidSize)?
+            }
+        };
+        let script_hint = djb_hash(hash) | 1;
+        Ok(xor_bytes(script_hint) % GROUP_NUM)
+    }
+
+    // Write the base58 address to the output, the limbs on the stack only fit the P2PKH, P2SH and P2C addresses
+    pub fn render<'b>(&self, output: &'b mut [u8]) -> Result<&'b [u8], ErrorCode> {
+        let mut limbs = [0u32; limb_size(HASH_ADDRESS_SIZE)];
+        self.render_with_limbs(&mut limbs[..], output)
+    }
+
+    // The P2MPKH address grows with the number of keys, so the limbs are provided by the caller
+    pub fn render_with_limbs<'b, L: LimbStore + ?Sized>(
+        &self,
+        limbs: &mut L,
+        output: &'b mut [u8],
+    ) -> Result<&'b [u8], ErrorCode> {
+        let str_bytes = match self {
+            Self::Hash { lockup_type, hash } => {
+                base58_encode_inputs(&[&[*lockup_type], &hash[..]], limbs, output)
+            }
+            Self::MultiSig { encoded, .. } => base58_encode(encoded, limbs, output),
+        };
+        str_bytes.ok_or(ErrorCode::Overflow)
+    }
+}
+
+#[inline]
+pub fn to_base58_address<'a>(
+    prefix: u8,
+    hash: &[u8; 32],
+    output: &'a mut [u8],
+) -> Result<&'a [u8], ErrorCode> {
+    let mut limbs = [0u32; limb_size(HASH_ADDRESS_SIZE)];
+    if let Some(str_bytes) = base58_encode_inputs(&[&[prefix], &hash[..]], &mut limbs[..], output) {
+        Ok(str_bytes)
+    } else {
+        Err(ErrorCode::Overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{
+        hash_of_public_key, to_base58_address, Address, GROUP_NUM, HASH_ADDRESS_SIZE, P2C_TYPE,
+    };
+    use crate::mock::{MockHasher, MockKeys};
+    use crate::platform::{Hasher, KeyDerivation};
+    use std::vec;
+    use std::vec::Vec;
+    use utils::base58::limb_size;
+    use utils::buffer::Buffer;
+    use utils::decode::{new_decoder, Decoder};
+    use utils::types::{Hash, LockupScript, PublicKey, UnlockScript};
+
+    #[test]
+    fn test_compare_with_lockup_scripts() {
+        let pub_key = MockKeys.derive_pub_key(&[0; 5]).unwrap();
+        let device_address = Address::from_pub_key::<MockHasher>(&pub_key);
+        let hash = hash_of_public_key::<MockHasher>(&pub_key);
+
+        let p2pkh = LockupScript::P2PKH(Hash::from_bytes(hash));
+        let address = Address::from_lockup_script(&p2pkh, &[]).unwrap();
+        assert!(address == device_address);
+
+        let p2sh = LockupScript::P2SH(Hash::from_bytes(hash));
+        let address = Address::from_lockup_script(&p2sh, &[]).unwrap();
+        assert!(address != device_address);
+        assert_eq!(address.hash(), device_address.hash());
+
+        let mut expected = [0u8; HASH_ADDRESS_SIZE];
+        let mut output = [0u8; HASH_ADDRESS_SIZE];
+        assert_eq!(
+            address.render(&mut output).unwrap(),
+            to_base58_address(2u8, &hash, &mut expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_multi_sig_address() {
+        let mut bytes = Vec::from([1u8, 2u8]);
+        bytes.extend([3u8; 32]);
+        bytes.extend([4u8; 32]);
+        bytes.push(1u8);
+
+        let mut temp_data = Vec::new();
+        let mut decoder = new_decoder::<LockupScript>();
+        let mut buffer = Buffer::new(&bytes, &mut temp_data);
+        assert!(decoder.decode(&mut buffer).unwrap().is_some());
+        let address = Address::from_lockup_script(&decoder.inner, &temp_data).unwrap();
+        assert_eq!(address.get_type(), 1u8);
+        assert!(address != Address::p2pkh([3u8; 32]));
+
+        let mut output = [0u8; 128];
+        assert!(address.render(&mut output).is_err());
+        let mut limbs = vec![0u32; limb_size(output.len())];
+        let address_str = address
+            .render_with_limbs(&mut limbs[..], &mut output)
+            .unwrap();
+        let mut decoded = [0u8; 128];
+        let (lockup_script, decoded) =
+            LockupScript::from_address_str(address_str, &mut decoded).unwrap();
+        assert_eq!(decoded, bytes.as_slice());
+        assert!(Address::from_lockup_script(&lockup_script, decoded).unwrap() == address);
+    }
+
+    #[test]
+    fn test_group() {
+        let p2pkh = Address::p2pkh([3u8; 32]);
+        let group = p2pkh.group().unwrap();
+        assert!(group < GROUP_NUM);
+        assert_eq!(Address::p2sh([3u8; 32]).group().unwrap(), group);
+
+        // the group of a multisig address is the group of its first key
+        let mut bytes = Vec::from([1u8, 2u8]);
+        bytes.extend([3u8; 32]);
+        bytes.extend([4u8; 32]);
+        bytes.push(1u8);
+        let multisig = Address::MultiSig {
+            size: 2,
+            m: 1,
+            encoded: &bytes,
+        };
+        assert_eq!(multisig.group().unwrap(), group);
+        let truncated = Address::MultiSig {
+            size: 2,
+            m: 1,
+            encoded: &bytes[..20],
+        };
+        assert!(truncated.group().is_err());
+
+        let mut contract_id = [0u8; 32];
+        contract_id[31] = 6;
+        let p2c = Address::Hash {
+            lockup_type: P2C_TYPE,
+            hash: contract_id,
+        };
+        assert_eq!(p2c.group().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_from_unlock_script() {
+        let public_key = MockKeys::compressed_pub_key(&[0; 5]);
+        let unlock_script = UnlockScript::P2PKH(PublicKey::from_bytes(public_key));
+        let address = Address::from_unlock_script::<MockHasher>(&unlock_script, &[]).unwrap();
+        let expected = Address::p2pkh(MockHasher::hash(&public_key).unwrap());
+        assert!(address == Some(expected));
+
+        let address =
+            Address::from_unlock_script::<MockHasher>(&UnlockScript::SameAsPrevious, &[]).unwrap();
+        assert!(address.is_none());
+    }
+}
@@ -0,0 +1,121 @@
+use utils::decode::DecodeError;
+
+// The status words returned to the client, the common status words are the same as the Ledger SDK's `StatusWords`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u16)]
+pub enum ErrorCode {
+    Ok = 0x9000,
+    BadCla = 0x6E00,
+    BadIns = 0x6E01,
+    BadP1P2 = 0x6E02,
+    BadLen = 0x6E03,
+    UserCancelled = 0x6E04,
+    TxDecodingFailed = 0xE000,
+    TxSigningFailed = 0xE001,
+    Overflow = 0xE002,
+    HDPathDecodingFailed = 0xE003,
+    BlindSigningDisabled = 0xE004,
+    DerivingPublicKeyFailed = 0xE005,
+    InvalidTokenSize = 0xE006,
+    InvalidMetadataVersion = 0xE007,
+    InvalidTokenProofSize = 0xE008,
+    InvalidTokenMetadata = 0xE009,
+    InvalidAddressBookEntry = 0xE00A,
+    AddressBookFull = 0xE00B,
+    TxDecodingInvalidSize = 0xE00C,
+    TxDecodingInvalidData = 0xE00D,
+    TxDecodingOverflow = 0xE00E,
+    InvalidOutput = 0xE00F,
+    InvalidNetworkId = 0xE010,
+    HashSigningDisabled = 0xE011,
+    InternalError = 0xEF00,
+}
+
+impl ErrorCode {
+    // The reason displayed to the user when the app rejects the tx with this error, or None if the
+    // user rejects the tx, a warning has already been displayed, or the error is not raised by the tx signing
+    // The decoding errors are explained by the tx field that cannot be decoded if it is known, see `DecodeErrorInfo`
+    pub fn rejection_reason(&self) -> Option<&'static str> {
+        let reason = match self {
+            ErrorCode::BadLen => "Invalid command length",
+            ErrorCode::BadP1P2 => "Unexpected command",
+            ErrorCode::TxDecodingFailed => "Transaction decoding failed",
+            ErrorCode::TxSigningFailed => "Signing failed",
+            ErrorCode::Overflow => "Transaction too large to display",
+            ErrorCode::HDPathDecodingFailed => "Invalid derivation path",
+            ErrorCode::DerivingPublicKeyFailed => "Key derivation failed",
+            ErrorCode::InvalidTokenSize => "Too many tokens",
+            ErrorCode::InvalidMetadataVersion => "Unsupported token metadata version",
+            ErrorCode::InvalidTokenProofSize => "Invalid token proof size",
+            ErrorCode::InvalidTokenMetadata => "Invalid token metadata",
+            ErrorCode::TxDecodingInvalidSize => "Invalid transaction length",
+            ErrorCode::TxDecodingInvalidData => "Invalid transaction data",
+            ErrorCode::TxDecodingOverflow => "Transaction data too large",
+            ErrorCode::InvalidNetworkId => "Unsupported network",
+            ErrorCode::InternalError => "Internal error",
+            ErrorCode::UserCancelled
+            | ErrorCode::BlindSigningDisabled
+            | ErrorCode::InvalidOutput
+            | ErrorCode::Ok
+            | ErrorCode::BadCla
+            | ErrorCode::BadIns
+            | ErrorCode::InvalidAddressBookEntry
+            | ErrorCode::AddressBookFull
+            | ErrorCode::HashSigningDisabled => return None,
+        };
+        Some(reason)
+    }
+}
+
+impl From<DecodeError> for ErrorCode {
+    fn from(err: DecodeError) -> ErrorCode {
+        match err {
+            DecodeError::InvalidSize => ErrorCode::TxDecodingInvalidSize,
+            DecodeError::InvalidData => ErrorCode::TxDecodingInvalidData,
+            DecodeError::Overflow => ErrorCode::TxDecodingOverflow,
+            DecodeError::InternalError => ErrorCode::TxDecodingFailed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::ErrorCode;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_rejection_reasons_are_distinct() {
+        let codes = [
+            ErrorCode::BadLen,
+            ErrorCode::BadP1P2,
+            ErrorCode::TxDecodingFailed,
+            ErrorCode::TxSigningFailed,
+            ErrorCode::Overflow,
+            ErrorCode::HDPathDecodingFailed,
+            ErrorCode::DerivingPublicKeyFailed,
+            ErrorCode::InvalidTokenSize,
+            ErrorCode::InvalidMetadataVersion,
+            ErrorCode::InvalidTokenProofSize,
+            ErrorCode::InvalidTokenMetadata,
+            ErrorCode::TxDecodingInvalidSize,
+            ErrorCode::TxDecodingInvalidData,
+            ErrorCode::TxDecodingOverflow,
+            ErrorCode::InvalidNetworkId,
+            ErrorCode::InternalError,
+        ];
+        let mut reasons: Vec<&str> = codes
+            .iter()
+            .map(|code| code.rejection_reason().unwrap())
+            .collect();
+        reasons.sort();
+        reasons.dedup();
+        assert_eq!(reasons.len(), codes.len());
+
+        assert!(ErrorCode::UserCancelled.rejection_reason().is_none());
+        assert!(ErrorCode::BlindSigningDisabled.rejection_reason().is_none());
+        assert!(ErrorCode::InvalidOutput.rejection_reason().is_none());
+        assert!(ErrorCode::AddressBookFull.rejection_reason().is_none());
+    }
+}
@@ -0,0 +1,665 @@
+// Golden-file tests of the review screens
+// Each case signs a tx with the recording UI, and the displayed screens are compared with the file in `protocol/golden`.
+// After reviewing the changes of the screens, run `UPDATE_GOLDEN=1 cargo test` to update the files.
+extern crate std;
+
+use std::format;
+use std::string::String;
+use std::vec::Vec;
+use utils::{
+    encode::Encoder,
+    model::{
+        AssetOutputModel, InstrModel, LockupScriptModel, MethodModel, ScriptModel, TokenModel,
+        TxInputModel, UnlockScriptModel, UnsignedTxModel,
+    },
+    types::ByteVec,
+};
+
+use crate::{
+    error_code::ErrorCode,
+    mock::{
+        device_lockup_script, encode_tx, new_test_context, output, sign, u256, MockKeys,
+        MockStorage, MockUi, OwnedFields, TestToken, UiEvent, ALF_TOKEN, PACA_TOKEN, TEST_PATH,
+    },
+    sign_tx::handle_sign_tx,
+};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden");
+const ALPH: u128 = 1_000_000_000_000_000_000;
+
+fn render_fields(lines: &mut Vec<String>, screen: &str, fields: &OwnedFields) {
+    for (name, value) in fields {
+        lines.push(format!("{} | {} | {}", screen, name, value));
+    }
+}
+
+// Render the screens as lines of (screen, field name, value)
+fn render(events: &[UiEvent], result: &Result<(), ErrorCode>) -> String {
+    let mut lines = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        let screen_index = index + 1;
+        match event {
+            UiEvent::StartReview => lines.push(format!("{} start review", screen_index)),
+            UiEvent::ReviewFields(review_message, fields) => {
+                let screen = format!("{} review \"{}\"", screen_index, review_message);
+                render_fields(&mut lines, &screen, fields)
+            }
+            UiEvent::ReviewSelfTransfer(fields) => {
+                let screen = format!("{} review self-transfer", screen_index);
+                render_fields(&mut lines, &screen, fields)
+            }
+            UiEvent::ExternalInputsWarning => {
+                lines.push(format!("{} warning external inputs", screen_index))
+            }
+            UiEvent::FinishReview(fields) => {
+                let screen = format!("{} finish review", screen_index);
+                render_fields(&mut lines, &screen, fields)
+            }
+            UiEvent::BlindSigningDisabled => {
+                lines.push(format!("{} warning blind signing disabled", screen_index))
+            }
+            UiEvent::InvalidOutput => {
+                lines.push(format!("{} warning invalid output", screen_index))
+            }
+            UiEvent::TxRejected(reason) => {
+                lines.push(format!("{} warning tx rejected | {}", screen_index, reason))
+            }
+            UiEvent::AskReviewOutputs(output_size) => lines.push(format!(
+                "{} ask review {} outputs",
+                screen_index, output_size
+            )),
+            UiEvent::AskShowScript => lines.push(format!("{} ask show script", screen_index)),
+            UiEvent::ScriptTooLargeWarning => {
+                lines.push(format!("{} warning script too large", screen_index))
+            }
+            UiEvent::ReviewScriptPage(review_message, fields) => {
+                let screen = format!("{} review script \"{}\"", screen_index, review_message);
+                render_fields(&mut lines, &screen, fields)
+            }
+        }
+    }
+    match result {
+        Ok(()) => lines.push(String::from("signed")),
+        Err(code) => lines.push(format!("rejected with {:?}", code)),
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn check_golden(name: &str, actual: &str) {
+    let path = format!("{}/{}.golden", GOLDEN_DIR, name);
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "Golden file {} does not exist, run the tests with UPDATE_GOLDEN=1 to create it",
+            path
+        )
+    });
+    assert_eq!(
+        expected, actual,
+        "The review screens of {} have changed, run the tests with UPDATE_GOLDEN=1 to update the golden file after reviewing the changes",
+        name
+    );
+}
+
+struct GoldenCase {
+    name: &'static str,
+    tx: UnsignedTxModel,
+    // Overrides the encoded tx, so that the tx can be invalid
+    encoded_tx: Option<Vec<u8>>,
+    tokens: Vec<TestToken>,
+    storage: MockStorage,
+    ui: MockUi,
+}
+
+impl GoldenCase {
+    fn new(name: &'static str, tx: UnsignedTxModel) -> Self {
+        Self {
+            name,
+            tx,
+            encoded_tx: None,
+            tokens: Vec::new(),
+            storage: MockStorage::default(),
+            ui: MockUi::default(),
+        }
+    }
+
+    // Returns the rendered screens
+    fn run(self) -> String {
+        let (mut context, mut reviewer) = new_test_context(self.storage, self.ui);
+        let encoded_tx = self.encoded_tx.unwrap_or_else(|| encode_tx(&self.tx));
+        let result = sign(&mut context, &mut reviewer, &self.tokens, &encoded_tx);
+        let result = result.map(|signature| assert!(signature.is_some()));
+        let rendered = render(&reviewer.ui().events, &result);
+        check_golden(self.name, &rendered);
+        rendered
+    }
+}
+
+fn device_input(index: u8) -> TxInputModel {
+    TxInputModel {
+        hint: [index; 4],
+        key: [index; 32],
+        unlock_script: UnlockScriptModel::P2PKH(MockKeys::compressed_pub_key(&TEST_PATH)),
+    }
+}
+
+fn token(token: &TestToken, amount: u128) -> TokenModel {
+    TokenModel {
+        id: token.id_bytes(),
+        amount: u256(amount),
+    }
+}
+
+fn tx(fixed_outputs: Vec<AssetOutputModel>) -> UnsignedTxModel {
+    UnsignedTxModel {
+        version: 0,
+        network_id: 0,
+        script: None,
+        gas_amount: 20000,
+        gas_price: u256(100_000_000_000),
+        inputs: std::vec![device_input(0)],
+        fixed_outputs,
+    }
+}
+
+fn change_output() -> AssetOutputModel {
+    output(10 * ALPH, device_lockup_script(&TEST_PATH), Vec::new())
+}
+
+fn transfer_tx() -> UnsignedTxModel {
+    tx(std::vec![
+        output(
+            ALPH,
+            LockupScriptModel::P2PKH([1; 32]),
+            std::vec![token(&ALF_TOKEN, 1_500_000_000)],
+        ),
+        output(
+            ALPH / 10,
+            LockupScriptModel::P2PKH([2; 32]),
+            std::vec![token(&PACA_TOKEN, 42)],
+        ),
+        change_output(),
+    ])
+}
+
+#[test]
+fn test_golden_transfer_with_token_metadata() {
+    GoldenCase {
+        tokens: std::vec![ALF_TOKEN, PACA_TOKEN],
+        ..GoldenCase::new("transfer_with_token_metadata", transfer_tx())
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_transfer_without_token_metadata() {
+    GoldenCase::new("transfer_without_token_metadata", transfer_tx()).run();
+}
+
+#[test]
+fn test_golden_output_index_as_field() {
+    GoldenCase {
+        tokens: std::vec![ALF_TOKEN],
+        ui: MockUi {
+            output_index_as_field: true,
+            ..MockUi::default()
+        },
+        ..GoldenCase::new("output_index_as_field", transfer_tx())
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_alph_amounts() {
+    // outputs with less than the dust amount are rejected
+    let amounts = [
+        1_000_000_000_000_000,
+        1_000_000_000_000_001,
+        1_234_567_890_000_000_000,
+        1_000_000 * ALPH,
+    ];
+    let outputs = amounts
+        .iter()
+        .enumerate()
+        .map(|(index, amount)| {
+            output(
+                *amount,
+                LockupScriptModel::P2PKH([index as u8 + 1; 32]),
+                Vec::new(),
+            )
+        })
+        .collect();
+    GoldenCase::new("alph_amounts", tx(outputs)).run();
+}
+
+#[test]
+fn test_golden_multisig_and_p2sh_outputs() {
+    // the second key of the multisig address is the device key
+    let device_key_hash = match device_lockup_script(&TEST_PATH) {
+        LockupScriptModel::P2PKH(hash) => hash,
+        _ => unreachable!(),
+    };
+    let multisig = LockupScriptModel::P2MPKH {
+        public_key_hashes: std::vec![[1; 32], device_key_hash, [3; 32]],
+        m: 2,
+    };
+    let outputs = std::vec![
+        output(ALPH, multisig, Vec::new()),
+        output(2 * ALPH, LockupScriptModel::P2SH([4; 32]), Vec::new()),
+        change_output(),
+    ];
+    let rendered = GoldenCase::new("multisig_and_p2sh_outputs", tx(outputs)).run();
+    for hash in [[1; 32], device_key_hash, [3; 32]] {
+        assert!(rendered.contains(&LockupScriptModel::P2PKH(hash).to_address()));
+    }
+}
+
+#[test]
+fn test_golden_multisig_with_five_keys() {
+    // the encoded address is longer than 160 digits
+    let multisig = LockupScriptModel::P2MPKH {
+        public_key_hashes: (1..=5).map(|index| [index; 32]).collect(),
+        m: 3,
+    };
+    let address = multisig.to_address();
+    assert!(address.len() > 160);
+    let outputs = std::vec![output(ALPH, multisig, Vec::new()), change_output()];
+    let rendered = GoldenCase::new("multisig_with_five_keys", tx(outputs)).run();
+    assert!(rendered.contains(&address));
+}
+
+#[test]
+fn test_golden_address_book_label() {
+    let mut storage = MockStorage::default();
+    storage.address_book.push((0, [1; 32], b"Alice".to_vec()));
+    GoldenCase {
+        storage,
+        ..GoldenCase::new("address_book_label", transfer_tx())
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_self_transfer() {
+    GoldenCase::new("self_transfer", tx(std::vec![change_output()])).run();
+}
+
+#[test]
+fn test_golden_self_transfer_with_all_approve_fields() {
+    GoldenCase {
+        storage: MockStorage {
+            expert_mode_enabled: true,
+            tx_id_display_enabled: true,
+            ..MockStorage::default()
+        },
+        ..GoldenCase::new(
+            "self_transfer_with_all_approve_fields",
+            tx(std::vec![change_output()]),
+        )
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_external_inputs() {
+    let mut tx = transfer_tx();
+    tx.inputs.push(TxInputModel {
+        unlock_script: UnlockScriptModel::P2PKH([2; 33]),
+        ..device_input(1)
+    });
+    GoldenCase::new("external_inputs", tx).run();
+}
+
+#[test]
+fn test_golden_script_tx() {
+    let tx = UnsignedTxModel {
+        script: Some(ScriptModel {
+            methods: Vec::new(),
+        }),
+        ..transfer_tx()
+    };
+    GoldenCase {
+        storage: MockStorage {
+            blind_signing_enabled: true,
+            ..MockStorage::default()
+        },
+        ..GoldenCase::new("script_tx", tx.clone())
+    }
+    .run();
+    GoldenCase::new("script_tx_blind_signing_disabled", tx).run();
+}
+
+fn instr(code: u8, operands: Vec<u8>) -> InstrModel {
+    InstrModel { code, operands }
+}
+
+fn method(is_public: u8, asset_modifier: u8, instrs: Vec<InstrModel>) -> MethodModel {
+    MethodModel {
+        is_public,
+        asset_modifier,
+        args_length: 0,
+        locals_length: 0,
+        return_length: 0,
+        instrs,
+    }
+}
+
+#[test]
+fn test_golden_script_disassembly() {
+    let mut u256_const = Vec::new();
+    u256(ALPH).encode(&mut u256_const).unwrap();
+    let mut bytes_const = Vec::new();
+    ByteVec(&[0xde, 0xad, 0xbe, 0xef])
+        .encode(&mut bytes_const)
+        .unwrap();
+    let mut address_const = std::vec![0u8];
+    address_const.extend([1u8; 32]);
+    let script = ScriptModel {
+        methods: std::vec![
+            method(
+                1,
+                1,
+                std::vec![
+                    instr(19, u256_const),
+                    instr(20, bytes_const),
+                    instr(21, address_const),
+                    instr(3, Vec::new()),
+                    instr(212, std::vec![0x12, 0x34, 0x56, 0x78]),
+                    instr(24, Vec::new()),
+                ],
+            ),
+            method(0, 6, std::vec![instr(2, Vec::new())]),
+        ],
+    };
+    let tx = UnsignedTxModel {
+        script: Some(script),
+        ..transfer_tx()
+    };
+    GoldenCase {
+        storage: MockStorage {
+            blind_signing_enabled: true,
+            expert_mode_enabled: true,
+            ..MockStorage::default()
+        },
+        ui: MockUi {
+            show_script: true,
+            ..MockUi::default()
+        },
+        ..GoldenCase::new("script_disassembly", tx)
+    }
+    .run();
+}
+
+fn bytes_const_script(sizes: &[usize]) -> ScriptModel {
+    let instrs = sizes
+        .iter()
+        .map(|size| {
+            let mut bytes_const = Vec::new();
+            ByteVec(&std::vec![0xab; *size])
+                .encode(&mut bytes_const)
+                .unwrap();
+            instr(20, bytes_const)
+        })
+        .collect();
+    ScriptModel {
+        methods: std::vec![method(1, 0, instrs)],
+    }
+}
+
+fn expert_script_case(name: &'static str, script: ScriptModel) -> GoldenCase {
+    let tx = UnsignedTxModel {
+        script: Some(script),
+        ..transfer_tx()
+    };
+    GoldenCase {
+        storage: MockStorage {
+            blind_signing_enabled: true,
+            expert_mode_enabled: true,
+            ..MockStorage::default()
+        },
+        ui: MockUi {
+            show_script: true,
+            ..MockUi::default()
+        },
+        ..GoldenCase::new(name, tx)
+    }
+}
+
+// The script is recorded beyond the RAM of the swapping buffer, so the pages are rendered in the flash
+#[test]
+fn test_golden_script_disassembly_in_flash() {
+    let script = bytes_const_script(&[200, 200, 200, 200, 200]);
+    expert_script_case("script_disassembly_in_flash", script).run();
+}
+
+#[test]
+fn test_golden_script_too_large() {
+    let script = bytes_const_script(&[1000, 1000]);
+    expert_script_case("script_too_large", script).run();
+}
+
+// The change output is not the last output, it is only displayed if the outputs are reviewed one by one
+fn output_summary_tx() -> UnsignedTxModel {
+    let mut tx = transfer_tx();
+    tx.fixed_outputs.push(output(
+        2 * ALPH,
+        LockupScriptModel::P2PKH([5; 32]),
+        std::vec![token(&ALF_TOKEN, 500_000_000)],
+    ));
+    tx
+}
+
+#[test]
+fn test_golden_output_summary() {
+    GoldenCase {
+        tokens: std::vec![ALF_TOKEN],
+        storage: MockStorage {
+            output_summary_threshold: Some(2),
+            ..MockStorage::default()
+        },
+        ..GoldenCase::new("output_summary", output_summary_tx())
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_review_outputs_one_by_one() {
+    GoldenCase {
+        tokens: std::vec![ALF_TOKEN],
+        storage: MockStorage {
+            output_summary_threshold: Some(2),
+            ..MockStorage::default()
+        },
+        ui: MockUi {
+            review_outputs: true,
+            ..MockUi::default()
+        },
+        ..GoldenCase::new("review_outputs_one_by_one", output_summary_tx())
+    }
+    .run();
+}
+
+// Send the frames until the app rejects the tx
+fn check_rejected_frames(name: &str, frames: &[(u8, u8, Vec<u8>)]) {
+    let (mut context, mut reviewer) = new_test_context(MockStorage::default(), MockUi::default());
+    let mut result = Ok(());
+    for (p1, p2, data) in frames {
+        if let Err(code) = handle_sign_tx(*p1, *p2, data, &mut context, &mut reviewer) {
+            result = Err(code);
+            break;
+        }
+    }
+    assert!(result.is_err());
+    check_golden(name, &render(&reviewer.ui().events, &result));
+}
+
+#[test]
+fn test_golden_tx_rejected_frames() {
+    check_rejected_frames("tx_rejected_bad_len", &[(0, 0, Vec::new())]);
+    check_rejected_frames("tx_rejected_bad_p1_p2", &[(2, 0, std::vec![0])]);
+    check_rejected_frames("tx_rejected_too_many_tokens", &[(0, 0, std::vec![6])]);
+
+    let mut metadata = ALF_TOKEN.metadata();
+    metadata[0] = 1; // the first byte is the metadata version
+    let mut frame = std::vec![1];
+    frame.extend(&metadata);
+    frame.extend(0u16.to_be_bytes());
+    check_rejected_frames("tx_rejected_metadata_version", &[(0, 0, frame)]);
+
+    let mut frame = std::vec![1];
+    frame.extend(ALF_TOKEN.metadata());
+    frame.extend(31u16.to_be_bytes()); // the proof is a list of 32-byte hashes
+    frame.extend([0u8; 31]);
+    check_rejected_frames("tx_rejected_token_proof_size", &[(0, 0, frame)]);
+}
+
+#[test]
+fn test_golden_tx_rejected_invalid_token_metadata() {
+    // the decimals do not match the token list, so the proof is invalid
+    let token = TestToken {
+        decimals: 18,
+        ..ALF_TOKEN
+    };
+    GoldenCase {
+        tokens: std::vec![token],
+        ..GoldenCase::new("tx_rejected_invalid_token_metadata", transfer_tx())
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_tx_rejected_too_large_to_display() {
+    // the output summary has at most 8 distinct tokens
+    let outputs = (0..9)
+        .map(|index| {
+            output(
+                ALPH,
+                LockupScriptModel::P2PKH([1; 32]),
+                std::vec![TokenModel {
+                    id: [index; 32],
+                    amount: u256(1),
+                }],
+            )
+        })
+        .collect();
+    GoldenCase {
+        storage: MockStorage {
+            output_summary_threshold: Some(2),
+            ..MockStorage::default()
+        },
+        ..GoldenCase::new("tx_rejected_too_large_to_display", tx(outputs))
+    }
+    .run();
+}
+
+// Run the signing with an invalid encoded tx, which is built from the encoded tx without
+// inputs and outputs, the last two bytes are the sizes of the inputs and the outputs
+fn check_invalid_tx(name: &'static str, build: impl FnOnce(&mut Vec<u8>)) {
+    let empty_tx = UnsignedTxModel {
+        inputs: Vec::new(),
+        ..tx(Vec::new())
+    };
+    let mut encoded_tx = encode_tx(&empty_tx);
+    build(&mut encoded_tx);
+    GoldenCase {
+        encoded_tx: Some(encoded_tx),
+        ..GoldenCase::new(name, empty_tx)
+    }
+    .run();
+}
+
+// A compact integer prefix with more than 4 length bytes
+const INVALID_SIZE: u8 = 0xff;
+
+fn encode<T: Encoder>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.encode(&mut bytes).unwrap();
+    bytes
+}
+
+fn device_input_bytes() -> Vec<u8> {
+    let input = device_input(0);
+    let mut bytes = std::vec![1]; // the input size
+    bytes.extend(input.hint);
+    bytes.extend(input.key);
+    bytes
+}
+
+#[test]
+fn test_golden_tx_rejected_decoding_errors() {
+    check_invalid_tx("tx_rejected_unsupported_script", |bytes| {
+        bytes[2] = 2; // the script option flag
+    });
+    check_invalid_tx("tx_rejected_invalid_gas", |bytes| {
+        bytes.truncate(3); // the version, network ID and script option flag
+        bytes.push(INVALID_SIZE);
+    });
+    check_invalid_tx("tx_rejected_invalid_input_size", |bytes| {
+        bytes.truncate(bytes.len() - 2);
+        bytes.push(INVALID_SIZE);
+    });
+    check_invalid_tx("tx_rejected_unsupported_input_type", |bytes| {
+        bytes.truncate(bytes.len() - 2);
+        bytes.extend(device_input_bytes());
+        bytes.push(0xff); // the unlock script type
+    });
+
+    // the outputs are decoded after the device input
+    let outputs_prefix = |bytes: &mut Vec<u8>| {
+        bytes.truncate(bytes.len() - 2);
+        bytes.extend(device_input_bytes());
+        bytes.extend(encode(&device_input(0).unlock_script));
+    };
+    check_invalid_tx("tx_rejected_invalid_output_size", |bytes| {
+        outputs_prefix(bytes);
+        bytes.push(INVALID_SIZE);
+    });
+    check_invalid_tx("tx_rejected_invalid_output_amount", |bytes| {
+        outputs_prefix(bytes);
+        bytes.push(1); // the output size
+        bytes.push(INVALID_SIZE);
+    });
+    check_invalid_tx("tx_rejected_unsupported_output_type", |bytes| {
+        outputs_prefix(bytes);
+        bytes.push(1);
+        bytes.extend(encode(&u256(ALPH)));
+        bytes.push(0xff); // the lockup script type
+    });
+    check_invalid_tx("tx_rejected_invalid_output_tokens", |bytes| {
+        outputs_prefix(bytes);
+        bytes.push(1);
+        bytes.extend(encode(&u256(ALPH)));
+        bytes.extend(encode(&LockupScriptModel::P2PKH([1; 32])));
+        bytes.extend([0u8; 8]); // the lock time
+        bytes.push(INVALID_SIZE);
+    });
+}
+
+#[test]
+fn test_golden_tx_rejected_unsupported_network() {
+    GoldenCase {
+        storage: MockStorage {
+            allowed_network_id: Some(1),
+            ..MockStorage::default()
+        },
+        ..GoldenCase::new("tx_rejected_unsupported_network", transfer_tx())
+    }
+    .run();
+}
+
+#[test]
+fn test_golden_user_rejects_tx() {
+    GoldenCase {
+        ui: MockUi {
+            reject_at: Some(1),
+            ..MockUi::default()
+        },
+        ..GoldenCase::new("user_rejects_tx", transfer_tx())
+    }
+    .run();
+}
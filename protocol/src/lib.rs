@@ -0,0 +1,51 @@
+// The platform-agnostic part of the app: the tx signing flow, the tx review and the token verification
+// The Ledger devices implement the traits in `platform`, so the whole flow can also run on the host
+#![no_std]
+
+pub mod address;
+pub mod error_code;
+#[cfg(test)]
+mod golden;
+#[cfg(test)]
+pub mod mock;
+pub mod output_summary;
+pub mod platform;
+pub mod script_disassembler;
+pub mod sign_tx;
+pub mod sign_tx_context;
+pub mod swapping_buffer;
+pub mod token_verifier;
+pub mod tx_reviewer;
+
+use core::str::from_utf8;
+
+pub use error_code::ErrorCode;
+pub use platform::{Field, Hasher, KeyDerivation, NvmStorage, ReviewUi, Storage};
+pub use sign_tx_context::SignTxContext;
+pub use tx_reviewer::TxReviewer;
+
+// The `stax` target is only known when building the app for the device
+#[allow(unexpected_cfgs)]
+#[inline]
+pub fn bytes_to_string(bytes: &[u8]) -> Result<&str, ErrorCode> {
+    #[cfg(not(target_os = "stax"))]
+    {
+        match from_utf8(bytes) {
+            Ok(str) => Ok(str),
+            Err(_) => Err(ErrorCode::InternalError),
+        }
+    }
+
+    // We encountered a strange bug on Ledger Stax where Speculos exits immediately after
+    // loading the app (`syscall: os_sched_exit(0)[*] exit called (0)`), even though we haven't
+    // run any tests yet. This may be a bug in Speculos, although this bug might not be related
+    // to this function, the issue was resolved after changing to the following code,
+    // so we implemented this workaround to address the issue.
+    #[cfg(target_os = "stax")]
+    {
+        match from_utf8(bytes) {
+            Ok(_) => Ok(from_utf8(bytes).unwrap()),
+            Err(_) => Err(ErrorCode::InternalError),
+        }
+    }
+}
@@ -0,0 +1,438 @@
+// The mock platform used to run the signing flow in the tests
+extern crate std;
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use std::boxed::Box;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use utils::{
+    encode::Encoder,
+    model::{AssetOutputModel, LockupScriptModel, TokenModel, UnsignedTxModel},
+    types::{AmountFormat, U256},
+};
+
+use crate::{
+    error_code::ErrorCode,
+    platform::{
+        Blake2bHash, Field, Hasher, KeyDerivation, NvmStorage, ReviewUi, Storage, RAW_PUBKEY_SIZE,
+    },
+    sign_tx::handle_sign_tx,
+    sign_tx_context::{SignTxContext, SignedTx},
+    swapping_buffer::NVM_DATA_SIZE,
+    tx_reviewer::{TxReviewer, MAX_APPROVE_FIELD_SIZE},
+};
+
+pub struct MockHasher(Blake2b<U32>);
+
+impl Hasher for MockHasher {
+    fn new() -> Self {
+        Self(Blake2b::new())
+    }
+
+    fn reset(&mut self) {
+        Digest::reset(&mut self.0);
+    }
+
+    fn update(&mut self, input: &[u8]) -> Result<(), ErrorCode> {
+        Digest::update(&mut self.0, input);
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Blake2bHash, ErrorCode> {
+        Ok(self.0.finalize_reset().into())
+    }
+}
+
+fn path_hash(path: &[u32]) -> Blake2bHash {
+    let bytes: Vec<u8> = path.iter().flat_map(|v| v.to_be_bytes()).collect();
+    MockHasher::hash(&bytes).unwrap()
+}
+
+// The keys are derived from the hash of the path, they are not valid secp256k1 keys
+pub struct MockKeys;
+
+impl MockKeys {
+    pub fn compressed_pub_key(path: &[u32]) -> [u8; 33] {
+        let pub_key = MockKeys.derive_pub_key(path).unwrap();
+        let mut compressed = [0u8; 33];
+        compressed[0] = 0x02; // the `y` coordinate is always even
+        compressed[1..].copy_from_slice(&pub_key[1..33]);
+        compressed
+    }
+
+    // The signature is the tx id followed by the path hash
+    pub fn signature(path: &[u32], hash: &[u8]) -> Vec<u8> {
+        [hash, &path_hash(path)].concat()
+    }
+}
+
+impl KeyDerivation for MockKeys {
+    fn derive_pub_key(&self, path: &[u32]) -> Result<[u8; RAW_PUBKEY_SIZE], ErrorCode> {
+        let x = path_hash(path);
+        let mut y = MockHasher::hash(&x)?;
+        y[31] &= 0xfe;
+        let mut pub_key = [0u8; RAW_PUBKEY_SIZE];
+        pub_key[0] = 0x04;
+        pub_key[1..33].copy_from_slice(&x);
+        pub_key[33..].copy_from_slice(&y);
+        Ok(pub_key)
+    }
+
+    fn sign_hash(&self, path: &[u32], hash: &[u8]) -> Result<([u8; 72], u32, u32), ErrorCode> {
+        let signature = Self::signature(path, hash);
+        let mut bytes = [0u8; 72];
+        bytes[..signature.len()].copy_from_slice(&signature);
+        Ok((bytes, signature.len() as u32, 0))
+    }
+}
+
+pub struct MockNvm<const N: usize>([u8; N]);
+
+impl<const N: usize> MockNvm<N> {
+    // The device NVM data is static, so the mock is leaked to have the same lifetime
+    pub fn new_static() -> &'static mut Self {
+        Box::leak(Box::new(Self([0; N])))
+    }
+}
+
+impl<const N: usize> NvmStorage<N> for MockNvm<N> {
+    fn get_ref(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    fn write_from(&mut self, from_index: usize, bytes: &[u8]) -> Result<(), ErrorCode> {
+        if from_index + bytes.len() > N {
+            return Err(ErrorCode::Overflow);
+        }
+        self.0[from_index..(from_index + bytes.len())].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MockStorage {
+    pub blind_signing_enabled: bool,
+    pub tx_id_display_enabled: bool,
+    pub expert_mode_enabled: bool,
+    pub allowed_network_id: Option<u8>,
+    pub amount_format: AmountFormat,
+    pub output_summary_threshold: Option<usize>,
+    pub address_book: Vec<(u8, [u8; 32], Vec<u8>)>,
+}
+
+impl Storage for MockStorage {
+    fn is_blind_signing_enabled(&self) -> bool {
+        self.blind_signing_enabled
+    }
+
+    fn is_tx_id_display_enabled(&self) -> bool {
+        self.tx_id_display_enabled
+    }
+
+    fn is_expert_mode_enabled(&self) -> bool {
+        self.expert_mode_enabled
+    }
+
+    fn allowed_network_id(&self) -> Option<u8> {
+        self.allowed_network_id
+    }
+
+    fn amount_format(&self) -> AmountFormat {
+        self.amount_format
+    }
+
+    fn output_summary_threshold(&self) -> Option<usize> {
+        self.output_summary_threshold
+    }
+
+    fn find_address_label(&self, address_type: u8, hash: &[u8; 32]) -> Option<&[u8]> {
+        self.address_book
+            .iter()
+            .find(|(tpe, entry_hash, _)| *tpe == address_type && entry_hash == hash)
+            .map(|(_, _, label)| label.as_slice())
+    }
+}
+
+pub type OwnedFields = Vec<(String, String)>;
+
+// The screens displayed to the user, with the fields copied out of the reviewer buffer
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiEvent {
+    StartReview,
+    ReviewFields(String, OwnedFields),
+    ReviewSelfTransfer(OwnedFields),
+    ExternalInputsWarning,
+    FinishReview(OwnedFields),
+    BlindSigningDisabled,
+    InvalidOutput,
+    TxRejected(String),
+    AskReviewOutputs(usize),
+    AskShowScript,
+    ScriptTooLargeWarning,
+    ReviewScriptPage(String, OwnedFields),
+}
+
+#[derive(Default)]
+pub struct MockUi {
+    pub events: Vec<UiEvent>,
+    pub is_tx_execute_script: bool,
+    // Nbgl displays the output index as a field, bagl displays it in the review message
+    pub output_index_as_field: bool,
+    // The user rejects the tx at the screen with this event index
+    pub reject_at: Option<usize>,
+    // Whether the user chooses to review the outputs one by one instead of the output summary
+    pub review_outputs: bool,
+    // Whether the user chooses to display the disassembled script
+    pub show_script: bool,
+}
+
+impl MockUi {
+    fn record(&mut self, event: UiEvent) -> Result<(), ErrorCode> {
+        let rejected = self.reject_at == Some(self.events.len());
+        self.events.push(event);
+        if rejected {
+            Err(ErrorCode::UserCancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub fn to_owned_fields(fields: &[Field]) -> OwnedFields {
+    fields
+        .iter()
+        .map(|field| (field.name.to_string(), field.value.to_string()))
+        .collect()
+}
+
+impl ReviewUi for MockUi {
+    fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
+        self.is_tx_execute_script = is_tx_execute_script;
+    }
+
+    // The recorded events are kept so that they can be checked after the tx is signed
+    fn reset(&mut self) {
+        self.is_tx_execute_script = false;
+    }
+
+    fn output_index_as_field(&self) -> bool {
+        self.output_index_as_field
+    }
+
+    fn start_review(&mut self) -> Result<(), ErrorCode> {
+        self.record(UiEvent::StartReview)
+    }
+
+    fn review_fields(&mut self, fields: &[Field], review_message: &str) -> Result<(), ErrorCode> {
+        self.record(UiEvent::ReviewFields(
+            review_message.to_string(),
+            to_owned_fields(fields),
+        ))
+    }
+
+    fn review_self_transfer(&mut self, fields: &[Field]) -> Result<(), ErrorCode> {
+        assert!(fields.len() <= MAX_APPROVE_FIELD_SIZE);
+        self.record(UiEvent::ReviewSelfTransfer(to_owned_fields(fields)))
+    }
+
+    fn warning_external_inputs(&mut self) -> Result<(), ErrorCode> {
+        self.record(UiEvent::ExternalInputsWarning)
+    }
+
+    fn finish_review(&mut self, fields: &[Field]) -> Result<(), ErrorCode> {
+        self.record(UiEvent::FinishReview(to_owned_fields(fields)))
+    }
+
+    fn warning_blind_signing_disabled(&mut self) {
+        self.events.push(UiEvent::BlindSigningDisabled);
+    }
+
+    fn warning_invalid_output(&mut self) {
+        self.events.push(UiEvent::InvalidOutput);
+    }
+
+    fn warning_tx_rejected(&mut self, reason: &str) {
+        self.events.push(UiEvent::TxRejected(reason.to_string()));
+    }
+
+    fn ask_review_outputs(&mut self, output_size: usize) -> Result<bool, ErrorCode> {
+        self.record(UiEvent::AskReviewOutputs(output_size))?;
+        Ok(self.review_outputs)
+    }
+
+    fn ask_show_script(&mut self) -> Result<bool, ErrorCode> {
+        self.record(UiEvent::AskShowScript)?;
+        Ok(self.show_script)
+    }
+
+    fn warning_script_too_large(&mut self) -> Result<(), ErrorCode> {
+        self.record(UiEvent::ScriptTooLargeWarning)
+    }
+
+    fn review_script_page(
+        &mut self,
+        fields: &[Field],
+        review_message: &str,
+    ) -> Result<(), ErrorCode> {
+        self.record(UiEvent::ReviewScriptPage(
+            review_message.to_string(),
+            to_owned_fields(fields),
+        ))
+    }
+}
+
+pub type TestContext = SignTxContext<MockHasher, MockKeys, MockNvm<NVM_DATA_SIZE>>;
+pub type TestReviewer = TxReviewer<MockHasher, MockUi, MockStorage, MockNvm<NVM_DATA_SIZE>>;
+pub type SignResult = Result<Option<SignedTx>, ErrorCode>;
+
+pub const MAX_FRAME_SIZE: usize = 255;
+pub const TEST_PATH: [u32; 5] = [0x8000002c, 0x80000469, 0x80000000, 0, 0];
+
+// A token from the token list, with the merkle proof from `js/merkle-tree/proofs.json`
+#[derive(Clone, Copy)]
+pub struct TestToken {
+    pub id: &'static str,
+    pub symbol: &'static str,
+    pub decimals: u8,
+    pub proof: &'static str,
+}
+
+pub const ALF_TOKEN: TestToken = TestToken {
+    id: "66da610efb5129c062e88e5fd65fe810f31efd1597021b2edf887a4360fa0800",
+    symbol: "ALF",
+    decimals: 9,
+    proof: "433dcb50939614f577c195f0f2b31dc4816ad2a222596f8fb61989e3f028cbf7d44c8ad103de615f757e2f1fbfb50a500c205f85997bfa5e254220797ff985af13f674f605befab344528a171d78a450dfc57604f4f4160d906c4c6677a0dffb2f258277595f73fea7f0287d013f8140c8f280bd32de39b348afec71d9bbe08d31a36b09a85ffa234df2e3a12d7389ef34fe33c1879dd4d1a080eeb0ba0084f9d3b337ffee082c8dbd7c99be68ec22ddeeaa021433aaed6eda7d34cb2ca585e0123b36dd6e280859ba569c6418a19e7af8640756c2e6edba5265a5fb84a74b7becc5adf593f6de7af52c45fdce3b3fca5162831b1115bb3e34e18bffc46d439b",
+};
+
+pub const PACA_TOKEN: TestToken = TestToken {
+    id: "b2d71c116408ae47b931482a440f675dc9ea64453db24ee931dacd578cae9002",
+    symbol: "PACA",
+    decimals: 0,
+    proof: "d69d694a51bf16b8b85f9c99f463b277fa03af68afe429a1610b76839ba079d0d44c8ad103de615f757e2f1fbfb50a500c205f85997bfa5e254220797ff985af13f674f605befab344528a171d78a450dfc57604f4f4160d906c4c6677a0dffb2f258277595f73fea7f0287d013f8140c8f280bd32de39b348afec71d9bbe08d31a36b09a85ffa234df2e3a12d7389ef34fe33c1879dd4d1a080eeb0ba0084f9d3b337ffee082c8dbd7c99be68ec22ddeeaa021433aaed6eda7d34cb2ca585e0123b36dd6e280859ba569c6418a19e7af8640756c2e6edba5265a5fb84a74b7becc5adf593f6de7af52c45fdce3b3fca5162831b1115bb3e34e18bffc46d439b",
+};
+
+impl TestToken {
+    pub fn id_bytes(&self) -> [u8; 32] {
+        hex_to_bytes(self.id).try_into().unwrap()
+    }
+
+    // The metadata is the version, the token id, the symbol and the decimals
+    pub fn metadata(&self) -> Vec<u8> {
+        let mut symbol = [0u8; 12];
+        symbol[..self.symbol.len()].copy_from_slice(self.symbol.as_bytes());
+        let mut metadata = std::vec![0u8];
+        metadata.extend(self.id_bytes());
+        metadata.extend(symbol);
+        metadata.push(self.decimals);
+        metadata
+    }
+}
+
+pub fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// Encode the value with the compact encoding of `U256`
+pub fn u256(value: u128) -> U256 {
+    let encoded = if value < 0x40 {
+        std::vec![value as u8]
+    } else if value < 0x4000 {
+        ((value as u16) | 0x4000).to_be_bytes().to_vec()
+    } else if value < 0x4000_0000 {
+        ((value as u32) | 0x8000_0000).to_be_bytes().to_vec()
+    } else {
+        let bytes = value.to_be_bytes();
+        let leading_zeros = bytes.iter().take_while(|b| **b == 0).count().min(12);
+        let mut encoded = std::vec![0xc0 + (12 - leading_zeros) as u8];
+        encoded.extend_from_slice(&bytes[leading_zeros..]);
+        encoded
+    };
+    U256::from_encoded_bytes(&encoded)
+}
+
+pub fn device_lockup_script(path: &[u32]) -> LockupScriptModel {
+    let pub_key = MockKeys::compressed_pub_key(path);
+    LockupScriptModel::P2PKH(MockHasher::hash(&pub_key).unwrap())
+}
+
+pub fn output(
+    amount: u128,
+    lockup_script: LockupScriptModel,
+    tokens: Vec<TokenModel>,
+) -> AssetOutputModel {
+    AssetOutputModel {
+        amount: u256(amount),
+        lockup_script,
+        lock_time: 0,
+        tokens,
+        additional_data: Vec::new(),
+    }
+}
+
+pub fn encode_tx(tx: &UnsignedTxModel) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    tx.encode(&mut bytes).unwrap();
+    bytes
+}
+
+pub fn new_test_context(storage: MockStorage, ui: MockUi) -> (TestContext, TestReviewer) {
+    let context = SignTxContext::new(MockKeys, MockNvm::new_static());
+    let reviewer = TxReviewer::new(ui, storage, MockNvm::new_static());
+    (context, reviewer)
+}
+
+// Send the token metadata frames and the tx frames the same way as the JS SDK
+pub fn sign(
+    context: &mut TestContext,
+    reviewer: &mut TestReviewer,
+    tokens: &[TestToken],
+    tx: &[u8],
+) -> SignResult {
+    sign_with_first_tx_p2(context, reviewer, tokens, tx, 0)
+}
+
+// The `p2` of the first tx frame requests the tx ID along with the signature
+pub fn sign_with_first_tx_p2(
+    context: &mut TestContext,
+    reviewer: &mut TestReviewer,
+    tokens: &[TestToken],
+    tx: &[u8],
+    first_tx_p2: u8,
+) -> SignResult {
+    if tokens.is_empty() {
+        assert_eq!(handle_sign_tx(0, 0, &[0], context, reviewer)?, None);
+    }
+    for (index, token) in tokens.iter().enumerate() {
+        let proof = hex_to_bytes(token.proof);
+        let mut frame = if index == 0 {
+            std::vec![tokens.len() as u8]
+        } else {
+            Vec::new()
+        };
+        frame.extend(token.metadata());
+        frame.extend((proof.len() as u16).to_be_bytes());
+        let proof_prefix_size = (MAX_FRAME_SIZE - frame.len()) / 32 * 32;
+        frame.extend(&proof[..proof_prefix_size]);
+        let p2 = if index == 0 { 0 } else { 1 };
+        assert_eq!(handle_sign_tx(0, p2, &frame, context, reviewer)?, None);
+        for chunk in proof[proof_prefix_size..].chunks(MAX_FRAME_SIZE / 32 * 32) {
+            assert_eq!(handle_sign_tx(0, 2, chunk, context, reviewer)?, None);
+        }
+    }
+
+    let mut first_frame: Vec<u8> = TEST_PATH.iter().flat_map(|v| v.to_be_bytes()).collect();
+    let first_chunk_size = MAX_FRAME_SIZE - first_frame.len();
+    first_frame.extend(&tx[..first_chunk_size.min(tx.len())]);
+    let mut result = handle_sign_tx(1, first_tx_p2, &first_frame, context, reviewer)?;
+    if tx.len() > first_chunk_size {
+        for chunk in tx[first_chunk_size..].chunks(MAX_FRAME_SIZE) {
+            assert_eq!(result, None);
+            result = handle_sign_tx(1, 1, chunk, context, reviewer)?;
+        }
+    }
+    Ok(result)
+}
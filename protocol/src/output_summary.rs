@@ -0,0 +1,64 @@
+use utils::types::AssetOutput;
+
+use crate::{address::GROUP_NUM, error_code::ErrorCode};
+
+// The tx is rejected if its outputs have more distinct tokens in the summary mode
+pub const MAX_SUMMARY_TOKEN_SIZE: usize = 8;
+
+// `OutputSummary` sums up the outputs when the user chooses not to review the outputs one by one
+// The outputs are streamed, so the totals are reviewed after the last output is decoded
+// The ALPH amounts are summed up by the group of the recipient, and the token amounts by the token ID
+#[derive(Default)]
+pub struct OutputSummary {
+    output_size: usize,
+    alph_amounts: [u128; GROUP_NUM as usize],
+    tokens: [([u8; 32], u128); MAX_SUMMARY_TOKEN_SIZE],
+    token_size: usize,
+}
+
+impl OutputSummary {
+    // Add an output that is not sent to the device address
+    pub fn add_output(&mut self, group: u8, output: &AssetOutput) -> Result<(), ErrorCode> {
+        let alph_amount = output.amount.to_u128().ok_or(ErrorCode::Overflow)?;
+        let total = &mut self.alph_amounts[group as usize];
+        *total = total.checked_add(alph_amount).ok_or(ErrorCode::Overflow)?;
+
+        // Asset output has at most one token
+        if let Some(token) = output.tokens.get_current_item() {
+            let token_amount = token.amount.to_u128().ok_or(ErrorCode::Overflow)?;
+            let index = match self.tokens[..self.token_size]
+                .iter()
+                .position(|(id, _)| *id == token.id.0)
+            {
+                Some(index) => index,
+                None if self.token_size < MAX_SUMMARY_TOKEN_SIZE => {
+                    self.tokens[self.token_size] = (token.id.0, 0);
+                    self.token_size += 1;
+                    self.token_size - 1
+                }
+                None => return Err(ErrorCode::Overflow),
+            };
+            let total = &mut self.tokens[index].1;
+            *total = total.checked_add(token_amount).ok_or(ErrorCode::Overflow)?;
+        }
+        self.output_size += 1;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    // The total ALPH amount sent to each group
+    #[inline]
+    pub fn alph_amounts(&self) -> &[u128] {
+        &self.alph_amounts
+    }
+
+    // The token IDs and the total amounts, in the order of the first output of each token
+    #[inline]
+    pub fn tokens(&self) -> &[([u8; 32], u128)] {
+        &self.tokens[..self.token_size]
+    }
+}
@@ -0,0 +1,122 @@
+// The traits implemented by the platform running the protocol, i.e. the Ledger devices or the mocks in the tests
+use crate::error_code::ErrorCode;
+use utils::types::AmountFormat;
+
+pub const BLAKE2B_HASH_SIZE: usize = 32;
+pub type Blake2bHash = [u8; BLAKE2B_HASH_SIZE];
+pub const RAW_PUBKEY_SIZE: usize = 65;
+
+// Blake2b hasher with 32 bytes output
+pub trait Hasher: Sized {
+    fn new() -> Self;
+
+    fn reset(&mut self);
+
+    fn update(&mut self, input: &[u8]) -> Result<(), ErrorCode>;
+
+    fn finalize(&mut self) -> Result<Blake2bHash, ErrorCode>;
+
+    fn hash(input: &[u8]) -> Result<Blake2bHash, ErrorCode> {
+        let mut hasher = Self::new();
+        hasher.update(input)?;
+        hasher.finalize()
+    }
+}
+
+// Derive the secp256k1 keys from the BIP32 path
+pub trait KeyDerivation {
+    // Returns the uncompressed public key
+    fn derive_pub_key(&self, path: &[u32]) -> Result<[u8; RAW_PUBKEY_SIZE], ErrorCode>;
+
+    // Returns the DER encoded signature, the signature length and the parity of the `R` point
+    fn sign_hash(&self, path: &[u32], hash: &[u8]) -> Result<([u8; 72], u32, u32), ErrorCode>;
+}
+
+// The NVM pages used by the `SwappingBuffer` in case RAM is not enough
+pub trait NvmStorage<const N: usize> {
+    fn get_ref(&self) -> &[u8; N];
+
+    fn write_from(&mut self, from_index: usize, bytes: &[u8]) -> Result<(), ErrorCode>;
+}
+
+// The user data persisted on the device
+pub trait Storage {
+    fn is_blind_signing_enabled(&self) -> bool;
+
+    // Whether the tx ID is displayed on the approval screen
+    fn is_tx_id_display_enabled(&self) -> bool;
+
+    // Whether the gas amount and gas price are displayed on the approval screen
+    fn is_expert_mode_enabled(&self) -> bool;
+
+    // The only network ID that the user allows to sign for, or None if any network is allowed
+    fn allowed_network_id(&self) -> Option<u8>;
+
+    // How the ALPH and token amounts are displayed
+    fn amount_format(&self) -> AmountFormat;
+
+    // The user can choose to review the totals instead of each output if the tx has more outputs
+    // than the threshold, or None if the outputs are always reviewed one by one
+    fn output_summary_threshold(&self) -> Option<usize>;
+
+    // Returns the label if the address has been saved in the address book
+    fn find_address_label(&self, address_type: u8, hash: &[u8; 32]) -> Option<&[u8]>;
+}
+
+// A tx detail displayed to the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Field<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+}
+
+// Different Ledger devices use different UI libraries, so the `TxReviewer` displays
+// the tx details through this trait, which is implemented for Nanos/Nanosp/Nanox and Stax/Flex.
+// The methods return `ErrorCode::UserCancelled` if the user rejects the tx
+pub trait ReviewUi {
+    fn set_tx_execute_script(&mut self, is_tx_execute_script: bool);
+
+    fn reset(&mut self);
+
+    // Whether the output index is displayed as a field or in the review message
+    fn output_index_as_field(&self) -> bool;
+
+    // Start review tx details
+    fn start_review(&mut self) -> Result<(), ErrorCode>;
+
+    fn review_fields(&mut self, fields: &[Field], review_message: &str) -> Result<(), ErrorCode>;
+
+    // Review transfer that sends to self, the fields are the fees and optionally the tx ID
+    fn review_self_transfer(&mut self, fields: &[Field]) -> Result<(), ErrorCode>;
+
+    // Review the warning for external inputs, i.e. inputs that are not from the device address
+    fn warning_external_inputs(&mut self) -> Result<(), ErrorCode>;
+
+    // The fields are the fees and optionally the tx ID
+    fn finish_review(&mut self, fields: &[Field]) -> Result<(), ErrorCode>;
+
+    // Tell the user that blind signing must be enabled to sign the tx
+    fn warning_blind_signing_disabled(&mut self);
+
+    // Tell the user that the tx is rejected because an output has a zero or dust amount
+    fn warning_invalid_output(&mut self);
+
+    // Tell the user why the tx is rejected, e.g. the tx data or the token metadata is invalid
+    fn warning_tx_rejected(&mut self, reason: &str);
+
+    // Ask the user whether to review the outputs one by one, the totals of the outputs are reviewed otherwise
+    fn ask_review_outputs(&mut self, output_size: usize) -> Result<bool, ErrorCode>;
+
+    // Ask the user whether to display the disassembled script of a blind-signed tx
+    fn ask_show_script(&mut self) -> Result<bool, ErrorCode>;
+
+    // Tell the user that the script is too large to be displayed, only the script hash is reviewed
+    fn warning_script_too_large(&mut self) -> Result<(), ErrorCode>;
+
+    // Review a page of the disassembled script
+    fn review_script_page(
+        &mut self,
+        fields: &[Field],
+        review_message: &str,
+    ) -> Result<(), ErrorCode>;
+}
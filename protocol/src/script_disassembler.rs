@@ -0,0 +1,335 @@
+use utils::{
+    base58::{base58_encode, limb_size},
+    buffer::{Buffer, SkipTempData},
+    decode::{new_decoder, Decoder, RawDecoder},
+    types::{Byte, Instr, I32, U16},
+};
+
+use crate::{
+    bytes_to_string,
+    error_code::ErrorCode,
+    platform::{Field, NvmStorage, ReviewUi},
+    swapping_buffer::{SwappingBuffer, NVM_PAGE_SIZE},
+};
+
+pub const SCRIPT_PAGE_SIZE: usize = 4;
+const SCRIPT_LINE_SIZE: usize = 96;
+const SCRIPT_NAME_SIZE: usize = 16;
+// The buffer size needed to render a page after the script, including the alignment to the next flash page
+pub const SCRIPT_PAGE_BUFFER_SIZE: usize =
+    NVM_PAGE_SIZE + SCRIPT_PAGE_SIZE * (SCRIPT_NAME_SIZE + SCRIPT_LINE_SIZE);
+const TRUNCATED_SUFFIX: &[u8] = b"...";
+const HEX_CHARS: &[u8] = b"0123456789abcdef";
+
+pub enum ScriptItem<'a> {
+    Method {
+        index: usize,
+        is_public: bool,
+        asset_modifier: u8,
+    },
+    Instr {
+        index: usize,
+        instr: Instr,
+        encoded: &'a [u8],
+    },
+}
+
+// The tx decoder only keeps the current method and instruction of the script, so the encoded script
+// is recorded when decoding the tx, and the disassembler decodes it again item by item for display
+// The script is passed to each call, so that the buffer of the script can be written between the calls
+#[derive(Default)]
+pub struct ScriptDisassembler {
+    offset: usize,
+    method_size: Option<usize>,
+    method_index: usize,
+    instr_size: usize,
+    instr_index: usize,
+    is_complete: bool,
+}
+
+impl ScriptDisassembler {
+    // Returns the decoded value and the encoded bytes
+    fn decode<'a, T: Default + RawDecoder>(
+        &mut self,
+        bytes: &'a [u8],
+    ) -> Result<(T, &'a [u8]), ErrorCode> {
+        let mut temp_data = SkipTempData;
+        let mut buffer = Buffer::new(&bytes[self.offset..], &mut temp_data);
+        let mut decoder = new_decoder::<T>();
+        if decoder.decode(&mut buffer)?.is_none() {
+            return Err(ErrorCode::TxDecodingInvalidSize);
+        }
+        let from_index = self.offset;
+        self.offset += buffer.get_index();
+        Ok((decoder.inner, &bytes[from_index..self.offset]))
+    }
+
+    // Returns the next method header or instruction, `None` if all the items have been disassembled
+    pub fn next_item<'a>(&mut self, bytes: &'a [u8]) -> Result<Option<ScriptItem<'a>>, ErrorCode> {
+        if self.is_complete {
+            return Ok(None);
+        }
+        let method_size = match self.method_size {
+            Some(size) => size,
+            None => {
+                let size = self.decode::<U16>(bytes)?.0.inner as usize;
+                self.method_size = Some(size);
+                size
+            }
+        };
+
+        if self.instr_index < self.instr_size {
+            let (instr, encoded) = self.decode::<Instr>(bytes)?;
+            // The following bytes cannot be decoded after an unknown instruction
+            self.is_complete = matches!(instr, Instr::Unknown);
+            let index = self.instr_index;
+            self.instr_index += 1;
+            return Ok(Some(ScriptItem::Instr {
+                index,
+                instr,
+                encoded,
+            }));
+        }
+
+        if self.method_index == method_size {
+            self.is_complete = true;
+            return Ok(None);
+        }
+        let (is_public, _) = self.decode::<Byte>(bytes)?;
+        let (asset_modifier, _) = self.decode::<Byte>(bytes)?;
+        self.decode::<U16>(bytes)?; // args length
+        self.decode::<U16>(bytes)?; // locals length
+        self.decode::<U16>(bytes)?; // return length
+        self.instr_size = self.decode::<U16>(bytes)?.0.inner as usize;
+        self.instr_index = 0;
+        let index = self.method_index;
+        self.method_index += 1;
+        Ok(Some(ScriptItem::Method {
+            index,
+            is_public: is_public.0 != 0,
+            asset_modifier: asset_modifier.0,
+        }))
+    }
+}
+
+// Write a line of the disassembly, the line is truncated if it exceeds the output size
+pub struct LineWriter<'a> {
+    output: &'a mut [u8],
+    length: usize,
+    is_truncated: bool,
+}
+
+impl<'a> LineWriter<'a> {
+    pub fn new(output: &'a mut [u8]) -> Self {
+        assert!(output.len() > TRUNCATED_SUFFIX.len());
+        Self {
+            output,
+            length: 0,
+            is_truncated: false,
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        if self.is_truncated {
+            return;
+        }
+        let size = core::cmp::min(bytes.len(), self.output.len() - self.length);
+        self.output[self.length..(self.length + size)].copy_from_slice(&bytes[..size]);
+        self.length += size;
+        if size < bytes.len() {
+            let from_index = self.output.len() - TRUNCATED_SUFFIX.len();
+            self.output[from_index..].copy_from_slice(TRUNCATED_SUFFIX);
+            self.is_truncated = true;
+        }
+    }
+
+    pub fn write_index(&mut self, index: usize) {
+        let mut output = [0u8; 10];
+        if let Some(str) = I32::unsafe_from(index).to_str(&mut output) {
+            self.write(str);
+        }
+    }
+
+    pub fn write_hex(&mut self, bytes: &[u8]) {
+        self.write(b"0x");
+        for byte in bytes {
+            self.write(&[
+                HEX_CHARS[(byte >> 4) as usize],
+                HEX_CHARS[(byte & 0x0f) as usize],
+            ]);
+        }
+    }
+}
+
+// The asset modifier of a method, the lower 2 bits are the asset permissions
+// and the third bit is the `payToContractOnly` flag
+pub fn write_asset_modifier(writer: &mut LineWriter, asset_modifier: u8) {
+    let permissions: &[u8] = match asset_modifier & 0x03 {
+        0 => b"None",
+        1 => b"Preapproved and contract assets",
+        2 => b"Contract assets",
+        _ => b"Preapproved assets",
+    };
+    writer.write(permissions);
+    if asset_modifier & 0x04 != 0 {
+        writer.write(b", pay to contract only");
+    }
+}
+
+// Write the mnemonic of the instruction, followed by the constant operand if any
+pub fn write_instr(writer: &mut LineWriter, instr: &Instr, encoded: &[u8]) {
+    writer.write(instr.mnemonic().as_bytes());
+    match instr {
+        Instr::U256Const(value) => {
+            let mut output = [0u8; 78]; // u256 max
+            if let Some(str) = value.to_str(&mut output) {
+                writer.write(b" ");
+                writer.write(str);
+            }
+        }
+        Instr::BytesConst(bytes) => {
+            writer.write(b" ");
+            writer.write_hex(&encoded[(encoded.len() - bytes.size())..]);
+        }
+        Instr::AddressConst(_) => {
+            // The address is the base58 encoding of the lockup script
+            let mut limbs = [0u32; limb_size(SCRIPT_LINE_SIZE)];
+            let mut output = [0u8; SCRIPT_LINE_SIZE];
+            writer.write(b" ");
+            match base58_encode(&encoded[1..], &mut limbs[..], &mut output) {
+                Some(address) => writer.write(address),
+                None => writer.write_hex(&encoded[1..]),
+            }
+        }
+        Instr::MethodSelector(_) | Instr::CallExternalBySelector(_) => {
+            writer.write(b" ");
+            writer.write_hex(&encoded[1..]);
+        }
+        _ => (),
+    }
+}
+
+// A field of the disassembly, which is rendered on the stack one at a time
+pub struct ScriptField {
+    name: [u8; SCRIPT_NAME_SIZE],
+    name_length: usize,
+    value: [u8; SCRIPT_LINE_SIZE],
+    value_length: usize,
+}
+
+impl ScriptField {
+    pub fn new<N, V>(write_name: N, write_value: V) -> Self
+    where
+        N: FnOnce(&mut LineWriter),
+        V: FnOnce(&mut LineWriter),
+    {
+        let mut name = [0u8; SCRIPT_NAME_SIZE];
+        let mut name_writer = LineWriter::new(&mut name);
+        write_name(&mut name_writer);
+        let name_length = name_writer.length();
+        let mut value = [0u8; SCRIPT_LINE_SIZE];
+        let mut value_writer = LineWriter::new(&mut value);
+        write_value(&mut value_writer);
+        let value_length = value_writer.length();
+        Self {
+            name,
+            name_length,
+            value,
+            value_length,
+        }
+    }
+}
+
+// A page of the disassembly, which is either the method header or up to `SCRIPT_PAGE_SIZE` instructions of the method
+// The fields are written to the swapping buffer from `from_index`, only their indexes are kept
+pub struct ScriptPage {
+    review_message: [u8; SCRIPT_NAME_SIZE],
+    review_message_length: usize,
+    from_index: usize,
+    to_index: usize,
+    names: [(usize, usize); SCRIPT_PAGE_SIZE],
+    values: [(usize, usize); SCRIPT_PAGE_SIZE],
+    size: usize,
+}
+
+impl ScriptPage {
+    pub fn new(from_index: usize) -> Self {
+        Self {
+            review_message: [0; SCRIPT_NAME_SIZE],
+            review_message_length: 0,
+            from_index,
+            to_index: from_index,
+            names: [(0, 0); SCRIPT_PAGE_SIZE],
+            values: [(0, 0); SCRIPT_PAGE_SIZE],
+            size: 0,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.size == SCRIPT_PAGE_SIZE
+    }
+
+    // The pages of a method are reviewed with the method index as the review message
+    pub fn set_method(&mut self, method_index: usize) {
+        let mut writer = LineWriter::new(&mut self.review_message);
+        writer.write(b"Method #");
+        writer.write_index(method_index);
+        self.review_message_length = writer.length();
+    }
+
+    fn write<const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>>(
+        &mut self,
+        buffer: &mut SwappingBuffer<'_, RAM, FLASH, S>,
+        bytes: &[u8],
+    ) -> Result<(usize, usize), ErrorCode> {
+        let from_index = self.to_index;
+        buffer.write_from(from_index, bytes)?;
+        self.to_index += bytes.len();
+        Ok((from_index, self.to_index))
+    }
+
+    pub fn add_field<const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>>(
+        &mut self,
+        buffer: &mut SwappingBuffer<'_, RAM, FLASH, S>,
+        field: &ScriptField,
+    ) -> Result<(), ErrorCode> {
+        assert!(!self.is_full());
+        self.names[self.size] = self.write(buffer, &field.name[..field.name_length])?;
+        self.values[self.size] = self.write(buffer, &field.value[..field.value_length])?;
+        self.size += 1;
+        Ok(())
+    }
+
+    // Review the fields of the page, the page is cleared after the review
+    pub fn review<const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>, U: ReviewUi>(
+        &mut self,
+        buffer: &mut SwappingBuffer<'_, RAM, FLASH, S>,
+        ui: &mut U,
+    ) -> Result<(), ErrorCode> {
+        if self.size == 0 {
+            return Ok(());
+        }
+        buffer.flush()?;
+        let mut fields: [Field; SCRIPT_PAGE_SIZE] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        for (index, field) in fields[..self.size].iter_mut().enumerate() {
+            let (name_from, name_to) = self.names[index];
+            let (value_from, value_to) = self.values[index];
+            *field = Field {
+                name: bytes_to_string(buffer.read(name_from, name_to))?,
+                value: bytes_to_string(buffer.read(value_from, value_to))?,
+            };
+        }
+        let review_message = bytes_to_string(&self.review_message[..self.review_message_length])?;
+        let result = ui.review_script_page(&fields[..self.size], review_message);
+        self.size = 0;
+        self.to_index = self.from_index;
+        result
+    }
+}
@@ -0,0 +1,814 @@
+use crate::{
+    error_code::ErrorCode,
+    platform::{Hasher, KeyDerivation, NvmStorage, ReviewUi, Storage},
+    sign_tx_context::{SignTxContext, SignedTx},
+    swapping_buffer::NVM_DATA_SIZE,
+    tx_reviewer::TxReviewer,
+};
+
+const MAX_TOKEN_SIZE: u8 = 5;
+pub const PATH_LENGTH: usize = 20;
+const CALL_CONTRACT_FLAG: u8 = 0x01;
+const SCRIPT_OFFSET: usize = 3; // the encoded script offset in the tx
+pub const TOKEN_METADATA_SIZE: usize = 46;
+// The `p2` of the first tx frame if the client requests the tx ID along with the signature
+pub const RETURN_TX_ID_FLAG: u8 = 0x02;
+
+// Handle a `SignTx` APDU frame
+// Returns the signature once all the tx data is received and the user approves the tx,
+// the context and the reviewer are reset if the tx is signed or an error occurs
+pub fn handle_sign_tx<H, K, U, S, F>(
+    p1: u8,
+    p2: u8,
+    data: &[u8],
+    sign_tx_context: &mut SignTxContext<H, K, F>,
+    tx_reviewer: &mut TxReviewer<H, U, S, F>,
+) -> Result<Option<SignedTx>, ErrorCode>
+where
+    H: Hasher,
+    K: KeyDerivation,
+    U: ReviewUi,
+    S: Storage,
+    F: NvmStorage<NVM_DATA_SIZE>,
+{
+    match handle_sign_tx_frame(p1, p2, data, sign_tx_context, tx_reviewer) {
+        Ok(()) if !sign_tx_context.is_complete() => Ok(None),
+        Ok(()) => {
+            // The transaction is signed when all the data is processed
+            let result = sign_tx_context.get_tx_id().and_then(|tx_id| {
+                tx_reviewer.approve_tx(&tx_id)?;
+                sign_tx_context.sign_tx(&tx_id)
+            });
+            if let Err(code) = result {
+                warning_tx_rejected(code, sign_tx_context, tx_reviewer);
+            }
+            reset(sign_tx_context, tx_reviewer);
+            result.map(Some)
+        }
+        Err(code) => {
+            warning_tx_rejected(code, sign_tx_context, tx_reviewer);
+            reset(sign_tx_context, tx_reviewer);
+            Err(code)
+        }
+    }
+}
+
+// Tell the user why the app rejects the tx, a decoding error is explained by the tx field that cannot be decoded
+fn warning_tx_rejected<H, K, U, S, F>(
+    code: ErrorCode,
+    sign_tx_context: &SignTxContext<H, K, F>,
+    tx_reviewer: &mut TxReviewer<H, U, S, F>,
+) where
+    H: Hasher,
+    K: KeyDerivation,
+    U: ReviewUi,
+    S: Storage,
+    F: NvmStorage<NVM_DATA_SIZE>,
+{
+    let reason = match sign_tx_context.get_last_error() {
+        Some(error_info) if error_info.code == code => error_info.rejection_reason(),
+        _ => code.rejection_reason(),
+    };
+    if let Some(reason) = reason {
+        tx_reviewer.warning_tx_rejected(reason);
+    }
+}
+
+// The transaction is split into multiple APDU commands, consisting of token metadata APDU and tx APDU commands
+// We use `p1` and `p2` to distinguish between APDUs:
+// * `p1` = 0 and `p2` = 0 indicates the first token metadata APDU frame
+// * `p1` = 0 and `p2` = 1 indicates a new token metadata APDU frame
+// * `p1` = 0 and `p2` = 2 indicates the remaining token proof APDU frame
+// * `p1` = 1 and `p2` = 0 indicates the first tx APDU frame
+// * `p1` = 1 and `p2` = 1 indicates subsequent tx APDU frames
+// * `p1` = 1 and `p2` = 2 indicates the first tx APDU frame, the tx ID is returned before the signature
+fn handle_sign_tx_frame<H, K, U, S, F>(
+    p1: u8,
+    p2: u8,
+    data: &[u8],
+    sign_tx_context: &mut SignTxContext<H, K, F>,
+    tx_reviewer: &mut TxReviewer<H, U, S, F>,
+) -> Result<(), ErrorCode>
+where
+    H: Hasher,
+    K: KeyDerivation,
+    U: ReviewUi,
+    S: Storage,
+    F: NvmStorage<NVM_DATA_SIZE>,
+{
+    match (p1, p2) {
+        (0, 0) => {
+            // the first frame, which starts a new signing session
+            sign_tx_context.clear_last_error();
+            if data.is_empty() {
+                return Err(ErrorCode::BadLen);
+            }
+            let token_size = data[0]; // the first byte is the token size
+            check_token_size(token_size)?;
+            tx_reviewer.init(token_size)?;
+            if token_size == 0 {
+                return Ok(());
+            }
+            tx_reviewer.handle_token_metadata(&data[1..])
+        }
+        (0, 1) => tx_reviewer.handle_token_metadata(data), // token metadata and proof frame
+        (0, 2) => tx_reviewer.handle_token_proof(data),    // the following token proof frame
+        (1, 0) | (1, RETURN_TX_ID_FLAG) => {
+            // the first unsigned tx frame
+            if data.len() < PATH_LENGTH + SCRIPT_OFFSET {
+                return Err(ErrorCode::BadLen);
+            }
+            let tx_data = &data[PATH_LENGTH..];
+            let is_tx_execute_script = tx_data[SCRIPT_OFFSET - 1] == CALL_CONTRACT_FLAG;
+            if is_tx_execute_script {
+                tx_reviewer.check_blind_signing()?;
+            }
+            tx_reviewer.set_tx_execute_script(is_tx_execute_script);
+
+            sign_tx_context.init(&data[..PATH_LENGTH], p2 == RETURN_TX_ID_FLAG)?;
+            sign_tx_context.handle_tx_data(p1, p2, tx_data, tx_reviewer)
+        }
+        (1, 1) => sign_tx_context.handle_tx_data(p1, p2, data, tx_reviewer), // the following unsigned tx frame
+        _ => Err(ErrorCode::BadP1P2),
+    }
+}
+
+#[inline]
+fn check_token_size(size: u8) -> Result<(), ErrorCode> {
+    if size > MAX_TOKEN_SIZE {
+        Err(ErrorCode::InvalidTokenSize)
+    } else {
+        Ok(())
+    }
+}
+
+#[inline]
+pub fn reset<H, K, U, S, F>(
+    sign_tx_context: &mut SignTxContext<H, K, F>,
+    tx_reviewer: &mut TxReviewer<H, U, S, F>,
+) where
+    H: Hasher,
+    K: KeyDerivation,
+    U: ReviewUi,
+    S: Storage,
+    F: NvmStorage<NVM_DATA_SIZE>,
+{
+    sign_tx_context.reset();
+    tx_reviewer.reset();
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::address::to_base58_address;
+    use crate::mock::{
+        device_lockup_script, encode_tx, hex_to_bytes, new_test_context, output, sign,
+        sign_with_first_tx_p2, u256, MockHasher, MockKeys, MockStorage, MockUi, TestToken, UiEvent,
+        ALF_TOKEN, MAX_FRAME_SIZE, TEST_PATH,
+    };
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+    use utils::encode::Encoder;
+    use utils::model::{
+        InstrModel, LockupScriptModel, MethodModel, ScriptModel, TokenModel, TxInputModel,
+        UnlockScriptModel, UnsignedTxModel, ValModel,
+    };
+    use utils::types::{AlphUnit, AmountFormat};
+
+    const ALF_TOKEN_ID: &str = ALF_TOKEN.id;
+
+    fn transfer_tx(input_pub_key: [u8; 33]) -> UnsignedTxModel {
+        let token_id: [u8; 32] = hex_to_bytes(ALF_TOKEN_ID).try_into().unwrap();
+        UnsignedTxModel {
+            version: 0,
+            network_id: 0,
+            script: None,
+            gas_amount: 20000,
+            gas_price: u256(100_000_000_000),
+            // several inputs so that the tx is split into multiple frames
+            inputs: (0..4)
+                .map(|index| TxInputModel {
+                    hint: [index; 4],
+                    key: [index; 32],
+                    unlock_script: UnlockScriptModel::P2PKH(input_pub_key),
+                })
+                .collect(),
+            fixed_outputs: std::vec![
+                output(
+                    1_000_000_000_000_000_000,
+                    LockupScriptModel::P2PKH([3; 32]),
+                    std::vec![TokenModel {
+                        id: token_id,
+                        amount: u256(1_500_000_000),
+                    }],
+                ),
+                output(
+                    2_000_000_000_000_000_000,
+                    device_lockup_script(&TEST_PATH),
+                    Vec::new()
+                ),
+            ],
+        }
+    }
+
+    fn fields(fields: &[(&str, &str)]) -> Vec<(String, String)> {
+        fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn address(hash: &[u8; 32]) -> String {
+        let mut output = [0u8; 46];
+        let address = to_base58_address(0, hash, &mut output).unwrap();
+        String::from_utf8(address.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_sign_transfer_tx() {
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        assert!(tx.len() > MAX_FRAME_SIZE);
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        let signed_tx = sign(&mut context, &mut reviewer, &[ALF_TOKEN], &tx)
+            .unwrap()
+            .unwrap();
+
+        let tx_id = MockHasher::hash(&tx).unwrap();
+        assert_eq!(signed_tx.tx_id, None);
+        assert_eq!(
+            signed_tx.signature(),
+            MockKeys::signature(&TEST_PATH, &tx_id)
+        );
+        assert_eq!(
+            reviewer.ui().events,
+            std::vec![
+                UiEvent::StartReview,
+                UiEvent::ReviewFields(
+                    "Output 1 of 2".to_string(),
+                    fields(&[
+                        ("Token ID", ALF_TOKEN_ID),
+                        ("Token Amount", "ALF 1.5"),
+                        ("Amount", "ALPH 1"),
+                        ("To", &address(&[3; 32])),
+                    ])
+                ),
+                UiEvent::FinishReview(fields(&[("Fees", "ALPH 0.002")])),
+            ]
+        );
+        // the tx fits in the RAM of the swapping buffers, so no NVM page is written
+        assert_eq!(context.nvm_write_count() + reviewer.nvm_write_count(), 0);
+    }
+
+    #[test]
+    fn test_return_and_display_tx_id() {
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        let tx_id = MockHasher::hash(&tx).unwrap();
+        let storage = MockStorage {
+            tx_id_display_enabled: true,
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        let signed_tx = sign_with_first_tx_p2(
+            &mut context,
+            &mut reviewer,
+            &[ALF_TOKEN],
+            &tx,
+            RETURN_TX_ID_FLAG,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(signed_tx.tx_id, Some(tx_id));
+        assert_eq!(
+            signed_tx.signature(),
+            MockKeys::signature(&TEST_PATH, &tx_id)
+        );
+
+        let tx_id_hex: [u8; 64] = utils::to_hex(&tx_id).unwrap();
+        assert_eq!(
+            reviewer.ui().events.last(),
+            Some(&UiEvent::FinishReview(fields(&[
+                ("Fees", "ALPH 0.002"),
+                ("Tx ID", core::str::from_utf8(&tx_id_hex).unwrap()),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_expert_mode() {
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        let storage = MockStorage {
+            expert_mode_enabled: true,
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[ALF_TOKEN], &tx)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            reviewer.ui().events.last(),
+            Some(&UiEvent::FinishReview(fields(&[
+                ("Fees", "ALPH 0.002"),
+                ("Gas Amount", "20000"),
+                ("Gas Price", "ALPH 0.0000001"),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_amount_format() {
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        let storage = MockStorage {
+            amount_format: AmountFormat {
+                thousands_separator: true,
+                max_significant_decimals: None,
+                alph_unit: AlphUnit::AttoAlph,
+            },
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &tx)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Output 1 of 2".to_string(),
+                fields(&[
+                    ("Token ID", ALF_TOKEN_ID),
+                    ("Raw Token Amount", "1,500,000,000"),
+                    ("Amount", "attoALPH 1,000,000,000,000,000,000"),
+                    ("To", &address(&[3; 32])),
+                ])
+            )
+        );
+        assert_eq!(
+            reviewer.ui().events.last(),
+            Some(&UiEvent::FinishReview(fields(&[(
+                "Fees",
+                "attoALPH 2,000,000,000,000,000"
+            )])))
+        );
+
+        let storage = MockStorage {
+            amount_format: AmountFormat {
+                max_significant_decimals: Some(0),
+                ..AmountFormat::default()
+            },
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[ALF_TOKEN], &tx)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Output 1 of 2".to_string(),
+                fields(&[
+                    ("Token ID", ALF_TOKEN_ID),
+                    ("Token Amount", "ALF \u{2248}1"),
+                    ("Amount", "ALPH 1"),
+                    ("To", &address(&[3; 32])),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn test_last_error() {
+        let mut tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        tx[SCRIPT_OFFSET - 1] = 2; // invalid script option flag
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert_eq!(
+            sign(&mut context, &mut reviewer, &[], &tx),
+            Err(ErrorCode::TxDecodingInvalidData)
+        );
+        // the error is kept after the context is reset
+        let error_info = context.get_last_error().unwrap();
+        assert_eq!(error_info.code, ErrorCode::TxDecodingInvalidData);
+        assert_eq!(error_info.offset, SCRIPT_OFFSET as u32);
+
+        // the next signing session clears the error
+        assert_eq!(
+            handle_sign_tx(0, 0, &[0], &mut context, &mut reviewer),
+            Ok(None)
+        );
+        assert!(context.get_last_error().is_none());
+    }
+
+    #[test]
+    fn test_last_error_in_later_frame() {
+        let mut tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        // the lockup script of the first output, which is sent in the second tx frame
+        let mut lockup_script = std::vec![0u8];
+        lockup_script.extend([3u8; 32]);
+        let lockup_script_offset = tx
+            .windows(lockup_script.len())
+            .rposition(|bytes| bytes == lockup_script.as_slice())
+            .unwrap();
+        let first_chunk_size = MAX_FRAME_SIZE - TEST_PATH.len() * 4;
+        assert!(lockup_script_offset > first_chunk_size);
+        tx[lockup_script_offset] = 9; // invalid lockup script type
+
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert_eq!(
+            sign(&mut context, &mut reviewer, &[], &tx),
+            Err(ErrorCode::TxDecodingInvalidData)
+        );
+        let error_info = context.get_last_error().unwrap();
+        assert_eq!(error_info.code, ErrorCode::TxDecodingInvalidData);
+        // the offset counts the bytes of the previous frames, and stops after the invalid type byte
+        assert_eq!(error_info.offset, (lockup_script_offset + 1) as u32);
+        assert_eq!(error_info.path.section, "FixedOutputs");
+        assert_eq!(error_info.path.index, Some(0));
+        assert_eq!(error_info.path.field, Some("lockup_script"));
+    }
+
+    #[test]
+    fn test_network_restriction() {
+        let mut tx = transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH));
+        tx.network_id = 1;
+        let storage = MockStorage {
+            allowed_network_id: Some(0),
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert_eq!(
+            sign(&mut context, &mut reviewer, &[], &encode_tx(&tx)),
+            Err(ErrorCode::InvalidNetworkId)
+        );
+        assert_eq!(
+            reviewer.ui().events,
+            std::vec![UiEvent::TxRejected("Unsupported network".to_string())]
+        );
+
+        let storage = MockStorage {
+            allowed_network_id: Some(1),
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &encode_tx(&tx))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_sign_tx_without_token_metadata() {
+        let mut storage = MockStorage::default();
+        storage.address_book.push((0, [3; 32], b"Alice".to_vec()));
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &tx)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Output 1 of 2".to_string(),
+                fields(&[
+                    ("Token ID", ALF_TOKEN_ID),
+                    ("Raw Token Amount", "1500000000"),
+                    ("Amount", "ALPH 1"),
+                    ("To", "Alice (saved)"),
+                    ("Address", &address(&[3; 32])),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn test_sign_self_transfer_tx() {
+        let mut tx = transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH));
+        tx.fixed_outputs.remove(0);
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &encode_tx(&tx))
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            reviewer.ui().events,
+            std::vec![
+                UiEvent::StartReview,
+                UiEvent::ReviewSelfTransfer(fields(&[("Fees", "ALPH 0.002")]))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_external_inputs() {
+        let tx = encode_tx(&transfer_tx([2; 33]));
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &tx)
+            .unwrap()
+            .is_some());
+        assert_eq!(reviewer.ui().events[0], UiEvent::ExternalInputsWarning);
+        assert_eq!(reviewer.ui().events[1], UiEvent::StartReview);
+    }
+
+    #[test]
+    fn test_invalid_outputs() {
+        let dust_amount = 1_000_000_000_000_000;
+        for (alph_amount, token_amount) in [
+            (0, 1_500_000_000),
+            (dust_amount - 1, 1_500_000_000),
+            (dust_amount, 0),
+        ] {
+            let mut tx = transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH));
+            tx.fixed_outputs[0].amount = u256(alph_amount);
+            tx.fixed_outputs[0].tokens[0].amount = u256(token_amount);
+            let (mut context, mut reviewer) =
+                new_test_context(MockStorage::default(), MockUi::default());
+            assert_eq!(
+                sign(&mut context, &mut reviewer, &[], &encode_tx(&tx)),
+                Err(ErrorCode::InvalidOutput)
+            );
+            assert_eq!(
+                reviewer.ui().events,
+                std::vec![UiEvent::StartReview, UiEvent::InvalidOutput]
+            );
+        }
+
+        let mut tx = transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH));
+        tx.fixed_outputs[0].amount = u256(dust_amount);
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &encode_tx(&tx))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_p2sh_input() {
+        let script = ScriptModel {
+            methods: std::vec![MethodModel {
+                is_public: 1,
+                asset_modifier: 0,
+                args_length: 2,
+                locals_length: 2,
+                return_length: 0,
+                instrs: std::vec![InstrModel {
+                    code: 2, // Return
+                    operands: Vec::new(),
+                }],
+            }],
+        };
+        let mut tx = transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH));
+        tx.inputs[1].unlock_script = UnlockScriptModel::P2SH {
+            script: script.clone(),
+            params: std::vec![ValModel::Bool(true), ValModel::U256(u256(1))],
+        };
+        let tx = encode_tx(&tx);
+
+        let mut encoded_script = Vec::new();
+        script.encode(&mut encoded_script).unwrap();
+        let script_hash = MockHasher::hash(&encoded_script).unwrap();
+        let mut output = [0u8; 46];
+        let script_address = to_base58_address(2, &script_hash, &mut output).unwrap();
+        let script_address = core::str::from_utf8(script_address).unwrap();
+
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &tx)
+            .unwrap()
+            .is_some());
+        // the script address replaces the external inputs warning
+        assert_eq!(reviewer.ui().events[0], UiEvent::StartReview);
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Spending from script address".to_string(),
+                fields(&[("Address", script_address), ("Unlock Arguments", "2")])
+            )
+        );
+        assert!(!reviewer
+            .ui()
+            .events
+            .contains(&UiEvent::ExternalInputsWarning));
+
+        let mut storage = MockStorage::default();
+        storage
+            .address_book
+            .push((2, script_hash, b"Vault".to_vec()));
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &tx)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Spending from script address".to_string(),
+                fields(&[
+                    ("Label", "Vault"),
+                    ("Address", script_address),
+                    ("Unlock Arguments", "2")
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn test_p2mpkh_input() {
+        let device_pub_key = MockKeys::compressed_pub_key(&TEST_PATH);
+        let mut tx = transfer_tx(device_pub_key);
+        tx.inputs[2].unlock_script =
+            UnlockScriptModel::P2MPKH(std::vec![([5; 33], 0), (device_pub_key, 2)]);
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &encode_tx(&tx))
+            .unwrap()
+            .is_some());
+        // the device is one of the signers, so the input is not reported as an external input
+        assert_eq!(reviewer.ui().events[0], UiEvent::StartReview);
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Multisig input".to_string(),
+                fields(&[("Signatures", "2"), ("Signer", "#2"), ("Key Index", "2")])
+            )
+        );
+        assert!(!reviewer
+            .ui()
+            .events
+            .contains(&UiEvent::ExternalInputsWarning));
+
+        tx.inputs[2].unlock_script =
+            UnlockScriptModel::P2MPKH(std::vec![([5; 33], 0), ([6; 33], 1)]);
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &encode_tx(&tx))
+            .unwrap()
+            .is_some());
+        assert_eq!(reviewer.ui().events[0], UiEvent::ExternalInputsWarning);
+        assert_eq!(reviewer.ui().events[1], UiEvent::StartReview);
+    }
+
+    #[test]
+    fn test_user_rejects_tx() {
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        for reject_at in 0..3 {
+            let ui = MockUi {
+                reject_at: Some(reject_at),
+                ..MockUi::default()
+            };
+            let (mut context, mut reviewer) = new_test_context(MockStorage::default(), ui);
+            assert_eq!(
+                sign(&mut context, &mut reviewer, &[], &tx),
+                Err(ErrorCode::UserCancelled)
+            );
+            assert_eq!(reviewer.ui().events.len(), reject_at + 1);
+            // the context is reset, so the following tx frames are rejected
+            assert_eq!(
+                handle_sign_tx(1, 1, &tx[..10], &mut context, &mut reviewer),
+                Err(ErrorCode::BadP1P2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_blind_signing() {
+        let script = ScriptModel {
+            methods: Vec::new(),
+        };
+        let tx = UnsignedTxModel {
+            script: Some(script.clone()),
+            ..transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH))
+        };
+        let tx = encode_tx(&tx);
+
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        assert_eq!(
+            sign(&mut context, &mut reviewer, &[], &tx),
+            Err(ErrorCode::BlindSigningDisabled)
+        );
+        assert_eq!(
+            reviewer.ui().events,
+            std::vec![UiEvent::BlindSigningDisabled]
+        );
+
+        let storage = MockStorage {
+            blind_signing_enabled: true,
+            ..MockStorage::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage, MockUi::default());
+        assert!(sign(&mut context, &mut reviewer, &[], &tx)
+            .unwrap()
+            .is_some());
+        // the script hash is displayed, the script is only displayed in expert mode
+        let mut encoded_script = Vec::new();
+        script.encode(&mut encoded_script).unwrap();
+        let script_hash = MockHasher::hash(&encoded_script).unwrap();
+        let script_hash_hex: [u8; 64] = utils::to_hex(&script_hash).unwrap();
+        assert_eq!(reviewer.ui().events[0], UiEvent::StartReview);
+        assert_eq!(
+            reviewer.ui().events[1],
+            UiEvent::ReviewFields(
+                "Script".to_string(),
+                std::vec![(
+                    "Script Hash".to_string(),
+                    String::from_utf8(script_hash_hex.to_vec()).unwrap()
+                )]
+            )
+        );
+        assert!(!reviewer.ui().events.contains(&UiEvent::AskShowScript));
+    }
+
+    #[test]
+    fn test_show_script() {
+        // each `BytesConst` instruction is encoded as 10 bytes
+        let script_tx = |instr_size: usize| {
+            let instrs = (0..instr_size)
+                .map(|_| InstrModel {
+                    code: 20,
+                    operands: std::vec![8, 0, 0, 0, 0, 0, 0, 0, 0],
+                })
+                .collect();
+            let method = MethodModel {
+                is_public: 1,
+                asset_modifier: 0,
+                args_length: 0,
+                locals_length: 0,
+                return_length: 0,
+                instrs,
+            };
+            let tx = UnsignedTxModel {
+                script: Some(ScriptModel {
+                    methods: std::vec![method],
+                }),
+                ..transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH))
+            };
+            encode_tx(&tx)
+        };
+        let storage = || MockStorage {
+            blind_signing_enabled: true,
+            expert_mode_enabled: true,
+            ..MockStorage::default()
+        };
+        let ui = MockUi {
+            show_script: true,
+            ..MockUi::default()
+        };
+
+        // the script is sent in multiple frames and stored in the NVM pages of the reviewer buffer
+        let (mut context, mut reviewer) = new_test_context(storage(), ui);
+        assert!(sign(&mut context, &mut reviewer, &[], &script_tx(100))
+            .unwrap()
+            .is_some());
+        let script_pages: Vec<_> = reviewer
+            .ui()
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                UiEvent::ReviewScriptPage(_, fields) => Some(fields),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(script_pages.len(), 26); // the method header and 25 pages of 4 instructions
+        assert_eq!(
+            script_pages[25].last().unwrap(),
+            &(
+                String::from("Instr #99"),
+                String::from("BytesConst 0x0000000000000000")
+            )
+        );
+
+        // the script exceeds the reviewer buffer, so the user is told that it cannot be displayed
+        let ui = MockUi {
+            show_script: true,
+            ..MockUi::default()
+        };
+        let (mut context, mut reviewer) = new_test_context(storage(), ui);
+        assert!(sign(&mut context, &mut reviewer, &[], &script_tx(250))
+            .unwrap()
+            .is_some());
+        let events = &reviewer.ui().events;
+        assert_eq!(events[2], UiEvent::ScriptTooLargeWarning);
+        assert!(!events.contains(&UiEvent::AskShowScript));
+    }
+
+    #[test]
+    fn test_invalid_token_metadata() {
+        let tx = encode_tx(&transfer_tx(MockKeys::compressed_pub_key(&TEST_PATH)));
+        let (mut context, mut reviewer) =
+            new_test_context(MockStorage::default(), MockUi::default());
+        // the decimals do not match the token list, so the proof is invalid
+        let token = TestToken {
+            decimals: 18,
+            ..ALF_TOKEN
+        };
+        assert_eq!(
+            sign(&mut context, &mut reviewer, &[token], &tx),
+            Err(ErrorCode::InvalidTokenMetadata)
+        );
+        assert_eq!(
+            reviewer.ui().events,
+            std::vec![UiEvent::TxRejected("Invalid token metadata".to_string())]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use utils::{
+    buffer::Buffer,
+    decode::{DecodePath, StreamingDecoder},
+    deserialize_path,
+    types::UnsignedTx,
+    PATH_LENGTH,
+};
+
+use crate::{
+    address::Address,
+    error_code::ErrorCode,
+    platform::{Blake2bHash, Hasher, KeyDerivation, NvmStorage, ReviewUi, Storage},
+    sign_tx::RETURN_TX_ID_FLAG,
+    swapping_buffer::{SwappingBuffer, NVM_DATA_SIZE, RAM_SIZE},
+    tx_reviewer::TxReviewer,
+};
+
+#[derive(PartialEq)]
+enum DecodeStep {
+    Init,
+    DecodingTx,
+    Complete,
+}
+
+// The details of the last tx decoding error, which can be queried by the `GetLastError` instruction
+#[derive(Clone, Copy)]
+pub struct DecodeErrorInfo {
+    pub code: ErrorCode,
+    // The number of tx bytes consumed when decoding failed, which counts the bytes of the previous
+    // frames and the bytes of the current frame read by the decoder. This is the position where the
+    // decoder stopped inside or right after the failing field, not the start of that field
+    pub offset: u32,
+    pub path: DecodePath,
+}
+
+impl DecodeErrorInfo {
+    // The reason displayed to the user, which names the tx field that cannot be decoded
+    // A decoding overflow means that the tx is too large for the device wherever it happens
+    pub fn rejection_reason(&self) -> Option<&'static str> {
+        if self.code == ErrorCode::TxDecodingOverflow {
+            return self.code.rejection_reason();
+        }
+        let reason = match (self.path.section, self.path.index, self.path.field) {
+            ("ScriptOpt", _, _) => "Unsupported script",
+            ("TxFee", _, _) => "Invalid gas amount or price",
+            ("Inputs", None, _) => "Invalid number of inputs",
+            ("Inputs", Some(_), Some("unlock_script")) => "Unsupported input type",
+            ("Inputs", Some(_), _) => "Invalid input",
+            ("FixedOutputs", None, _) => "Invalid number of outputs",
+            ("FixedOutputs", Some(_), Some("amount")) => "Invalid output amount",
+            ("FixedOutputs", Some(_), Some("lockup_script")) => "Unsupported output type",
+            ("FixedOutputs", Some(_), Some("tokens")) => "Invalid output tokens",
+            ("FixedOutputs", Some(_), _) => "Invalid output",
+            _ => return self.code.rejection_reason(),
+        };
+        Some(reason)
+    }
+}
+
+// The signed tx returned to the client, the tx ID is only returned if it is requested in the first tx frame
+#[derive(Debug, PartialEq)]
+pub struct SignedTx {
+    pub tx_id: Option<Blake2bHash>,
+    pub signature: [u8; 72],
+    pub signature_length: u32,
+}
+
+impl SignedTx {
+    #[inline]
+    pub fn signature(&self) -> &[u8] {
+        &self.signature[..(self.signature_length as usize)]
+    }
+}
+
+// The context for signing a transaction
+// It keeps track of the current step, the transaction decoder, the path, and the device address
+// A streaming decoder is used to decode the transaction in chunks so that it can handle large transactions
+// The NVM data is used for SwappingBuffer to store temporary data in case RAM is not enough
+pub struct SignTxContext<H, K, F: NvmStorage<NVM_DATA_SIZE> + 'static> {
+    pub path: [u32; PATH_LENGTH],
+    pub tx_decoder: StreamingDecoder<UnsignedTx>,
+    current_step: DecodeStep,
+    hasher: H,
+    // The script hash is the Blake2b hash of the encoded script, without the option flag of `ScriptOpt`
+    script_hasher: H,
+    script_length: usize,
+    // Whether the tx ID is returned before the signature, old clients do not request it
+    return_tx_id: bool,
+    keys: K,
+    temp_data: SwappingBuffer<'static, RAM_SIZE, NVM_DATA_SIZE, F>,
+    device_address: Option<Address<'static>>,
+    decoded_size: usize,
+    // The last error is kept after resetting the context so that `GetLastError` can query it
+    // after the signing fails, it is cleared when the next signing session starts
+    last_error: Option<DecodeErrorInfo>,
+}
+
+impl<H: Hasher, K: KeyDerivation, F: NvmStorage<NVM_DATA_SIZE>> SignTxContext<H, K, F> {
+    pub fn new(keys: K, flash: &'static mut F) -> Self {
+        SignTxContext {
+            path: [0; PATH_LENGTH],
+            tx_decoder: StreamingDecoder::default(),
+            current_step: DecodeStep::Init,
+            hasher: H::new(),
+            script_hasher: H::new(),
+            script_length: 0,
+            return_tx_id: false,
+            keys,
+            temp_data: SwappingBuffer::new(flash),
+            device_address: None,
+            decoded_size: 0,
+            last_error: None,
+        }
+    }
+
+    // Initialize the context
+    pub fn init(&mut self, data: &[u8], return_tx_id: bool) -> Result<(), ErrorCode> {
+        deserialize_path(data, &mut self.path, ErrorCode::HDPathDecodingFailed)?;
+        self.tx_decoder.reset();
+        self.current_step = DecodeStep::Init;
+        self.hasher.reset();
+        self.script_hasher.reset();
+        self.script_length = 0;
+        self.return_tx_id = return_tx_id;
+        self.temp_data.reset(0);
+        self.temp_data.reset_nvm_write_count();
+        self.device_address = Some(Address::from_path::<H, K>(&self.keys, &self.path)?);
+        self.decoded_size = 0;
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.path = [0; PATH_LENGTH];
+        self.tx_decoder.reset();
+        self.current_step = DecodeStep::Init;
+        self.hasher.reset();
+        self.script_hasher.reset();
+        self.script_length = 0;
+        self.return_tx_id = false;
+        self.temp_data.reset(0);
+        self.device_address = None;
+        self.decoded_size = 0;
+    }
+
+    // The number of NVM writes of the temp data for the current tx
+    #[inline]
+    pub fn nvm_write_count(&self) -> usize {
+        self.temp_data.nvm_write_count()
+    }
+
+    pub fn get_last_error(&self) -> Option<&DecodeErrorInfo> {
+        self.last_error.as_ref()
+    }
+
+    #[inline]
+    pub fn clear_last_error(&mut self) {
+        self.last_error = None;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current_step == DecodeStep::Complete
+    }
+
+    // Get the transaction ID by finalizing the hash
+    pub fn get_tx_id(&mut self) -> Result<Blake2bHash, ErrorCode> {
+        assert!(self.is_complete());
+        self.hasher.finalize()
+    }
+
+    // Sign the transaction by signing the transaction ID
+    pub fn sign_tx(&mut self, tx_id: &Blake2bHash) -> Result<SignedTx, ErrorCode> {
+        let (signature, signature_length, _) = self.keys.sign_hash(&self.path, tx_id)?;
+        Ok(SignedTx {
+            tx_id: if self.return_tx_id {
+                Some(*tx_id)
+            } else {
+                None
+            },
+            signature,
+            signature_length,
+        })
+    }
+
+    fn update_script_hash(&mut self, script_bytes: &[u8]) -> Result<(), ErrorCode> {
+        // Skip the option flag, which is the first byte of `ScriptOpt`
+        let from_index = if self.script_length == 0 { 1 } else { 0 };
+        self.script_length += script_bytes.len();
+        if script_bytes.len() > from_index {
+            self.script_hasher.update(&script_bytes[from_index..])?;
+        }
+        Ok(())
+    }
+
+    fn _decode_tx<U: ReviewUi, S: Storage>(
+        &mut self,
+        buffer: &mut Buffer<'_, SwappingBuffer<'static, RAM_SIZE, NVM_DATA_SIZE, F>>,
+        tx_reviewer: &mut TxReviewer<H, U, S, F>,
+    ) -> Result<(), ErrorCode> {
+        while !buffer.is_empty() {
+            let from_index = buffer.get_index();
+            let is_decoding_script = matches!(self.tx_decoder.inner, UnsignedTx::ScriptOpt(_));
+            let result = self.tx_decoder.step(buffer);
+            if is_decoding_script && result.is_ok() {
+                let script_bytes = buffer.get_range(from_index, buffer.get_index());
+                self.update_script_hash(script_bytes)?;
+                tx_reviewer.record_script(script_bytes)?;
+            }
+            match result {
+                // New transaction details are available
+                Ok(true) => {
+                    if let UnsignedTx::ScriptOpt(script) = &self.tx_decoder.inner {
+                        if script.inner.is_some() {
+                            tx_reviewer.set_script_hash(self.script_hasher.finalize()?);
+                        }
+                    }
+                    self.temp_data.flush()?;
+                    tx_reviewer.review_tx_details(
+                        &self.tx_decoder.inner,
+                        self.device_address.as_ref().unwrap(),
+                        self.temp_data.read_all(),
+                    )?;
+                    self.temp_data.reset(0);
+                    if self.tx_decoder.inner.is_complete() {
+                        self.current_step = DecodeStep::Complete;
+                        return Ok(());
+                    } else {
+                        self.tx_decoder.inner.next_step();
+                        self.tx_decoder.reset_stage();
+                    }
+                }
+                // No new transaction details are available
+                Ok(false) => return Ok(()),
+                Err(err) => {
+                    let error_info = DecodeErrorInfo {
+                        code: err.into(),
+                        offset: (self.decoded_size + buffer.get_index()) as u32,
+                        path: self.tx_decoder.inner.decode_path(),
+                    };
+                    self.last_error = Some(error_info);
+                    return Err(error_info.code);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Decode a transaction chunk
+    fn decode_tx<U: ReviewUi, S: Storage>(
+        &mut self,
+        tx_chunk: &[u8],
+        tx_reviewer: &mut TxReviewer<H, U, S, F>,
+    ) -> Result<(), ErrorCode> {
+        if tx_chunk.len() > (u8::MAX as usize) {
+            return Err(ErrorCode::BadLen);
+        }
+        let mut buffer = Buffer::new(tx_chunk, &mut self.temp_data);
+        let result = self._decode_tx(&mut buffer, tx_reviewer);
+        self.decoded_size += tx_chunk.len();
+        self.hasher.update(tx_chunk)?;
+        result
+    }
+
+    // Handle a transaction data chunk, `p1` and `p2` are from the APDU header
+    pub fn handle_tx_data<U: ReviewUi, S: Storage>(
+        &mut self,
+        p1: u8,
+        p2: u8,
+        tx_data_chunk: &[u8],
+        tx_reviewer: &mut TxReviewer<H, U, S, F>,
+    ) -> Result<(), ErrorCode> {
+        match self.current_step {
+            DecodeStep::Complete => Err(ErrorCode::InternalError),
+            DecodeStep::Init => {
+                // The first chunk of the transaction
+                if p1 == 1 && (p2 == 0 || p2 == RETURN_TX_ID_FLAG) {
+                    self.current_step = DecodeStep::DecodingTx;
+                    self.decode_tx(tx_data_chunk, tx_reviewer)
+                } else {
+                    Err(ErrorCode::BadP1P2)
+                }
+            }
+            DecodeStep::DecodingTx => {
+                // The subsequent chunks of the transaction
+                if p1 == 1 && p2 == 1 {
+                    self.decode_tx(tx_data_chunk, tx_reviewer)
+                } else {
+                    Err(ErrorCode::BadP1P2)
+                }
+            }
+        }
+    }
+}
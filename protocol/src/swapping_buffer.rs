@@ -0,0 +1,413 @@
+// This code is inspired by code from zondax: https://github.com/Zondax/ledger-rust/blob/main/bolos/src/swapping_buffer.rs
+use utils::{base58::LimbStore, buffer::Writable};
+
+use crate::{error_code::ErrorCode, platform::NvmStorage};
+
+pub const RAM_SIZE: usize = 512;
+pub const NVM_DATA_SIZE: usize = 2048;
+// Writes to the same flash page are merged in the page cache, the page is written
+// to the NVM only when another page is written or when the buffer is flushed
+pub const NVM_PAGE_SIZE: usize = 64;
+const LIMB_BYTES: usize = core::mem::size_of::<u32>();
+
+#[derive(Clone, Copy)]
+enum BufferState {
+    WritingToRam(usize),
+    WritingToFlash(usize),
+}
+
+impl Default for BufferState {
+    fn default() -> Self {
+        BufferState::WritingToRam(0)
+    }
+}
+
+// The cached page is always dirty, it is written to the NVM when it is evicted
+// The page bytes are kept by the swapping buffer, see `SwappingBuffer::cached_page`
+struct PageCache {
+    page_index: Option<usize>,
+    nvm_write_count: usize,
+}
+
+impl PageCache {
+    fn new() -> Self {
+        Self {
+            page_index: None,
+            nvm_write_count: 0,
+        }
+    }
+
+    fn flush<const FLASH: usize, S: NvmStorage<FLASH>>(
+        &mut self,
+        flash: &mut S,
+        page: &[u8; NVM_PAGE_SIZE],
+    ) -> Result<(), ErrorCode> {
+        if let Some(page_index) = self.page_index.take() {
+            flash.write_from(page_index * NVM_PAGE_SIZE, page)?;
+            self.nvm_write_count += 1;
+        }
+        Ok(())
+    }
+
+    fn load_page<const FLASH: usize, S: NvmStorage<FLASH>>(
+        &mut self,
+        flash: &mut S,
+        page: &mut [u8; NVM_PAGE_SIZE],
+        page_index: usize,
+    ) -> Result<(), ErrorCode> {
+        if self.page_index != Some(page_index) {
+            self.flush(flash, page)?;
+            let from_index = page_index * NVM_PAGE_SIZE;
+            page.copy_from_slice(&flash.get_ref()[from_index..(from_index + NVM_PAGE_SIZE)]);
+            self.page_index = Some(page_index);
+        }
+        Ok(())
+    }
+
+    // Write the data to the cached pages, the cached page is evicted
+    // when the data is written to another page
+    fn write<const FLASH: usize, S: NvmStorage<FLASH>>(
+        &mut self,
+        flash: &mut S,
+        page: &mut [u8; NVM_PAGE_SIZE],
+        from_index: usize,
+        data: &[u8],
+    ) -> Result<(), ErrorCode> {
+        if from_index + data.len() > FLASH {
+            return Err(ErrorCode::Overflow);
+        }
+        let mut index = from_index;
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let offset = index % NVM_PAGE_SIZE;
+            let size = core::cmp::min(NVM_PAGE_SIZE - offset, remaining.len());
+            self.load_page(flash, page, index / NVM_PAGE_SIZE)?;
+            page[offset..(offset + size)].copy_from_slice(&remaining[..size]);
+            index += size;
+            remaining = &remaining[size..];
+        }
+        Ok(())
+    }
+}
+
+pub struct SwappingBuffer<'a, const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>> {
+    ram: [u8; RAM],
+    flash: &'a mut S,
+    state: BufferState,
+    cache: PageCache,
+}
+
+impl<'a, const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>>
+    SwappingBuffer<'a, RAM, FLASH, S>
+{
+    // The cached page takes the end of the RAM, which is not used after the RAM data is written to the flash
+    const CACHED_PAGE_INDEX: usize = RAM - NVM_PAGE_SIZE;
+
+    pub fn new(flash: &'a mut S) -> Self {
+        assert_eq!(FLASH % NVM_PAGE_SIZE, 0);
+        assert!(RAM >= NVM_PAGE_SIZE);
+        Self {
+            ram: [0u8; RAM],
+            flash,
+            state: BufferState::default(),
+            cache: PageCache::new(),
+        }
+    }
+
+    // Split the buffer into the flash, the page cache and the cached page, so that they can be borrowed together
+    #[inline]
+    fn cached_page(&mut self) -> (&mut S, &mut PageCache, &mut [u8; NVM_PAGE_SIZE]) {
+        let page = (&mut self.ram[Self::CACHED_PAGE_INDEX..])
+            .try_into()
+            .unwrap();
+        (self.flash, &mut self.cache, page)
+    }
+
+    // The data within the cached page is read from the cache, the buffer must be flushed
+    // before reading the data across the cached page
+    pub fn read(&self, from_index: usize, to_index: usize) -> &[u8] {
+        match self.state {
+            BufferState::WritingToRam(_) => {
+                assert!(from_index < to_index && to_index <= RAM);
+                &self.ram[from_index..to_index]
+            }
+            BufferState::WritingToFlash(_) => {
+                assert!(from_index < to_index && to_index <= FLASH);
+                if let Some(page_index) = self.cache.page_index {
+                    let page_from_index = page_index * NVM_PAGE_SIZE;
+                    if from_index >= page_from_index && to_index <= page_from_index + NVM_PAGE_SIZE
+                    {
+                        let from = Self::CACHED_PAGE_INDEX + from_index - page_from_index;
+                        return &self.ram[from..(from + to_index - from_index)];
+                    }
+                    assert!(
+                        to_index <= page_from_index
+                            || from_index >= page_from_index + NVM_PAGE_SIZE
+                    );
+                }
+                &self.flash.get_ref()[from_index..to_index]
+            }
+        }
+    }
+
+    // The buffer must be flushed before reading the data written to the flash
+    pub fn read_all(&self) -> &[u8] {
+        match self.state {
+            BufferState::WritingToRam(index) => &self.ram[..index],
+            BufferState::WritingToFlash(index) => {
+                assert!(self.cache.page_index.is_none());
+                &self.flash.get_ref()[..index]
+            }
+        }
+    }
+
+    pub fn get_index(&self) -> usize {
+        match self.state {
+            BufferState::WritingToRam(index) => index,
+            BufferState::WritingToFlash(index) => index,
+        }
+    }
+
+    // Write the cached page to the NVM
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), ErrorCode> {
+        let (flash, cache, page) = self.cached_page();
+        cache.flush(flash, page)
+    }
+
+    // The number of NVM writes since the count was reset, each write programs one flash page
+    #[inline]
+    pub fn nvm_write_count(&self) -> usize {
+        self.cache.nvm_write_count
+    }
+
+    #[inline]
+    pub fn reset_nvm_write_count(&mut self) {
+        self.cache.nvm_write_count = 0;
+    }
+
+    #[inline]
+    fn write_to_ram(&mut self, data: &[u8], from: usize, to: usize) {
+        self.ram[from..to].copy_from_slice(data);
+        self.state = BufferState::WritingToRam(to);
+    }
+
+    #[inline]
+    fn write_to_nvm(&mut self, data: &[u8], from: usize) -> Result<(), ErrorCode> {
+        let (flash, cache, page) = self.cached_page();
+        cache.write(flash, page, from, data)?;
+        self.state = BufferState::WritingToFlash(from + data.len());
+        Ok(())
+    }
+
+    // The RAM data is written to the NVM directly, since the end of the RAM is reused by the cached page
+    #[inline]
+    fn write_ram_to_nvm(&mut self, ram_length: usize) -> Result<(), ErrorCode> {
+        if ram_length > 0 {
+            self.flash.write_from(0, &self.ram[..ram_length])?;
+            self.cache.nvm_write_count += ram_length.div_ceil(NVM_PAGE_SIZE);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn switch_to_nvm(&mut self, ram_length: usize, data: &[u8]) -> Result<(), ErrorCode> {
+        self.write_ram_to_nvm(ram_length)?;
+        self.write_to_nvm(data, ram_length)
+    }
+
+    // The limb of the given index is kept at the end of the flash
+    #[inline]
+    fn limb_index(index: usize) -> usize {
+        FLASH - (index + 1) * LIMB_BYTES
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> Result<usize, ErrorCode> {
+        match self.state {
+            BufferState::WritingToRam(index) => {
+                let to = index + data.len();
+                if to <= RAM {
+                    self.write_to_ram(data, index, to);
+                    return Ok(to);
+                }
+
+                self.switch_to_nvm(index, data)?;
+                Ok(to)
+            }
+            BufferState::WritingToFlash(index) => {
+                self.write_to_nvm(data, index)?;
+                Ok(index + data.len())
+            }
+        }
+    }
+
+    pub fn write_from(&mut self, index: usize, data: &[u8]) -> Result<(), ErrorCode> {
+        match self.state {
+            BufferState::WritingToRam(_) => {
+                let to = index + data.len();
+                if to <= RAM {
+                    self.write_to_ram(data, index, to);
+                    return Ok(());
+                }
+
+                self.switch_to_nvm(index, data)
+            }
+            BufferState::WritingToFlash(_) => self.write_to_nvm(data, index),
+        }
+    }
+
+    pub fn update(&mut self, from_index: usize, data: &[u8]) {
+        let size = data.len();
+        match self.state {
+            BufferState::WritingToRam(_) => {
+                assert!(from_index + size <= RAM);
+                self.ram[from_index..(from_index + size)].copy_from_slice(data);
+            }
+            BufferState::WritingToFlash(_) => {
+                assert!(from_index + size <= FLASH);
+                let (flash, cache, page) = self.cached_page();
+                cache.write(flash, page, from_index, data).unwrap();
+            }
+        }
+    }
+
+    // The data written to the flash is not read after the reset, so the cached page
+    // is dropped instead of being written to the NVM
+    // The end of the RAM is restored from the flash, since it has been reused by the cached page
+    pub fn reset(&mut self, from_index: usize) {
+        if let BufferState::WritingToFlash(_) = self.state {
+            self.ram[Self::CACHED_PAGE_INDEX..]
+                .copy_from_slice(&self.flash.get_ref()[Self::CACHED_PAGE_INDEX..RAM]);
+        }
+        self.state = BufferState::WritingToRam(from_index);
+        self.cache.page_index = None;
+    }
+}
+
+impl<'a, const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>> Writable
+    for SwappingBuffer<'a, RAM, FLASH, S>
+{
+    fn write(&mut self, bytes: &[u8]) -> bool {
+        self.write(bytes).is_ok()
+    }
+}
+
+// The base58 limbs are kept at the end of the flash without moving the index of the buffer,
+// so that the encoded string can be written from the index, see `Base58Encoder::finalize_in_place`
+impl<'a, const RAM: usize, const FLASH: usize, S: NvmStorage<FLASH>> LimbStore
+    for SwappingBuffer<'a, RAM, FLASH, S>
+{
+    fn capacity(&self) -> usize {
+        (FLASH - self.get_index()) / LIMB_BYTES
+    }
+
+    fn get(&self, index: usize) -> u32 {
+        let from_index = Self::limb_index(index);
+        let bytes = self.read(from_index, from_index + LIMB_BYTES);
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn set(&mut self, index: usize, limb: u32) {
+        if let BufferState::WritingToRam(ram_length) = self.state {
+            self.write_ram_to_nvm(ram_length).unwrap();
+            self.state = BufferState::WritingToFlash(ram_length);
+        }
+        self.update(Self::limb_index(index), &limb.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{SwappingBuffer, NVM_PAGE_SIZE};
+    use crate::mock::MockNvm;
+    use std::{vec, vec::Vec};
+    use utils::base58::{base58_encode, limb_size, Base58Encoder};
+
+    const RAM: usize = NVM_PAGE_SIZE;
+    const FLASH: usize = 4 * NVM_PAGE_SIZE;
+
+    #[test]
+    fn test_merge_writes_to_the_same_page() {
+        let mut buffer = SwappingBuffer::<RAM, FLASH, _>::new(MockNvm::new_static());
+        buffer.write(&[0; RAM]).unwrap();
+        let data: [u8; NVM_PAGE_SIZE] = core::array::from_fn(|index| index as u8);
+        for chunk in data.chunks(8) {
+            buffer.write(chunk).unwrap();
+        }
+        // the RAM data is written to the first page when switching to the flash
+        assert_eq!(buffer.nvm_write_count(), 1);
+        buffer.flush().unwrap();
+        assert_eq!(buffer.nvm_write_count(), 2);
+        assert_eq!(&buffer.read_all()[..RAM], &[0; RAM]);
+        assert_eq!(&buffer.read_all()[RAM..], &data);
+
+        buffer.update(RAM + 4, &[0xff; 2]);
+        buffer.write(&[0xff; 2]).unwrap();
+        assert_eq!(buffer.nvm_write_count(), 3);
+        buffer.flush().unwrap();
+        assert_eq!(buffer.nvm_write_count(), 4);
+        assert_eq!(buffer.read(RAM + 4, RAM + 6), &[0xff; 2]);
+        assert_eq!(
+            buffer.read(RAM + NVM_PAGE_SIZE, RAM + NVM_PAGE_SIZE + 2),
+            &[0xff; 2]
+        );
+    }
+
+    #[test]
+    fn test_drop_cached_page_on_reset() {
+        let mut buffer = SwappingBuffer::<RAM, FLASH, _>::new(MockNvm::new_static());
+        buffer.write(&[1; RAM + 1]).unwrap();
+        assert_eq!(buffer.nvm_write_count(), 1);
+        buffer.reset(0);
+        buffer.flush().unwrap();
+        assert_eq!(buffer.nvm_write_count(), 1);
+        buffer.write(&[2; RAM + 1]).unwrap();
+        buffer.flush().unwrap();
+        assert_eq!(buffer.nvm_write_count(), 3);
+        assert_eq!(buffer.read_all(), &[2; RAM + 1]);
+
+        buffer.reset_nvm_write_count();
+        assert_eq!(buffer.nvm_write_count(), 0);
+    }
+
+    #[test]
+    fn test_restore_ram_on_reset() {
+        let mut buffer = SwappingBuffer::<RAM, FLASH, _>::new(MockNvm::new_static());
+        buffer.write(&[1; RAM]).unwrap();
+        // the cached page reuses the end of the RAM
+        buffer.write(&[2; 4]).unwrap();
+        buffer.reset(RAM);
+        assert_eq!(buffer.read(0, RAM), &[1; RAM]);
+    }
+
+    #[test]
+    fn test_base58_limbs_in_flash() {
+        let mut buffer = SwappingBuffer::<RAM, FLASH, _>::new(MockNvm::new_static());
+        buffer.write(b"address: ").unwrap();
+        let input: Vec<u8> = (1..=100u8).collect();
+        let mut limbs = vec![0u32; limb_size(input.len() * 2)];
+        let mut output = vec![0u8; input.len() * 2];
+        let expected = base58_encode(&input, &mut limbs[..], &mut output).unwrap();
+
+        let mut encoder = Base58Encoder::new(&mut buffer);
+        for chunk in input.chunks(7) {
+            assert!(encoder.update(chunk));
+        }
+        assert!(encoder.finalize_in_place());
+        buffer.flush().unwrap();
+        assert_eq!(&buffer.read_all()[..9], b"address: ");
+        assert_eq!(&buffer.read_all()[9..], expected);
+    }
+
+    #[test]
+    fn test_read_outside_cached_page() {
+        let mut buffer = SwappingBuffer::<RAM, FLASH, _>::new(MockNvm::new_static());
+        buffer.write(&[1; RAM]).unwrap();
+        buffer.write(&[2; 4]).unwrap();
+        // the first page can be read while the second page is cached
+        assert_eq!(buffer.read(0, NVM_PAGE_SIZE), &[1; NVM_PAGE_SIZE]);
+        assert_eq!(buffer.nvm_write_count(), 1);
+    }
+}
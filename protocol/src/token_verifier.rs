@@ -1,7 +1,7 @@
 use crate::{
-    blake2b_hasher::{Blake2bHash, Blake2bHasher, BLAKE2B_HASH_SIZE},
     error_code::ErrorCode,
-    handler::TOKEN_METADATA_SIZE,
+    platform::{Blake2bHash, Hasher, BLAKE2B_HASH_SIZE},
+    sign_tx::TOKEN_METADATA_SIZE,
 };
 
 // b3380866c595544781e9da0ccd79399de8878abfb0bf40545b57a287387d419d
@@ -19,9 +19,9 @@ pub struct TokenVerifier {
     hash: Blake2bHash,
 }
 
-pub fn hash_pair(a: &[u8], b: &[u8]) -> Result<Blake2bHash, ErrorCode> {
+pub fn hash_pair<H: Hasher>(a: &[u8], b: &[u8]) -> Result<Blake2bHash, ErrorCode> {
     assert!(a.len() == BLAKE2B_HASH_SIZE && b.len() == BLAKE2B_HASH_SIZE);
-    let mut hasher = Blake2bHasher::new();
+    let mut hasher = H::new();
     if a < b {
         hasher.update(a)?;
         hasher.update(b)?;
@@ -33,7 +33,7 @@ pub fn hash_pair(a: &[u8], b: &[u8]) -> Result<Blake2bHash, ErrorCode> {
 }
 
 impl TokenVerifier {
-    pub fn new(data: &[u8]) -> Result<TokenVerifier, ErrorCode> {
+    pub fn new<H: Hasher>(data: &[u8]) -> Result<TokenVerifier, ErrorCode> {
         let prefix_length = TOKEN_METADATA_SIZE + PROOF_PREFIX_LENGTH;
         if data.len() < prefix_length {
             return Err(ErrorCode::BadLen);
@@ -46,15 +46,15 @@ impl TokenVerifier {
 
         let mut verifier = TokenVerifier {
             remaining_proof_size: proof_size,
-            hash: Blake2bHasher::hash(encoded_token)?,
+            hash: H::hash(encoded_token)?,
         };
         let proof = &data[prefix_length..];
-        verifier.on_proof(proof)?;
+        verifier.on_proof::<H>(proof)?;
         Ok(verifier)
     }
 
     // update the hash when receiving token proof data
-    pub fn on_proof(&mut self, proof: &[u8]) -> Result<(), ErrorCode> {
+    pub fn on_proof<H: Hasher>(&mut self, proof: &[u8]) -> Result<(), ErrorCode> {
         check_proof_size(proof.len())?;
         if self.remaining_proof_size < proof.len() {
             return Err(ErrorCode::InvalidTokenProofSize);
@@ -63,7 +63,7 @@ impl TokenVerifier {
         let mut index: usize = 0;
         while index < proof.len() {
             let sibling = &proof[index..(index + BLAKE2B_HASH_SIZE)];
-            self.hash = hash_pair(&self.hash, sibling)?;
+            self.hash = hash_pair::<H>(&self.hash, sibling)?;
             index += BLAKE2B_HASH_SIZE
         }
         self.remaining_proof_size -= proof.len();
@@ -82,7 +82,7 @@ impl TokenVerifier {
 }
 
 fn check_proof_size(size: usize) -> Result<(), ErrorCode> {
-    if size % BLAKE2B_HASH_SIZE != 0 {
+    if !size.is_multiple_of(BLAKE2B_HASH_SIZE) {
         Err(ErrorCode::InvalidTokenProofSize)
     } else {
         Ok(())
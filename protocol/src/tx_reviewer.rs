@@ -0,0 +1,1121 @@
+use core::marker::PhantomData;
+
+use crate::{
+    address::{Address, GROUP_NUM, HASH_ADDRESS_SIZE},
+    bytes_to_string,
+    error_code::ErrorCode,
+    output_summary::OutputSummary,
+    platform::{Blake2bHash, Field, Hasher, NvmStorage, ReviewUi, Storage},
+    script_disassembler::{
+        write_asset_modifier, write_instr, ScriptDisassembler, ScriptField, ScriptItem, ScriptPage,
+        SCRIPT_PAGE_BUFFER_SIZE,
+    },
+    sign_tx::TOKEN_METADATA_SIZE,
+    swapping_buffer::{SwappingBuffer, NVM_DATA_SIZE, NVM_PAGE_SIZE, RAM_SIZE},
+    token_verifier::TokenVerifier,
+};
+use utils::{
+    base58::Base58Encoder,
+    buffer::{Buffer, SkipTempData},
+    decode::{new_decoder, Decoder, RawDecoder},
+    types::{
+        unlock_script::PublicKeyWithIndex, AssetOutput, Byte32, Hash, LockupScript, PublicKey,
+        TxInput, UnlockScript, UnsignedTx, I32, U16, U256,
+    },
+};
+
+const FIRST_OUTPUT_INDEX: u16 = 1;
+const MAX_TOKEN_SYMBOL_LENGTH: usize = 12;
+const TOKEN_METADATA_VERSION: u8 = 0;
+pub const MAX_OUTPUT_FIELD_SIZE: usize = 6;
+// The fees, the gas amount and the gas price in expert mode, and the tx ID
+pub const MAX_APPROVE_FIELD_SIZE: usize = 4;
+const SAVED_ADDRESS_SUFFIX: &[u8] = b" (saved)";
+const GROUP_FIELD_NAMES: [&str; GROUP_NUM as usize] = ["Group 0", "Group 1", "Group 2", "Group 3"];
+// The minimal ALPH amount of an output, the full node rejects outputs with less ALPH
+const DUST_UTXO_AMOUNT: u128 = 1_000_000_000_000_000; // 0.001 ALPH
+type TokenSymbol = [u8; MAX_TOKEN_SYMBOL_LENGTH];
+
+type ReviewBuffer<F> = SwappingBuffer<'static, RAM_SIZE, NVM_DATA_SIZE, F>;
+
+// The TxReviewer is used to review the transaction details
+// It keeps track of the transaction details and the current state
+// It also keeps track of the token metadata
+pub struct TxReviewer<H, U, S, F: NvmStorage<NVM_DATA_SIZE> + 'static> {
+    buffer: ReviewBuffer<F>,
+    has_external_inputs: bool,
+    next_output_index: u16,
+    tx_fee: Option<U256>,
+    // The gas amount and gas price are displayed in expert mode
+    gas_amount: i32,
+    gas_price: Option<U256>,
+    token_metadata_length: usize,
+    token_verifier: Option<TokenVerifier>,
+    // The encoded script is recorded after the token metadata, it is not displayed if the script
+    // and the rendered page exceed the buffer
+    script_length: usize,
+    script_too_large: bool,
+    script_hash: Option<Blake2bHash>,
+    is_review_started: bool,
+    // The outputs are summed up instead of reviewed one by one if the user chooses to
+    output_summary: Option<OutputSummary>,
+    // The change outputs are also reviewed if the user chooses to review the outputs one by one,
+    // so the user sees as many output screens as the outputs of the prompt
+    review_change_outputs: bool,
+    ui: U,
+    storage: S,
+    hasher: PhantomData<H>,
+}
+
+impl<H: Hasher, U: ReviewUi, S: Storage, F: NvmStorage<NVM_DATA_SIZE>> TxReviewer<H, U, S, F> {
+    pub fn new(ui: U, storage: S, flash: &'static mut F) -> Self {
+        Self {
+            buffer: SwappingBuffer::new(flash),
+            has_external_inputs: false,
+            next_output_index: FIRST_OUTPUT_INDEX, // display output from index 1, similar to BTC
+            tx_fee: None,
+            gas_amount: 0,
+            gas_price: None,
+            token_metadata_length: 0,
+            token_verifier: None,
+            script_length: 0,
+            script_too_large: false,
+            script_hash: None,
+            is_review_started: false,
+            output_summary: None,
+            review_change_outputs: false,
+            ui,
+            storage,
+            hasher: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn ui(&self) -> &U {
+        &self.ui
+    }
+
+    #[inline]
+    pub fn ui_mut(&mut self) -> &mut U {
+        &mut self.ui
+    }
+
+    // Tell the user why the tx is rejected, so the signing does not fail silently
+    #[inline]
+    pub fn warning_tx_rejected(&mut self, reason: &str) {
+        self.ui.warning_tx_rejected(reason);
+    }
+
+    // The number of NVM writes of the review buffer for the current tx
+    #[inline]
+    pub fn nvm_write_count(&self) -> usize {
+        self.buffer.nvm_write_count()
+    }
+
+    #[inline]
+    fn reset_buffer(&mut self, from_index: usize) {
+        self.buffer.reset(from_index);
+    }
+
+    #[inline]
+    pub fn init(&mut self, token_size: u8) -> Result<(), ErrorCode> {
+        self.reset_buffer(0);
+        self.buffer.reset_nvm_write_count();
+        self.has_external_inputs = false;
+        self.next_output_index = FIRST_OUTPUT_INDEX;
+        self.tx_fee = None;
+        self.gas_amount = 0;
+        self.gas_price = None;
+        self.token_metadata_length = (token_size as usize) * TOKEN_METADATA_SIZE;
+        self.token_verifier = None;
+        self.script_length = 0;
+        self.script_too_large = false;
+        self.script_hash = None;
+        self.is_review_started = false;
+        self.output_summary = None;
+        self.review_change_outputs = false;
+        self.ui.reset();
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.reset_buffer(0);
+        self.has_external_inputs = false;
+        self.next_output_index = FIRST_OUTPUT_INDEX;
+        self.tx_fee = None;
+        self.gas_amount = 0;
+        self.gas_price = None;
+        self.token_metadata_length = 0;
+        self.token_verifier = None;
+        self.script_length = 0;
+        self.script_too_large = false;
+        self.script_hash = None;
+        self.is_review_started = false;
+        self.output_summary = None;
+        self.review_change_outputs = false;
+        self.ui.reset();
+    }
+
+    pub fn handle_token_metadata(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        assert!(self.token_verifier.is_none());
+        let token_verifier = TokenVerifier::new::<H>(data)?;
+        // we have checked the data size in `TokenVerifier::new(data)`
+        let token_metadata = &data[..TOKEN_METADATA_SIZE];
+        if token_metadata[0] != TOKEN_METADATA_VERSION {
+            // the first byte is the metadata version
+            return Err(ErrorCode::InvalidMetadataVersion);
+        }
+        self.write_token_metadata(token_metadata)?;
+        if !token_verifier.is_complete() {
+            self.token_verifier = Some(token_verifier);
+            return Ok(());
+        }
+        if token_verifier.is_token_valid() {
+            Ok(())
+        } else {
+            Err(ErrorCode::InvalidTokenMetadata)
+        }
+    }
+
+    pub fn handle_token_proof(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        assert!(self.token_verifier.is_some());
+        let token_verifier = self.token_verifier.as_mut().unwrap();
+        token_verifier.on_proof::<H>(data)?;
+        if !token_verifier.is_complete() {
+            return Ok(());
+        }
+        let result = if token_verifier.is_token_valid() {
+            Ok(())
+        } else {
+            Err(ErrorCode::InvalidTokenMetadata)
+        };
+        self.token_verifier = None;
+        result
+    }
+
+    fn write_token_metadata(&mut self, token_metadata: &[u8]) -> Result<(), ErrorCode> {
+        let size = self.buffer.write(token_metadata)?;
+        if size > self.token_metadata_length {
+            Err(ErrorCode::InvalidTokenSize)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
+        self.ui.set_tx_execute_script(is_tx_execute_script);
+    }
+
+    // Record the encoded script when decoding the tx, so that it can be disassembled for review
+    pub fn record_script(&mut self, bytes: &[u8]) -> Result<(), ErrorCode> {
+        if self.script_too_large || bytes.is_empty() {
+            return Ok(());
+        }
+        let script_to_index = self.token_metadata_length + self.script_length + bytes.len();
+        if script_to_index + SCRIPT_PAGE_BUFFER_SIZE > NVM_DATA_SIZE {
+            self.script_too_large = true;
+            return Ok(());
+        }
+        self.buffer.write(bytes)?;
+        self.script_length += bytes.len();
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_script_hash(&mut self, script_hash: Blake2bHash) {
+        self.script_hash = Some(script_hash);
+    }
+
+    // The review is started by the script hash of a blind-signed tx, or by the first output
+    fn start_review(&mut self) -> Result<(), ErrorCode> {
+        if self.is_review_started {
+            return Ok(());
+        }
+        self.is_review_started = true;
+        self.ui.start_review()
+    }
+
+    // Review the script hash so that the user can compare it with the one displayed by the dApp
+    fn review_script_hash(&mut self) -> Result<(), ErrorCode> {
+        assert!(self.script_hash.is_some());
+        let hex_str: [u8; 64] = utils::to_hex(self.script_hash.as_ref().unwrap()).unwrap();
+        let fields = &[Field {
+            name: "Script Hash",
+            value: bytes_to_string(&hex_str)?,
+        }];
+        self.start_review()?;
+        self.ui.review_fields(fields, "Script")
+    }
+
+    // Review the disassembled script in expert mode if the user chooses to display it
+    fn review_script(&mut self) -> Result<(), ErrorCode> {
+        if !self.storage.is_expert_mode_enabled() {
+            return Ok(());
+        }
+        if self.script_too_large {
+            return self.ui.warning_script_too_large();
+        }
+        if !self.ui.ask_show_script()? {
+            return Ok(());
+        }
+        // Skip the option flag of the script
+        let from_index = self.token_metadata_length + 1;
+        let to_index = self.token_metadata_length + self.script_length;
+        self.buffer.flush()?;
+        // The pages are rendered from the next flash page after the script, so that the script
+        // can be read while the cached page of the rendered fields is not flushed
+        let mut page = ScriptPage::new(to_index.next_multiple_of(NVM_PAGE_SIZE));
+        let mut disassembler = ScriptDisassembler::default();
+        while let Some(item) = disassembler.next_item(self.buffer.read(from_index, to_index))? {
+            match item {
+                ScriptItem::Method {
+                    index,
+                    is_public,
+                    asset_modifier,
+                } => {
+                    page.review(&mut self.buffer, &mut self.ui)?;
+                    page.set_method(index);
+                    let public = ScriptField::new(
+                        |name| name.write(b"Public"),
+                        |value| value.write(if is_public { b"Yes" } else { b"No" }),
+                    );
+                    page.add_field(&mut self.buffer, &public)?;
+                    let asset_modifier = ScriptField::new(
+                        |name| name.write(b"Asset Modifier"),
+                        |value| write_asset_modifier(value, asset_modifier),
+                    );
+                    page.add_field(&mut self.buffer, &asset_modifier)?;
+                    page.review(&mut self.buffer, &mut self.ui)?;
+                }
+                ScriptItem::Instr {
+                    index,
+                    instr,
+                    encoded,
+                } => {
+                    let field = ScriptField::new(
+                        |name| {
+                            name.write(b"Instr #");
+                            name.write_index(index);
+                        },
+                        |value| write_instr(value, &instr, encoded),
+                    );
+                    page.add_field(&mut self.buffer, &field)?;
+                    if page.is_full() {
+                        page.review(&mut self.buffer, &mut self.ui)?;
+                    }
+                }
+            }
+        }
+        page.review(&mut self.buffer, &mut self.ui)
+    }
+
+    // Write the amount in alph format, the unit and digit grouping follow the user settings
+    fn write_alph_amount(&mut self, u256: &U256) -> Result<usize, ErrorCode> {
+        let mut amount_output = [0u8; 64];
+        let amount_str = u256
+            .to_alph_with_format(&mut amount_output, &self.storage.amount_format())
+            .ok_or(ErrorCode::Overflow)?;
+        self.buffer.write(amount_str)
+    }
+
+    // Write the amount in raw format
+    fn write_token_raw_amount(&mut self, u256: &U256) -> Result<usize, ErrorCode> {
+        let mut amount_output = [0u8; 104]; // u256 max with the thousands separators
+        let amount_str = u256
+            .to_str_with_format(&mut amount_output, 0, &self.storage.amount_format())
+            .ok_or(ErrorCode::Overflow)?;
+        self.buffer.write(amount_str)
+    }
+
+    // Write the amount in token format
+    fn write_token_amount(
+        &mut self,
+        u256: &U256,
+        symbol: TokenSymbol,
+        decimals: usize,
+    ) -> Result<usize, ErrorCode> {
+        let mut amount_output = [0u8; 128]; // u256 max with the thousands separators
+        let symbol_bytes = get_token_symbol_bytes(&symbol[..]);
+        amount_output[..symbol_bytes.len()].copy_from_slice(symbol_bytes);
+        amount_output[symbol_bytes.len()] = b' ';
+        let prefix_length = symbol_bytes.len() + 1;
+        let amount_str = u256.to_str_with_format(
+            &mut amount_output[prefix_length..],
+            decimals,
+            &self.storage.amount_format(),
+        );
+        if amount_str.is_none() {
+            return Err(ErrorCode::Overflow);
+        }
+        let total_length = prefix_length + amount_str.unwrap().len();
+        self.buffer.write(&amount_output[..total_length])
+    }
+
+    // Write the token id in hex format
+    fn write_token_id(&mut self, token_id: &Byte32) -> Result<usize, ErrorCode> {
+        let hex_str: [u8; 64] = utils::to_hex(&token_id.0).unwrap();
+        self.buffer.write(&hex_str)
+    }
+
+    // Write the address, the multi-sig address is too long to be rendered in RAM,
+    // so it is encoded in the swapping buffer
+    fn write_address(&mut self, address: &Address) -> Result<usize, ErrorCode> {
+        match address {
+            Address::Hash { .. } => {
+                let mut output = [0u8; HASH_ADDRESS_SIZE];
+                let str_bytes = address.render(&mut output)?;
+                self.buffer.write(str_bytes)
+            }
+            Address::MultiSig { encoded, .. } => {
+                // The limbs are kept in the buffer after the encoded string
+                let mut encoder = Base58Encoder::new(&mut self.buffer);
+                if !encoder.update(encoded) || !encoder.finalize_in_place() {
+                    return Err(ErrorCode::Overflow);
+                }
+                Ok(self.buffer.get_index())
+            }
+        }
+    }
+
+    // Write the threshold of the multi-sig address, e.g. "2-of-3"
+    // The keys are reviewed on separate pages
+    fn write_multi_sig_threshold(&mut self, m: u16, size: u16) -> Result<usize, ErrorCode> {
+        self.write_number(m as usize)?;
+        self.buffer.write(b"-of-")?;
+        self.write_number(size as usize)
+    }
+
+    // Write the number in decimal format
+    fn write_number(&mut self, number: usize) -> Result<usize, ErrorCode> {
+        let mut output = [0u8; 10];
+        let number_str = I32::unsafe_from(number)
+            .to_str(&mut output)
+            .ok_or(ErrorCode::Overflow)?;
+        self.buffer.write(number_str)
+    }
+
+    // Write the review progress of the output, e.g. "Output 2 of 10", the index starts from 1 similar to BTC
+    // The outputs are numbered by their position in the tx, which is known when the output is streamed,
+    // the number of outputs sent to the device address is not. So the numbers of the skipped change
+    // outputs are not displayed, unless the user chooses to review all the outputs one by one
+    fn write_output_index(&mut self, index: usize, size: usize) -> Result<usize, ErrorCode> {
+        self.buffer.write(b"Output ")?;
+        self.write_number(index + 1)?;
+        self.buffer.write(b" of ")?;
+        self.write_number(size)
+    }
+
+    // Write the label of the saved address
+    // The label is borrowed from the storage, so only the buffer is borrowed mutably
+    fn write_address_label(buffer: &mut ReviewBuffer<F>, label: &[u8]) -> Result<usize, ErrorCode> {
+        buffer.write(label)?;
+        buffer.write(SAVED_ADDRESS_SUFFIX)
+    }
+
+    fn get_token_metadata(&self, token_id: &Hash) -> Option<(TokenSymbol, u8)> {
+        let token_size = self.token_metadata_length / TOKEN_METADATA_SIZE;
+        if token_size == 0 {
+            return None;
+        }
+        for i in 0..token_size {
+            let from_index = i * TOKEN_METADATA_SIZE;
+            let to_index = from_index + TOKEN_METADATA_SIZE;
+            let token_metadata_bytes = self.buffer.read(from_index, to_index);
+            if token_metadata_bytes[1..33] == token_id.0 {
+                let last_index = TOKEN_METADATA_SIZE - 1; // the last index of the encoded token metadata
+                let token_symbol = token_metadata_bytes[33..last_index].try_into().unwrap();
+                let token_decimals = token_metadata_bytes[last_index];
+                return Some((token_symbol, token_decimals));
+            }
+        }
+        None
+    }
+
+    // Prepare the output for review
+    fn prepare_output(
+        &mut self,
+        output: &AssetOutput,
+        address: &Address,
+        output_index: usize,
+        output_size: usize,
+    ) -> Result<OutputIndexes, ErrorCode> {
+        let address_from_index = self.buffer.get_index();
+        let address_to_index = self.write_address(address)?;
+        let address_label = match address.hash() {
+            Some(hash) => self.storage.find_address_label(address.get_type(), hash),
+            None => None,
+        };
+        let address_label_indexes = match address_label {
+            Some(label) => {
+                let label_from_index = self.buffer.get_index();
+                let label_to_index = Self::write_address_label(&mut self.buffer, label)?;
+                Some((label_from_index, label_to_index))
+            }
+            None => None,
+        };
+
+        let multi_sig_threshold_indexes = match address {
+            Address::MultiSig { size, m, .. } => {
+                let threshold_from_index = self.buffer.get_index();
+                let threshold_to_index = self.write_multi_sig_threshold(*m, *size)?;
+                Some((threshold_from_index, threshold_to_index))
+            }
+            Address::Hash { .. } => None,
+        };
+
+        let review_message_from_index = self.buffer.get_index();
+        let review_message_to_index = self.write_output_index(output_index, output_size)?;
+
+        let alph_amount_from_index = self.buffer.get_index();
+        let alph_amount_to_index = self.write_alph_amount(&output.amount)?;
+
+        let output_indexes = OutputIndexes {
+            review_message: (review_message_from_index, review_message_to_index),
+            alph_amount: (alph_amount_from_index, alph_amount_to_index),
+            address: (address_from_index, address_to_index),
+            address_label: address_label_indexes,
+            multi_sig_threshold: multi_sig_threshold_indexes,
+            token: None,
+        };
+        if output.tokens.is_empty() {
+            return Ok(output_indexes);
+        }
+
+        // Asset output has at most one token
+        let token = output.tokens.get_current_item().unwrap();
+        let token_indexes = self.prepare_token(&token.id, &token.amount)?;
+        Ok(OutputIndexes {
+            token: Some(token_indexes),
+            ..output_indexes
+        })
+    }
+
+    // Prepare the token for review
+    fn prepare_token(&mut self, token_id: &Hash, amount: &U256) -> Result<TokenIndexes, ErrorCode> {
+        let token_id_from_index = self.buffer.get_index();
+        let token_id_to_index = self.write_token_id(token_id)?;
+        self.buffer.flush()?;
+        match self.get_token_metadata(token_id) {
+            Some((token_symbol, token_decimals)) => {
+                let token_amount_from_index = self.buffer.get_index();
+                let token_amount_to_index =
+                    self.write_token_amount(amount, token_symbol, token_decimals as usize)?;
+                Ok(TokenIndexes {
+                    has_token_metadata: true,
+                    token_id: (token_id_from_index, token_id_to_index),
+                    token_amount: (token_amount_from_index, token_amount_to_index),
+                })
+            }
+            None => {
+                let token_amount_from_index = self.buffer.get_index();
+                let token_amount_to_index = self.write_token_raw_amount(amount)?;
+                Ok(TokenIndexes {
+                    has_token_metadata: false,
+                    token_id: (token_id_from_index, token_id_to_index),
+                    token_amount: (token_amount_from_index, token_amount_to_index),
+                })
+            }
+        }
+    }
+
+    // Review the input for the transaction
+    pub fn review_input(
+        &mut self,
+        input: &TxInput,
+        current_index: usize,
+        input_size: usize,
+        device_address: &Address,
+        temp_data: &[u8],
+    ) -> Result<(), ErrorCode> {
+        assert!(current_index < input_size);
+        match &input.unlock_script {
+            UnlockScript::P2PKH(public_key) => {
+                if !self.has_external_inputs {
+                    self.has_external_inputs = !Self::is_device_key(public_key, device_address)?
+                }
+            }
+            UnlockScript::P2MPKH(_) => self.review_p2mpkh_input(temp_data, device_address)?,
+            UnlockScript::P2SH(p2sh) => {
+                // The P2SH decoder writes the revealed script to the temp data
+                let address = Address::from_unlock_script::<H>(&input.unlock_script, temp_data)?
+                    .ok_or(ErrorCode::InternalError)?;
+                self.review_p2sh_input(&address, p2sh.inner.args_size())?
+            }
+            UnlockScript::SameAsPrevious => (),
+            _ => panic!(),
+        };
+
+        if (current_index == input_size - 1) && self.has_external_inputs {
+            self.ui.warning_external_inputs()?;
+        }
+        Ok(())
+    }
+
+    fn is_device_key(public_key: &PublicKey, device_address: &Address) -> Result<bool, ErrorCode> {
+        Ok(Address::p2pkh(H::hash(&public_key.0)?) == *device_address)
+    }
+
+    // The P2MPKH decoder writes the encoded signer keys to the temp data, the multisig input is displayed
+    // instead of the external inputs warning if the device key is one of the signers
+    // The unlock script does not contain the keys of the multisig address, so only the signers are displayed
+    fn review_p2mpkh_input(
+        &mut self,
+        encoded_keys: &[u8],
+        device_address: &Address,
+    ) -> Result<(), ErrorCode> {
+        let mut temp_data = SkipTempData;
+        let mut buffer = Buffer::new(encoded_keys, &mut temp_data);
+        let signer_size = decode_item::<U16>(&mut buffer)?.inner as usize;
+        let mut device_signer = None;
+        for signer_index in 0..signer_size {
+            let key = decode_item::<PublicKeyWithIndex>(&mut buffer)?;
+            if device_signer.is_none() && Self::is_device_key(&key.public_key, device_address)? {
+                device_signer = Some((signer_index, key.index.inner as usize));
+            }
+        }
+        let (signer_index, key_index) = match device_signer {
+            Some(signer) => signer,
+            None => {
+                self.has_external_inputs = true;
+                return Ok(());
+            }
+        };
+
+        let mut signer_size_bytes = [0u8; 10];
+        let mut signer_index_bytes = [0u8; 11];
+        let mut key_index_bytes = [0u8; 10];
+        signer_index_bytes[0] = b'#';
+        let signer_size_str = I32::unsafe_from(signer_size)
+            .to_str(&mut signer_size_bytes)
+            .ok_or(ErrorCode::Overflow)?;
+        let signer_index_length = I32::unsafe_from(signer_index + 1)
+            .to_str(&mut signer_index_bytes[1..])
+            .ok_or(ErrorCode::Overflow)?
+            .len();
+        let key_index_str = I32::unsafe_from(key_index)
+            .to_str(&mut key_index_bytes)
+            .ok_or(ErrorCode::Overflow)?;
+        let fields = &[
+            Field {
+                name: "Signatures",
+                value: bytes_to_string(signer_size_str)?,
+            },
+            Field {
+                name: "Signer",
+                value: bytes_to_string(&signer_index_bytes[..(signer_index_length + 1)])?,
+            },
+            Field {
+                name: "Key Index",
+                value: bytes_to_string(key_index_str)?,
+            },
+        ];
+        self.start_review()?;
+        self.ui.review_fields(fields, "Multisig input")
+    }
+
+    // The script address is displayed instead of the external inputs warning
+    // since the user can check the address
+    fn review_p2sh_input(&mut self, address: &Address, args_size: usize) -> Result<(), ErrorCode> {
+        self.start_review()?;
+        let mut address_bytes = [0u8; HASH_ADDRESS_SIZE];
+        let address_str = address.render(&mut address_bytes)?;
+        let mut args_size_bytes = [0u8; 10];
+        let args_size_str = I32::unsafe_from(args_size)
+            .to_str(&mut args_size_bytes)
+            .ok_or(ErrorCode::Overflow)?;
+        let address_field = Field {
+            name: "Address",
+            value: bytes_to_string(address_str)?,
+        };
+        let args_size_field = Field {
+            name: "Unlock Arguments",
+            value: bytes_to_string(args_size_str)?,
+        };
+        let review_message = "Spending from script address";
+        let label = match address.hash() {
+            Some(hash) => self.storage.find_address_label(address.get_type(), hash),
+            None => None,
+        };
+        match label {
+            Some(label) => {
+                let label_field = Field {
+                    name: "Label",
+                    value: bytes_to_string(label)?,
+                };
+                self.ui.review_fields(
+                    &[label_field, address_field, args_size_field],
+                    review_message,
+                )
+            }
+            None => self
+                .ui
+                .review_fields(&[address_field, args_size_field], review_message),
+        }
+    }
+
+    // Review the output for the transaction
+    pub fn review_output(
+        &mut self,
+        output: &AssetOutput,
+        output_index: usize,
+        output_size: usize,
+        device_address: &Address,
+        temp_data: &[u8],
+    ) -> Result<(), ErrorCode> {
+        if !is_valid_output(output) {
+            self.ui.warning_invalid_output();
+            return Err(ErrorCode::InvalidOutput);
+        }
+        let recipient = Address::from_lockup_script(&output.lockup_script, temp_data)?;
+        if recipient == *device_address {
+            if !self.review_change_outputs {
+                return Ok(());
+            }
+        } else {
+            self.next_output_index += 1;
+        }
+        if let Some(output_summary) = self.output_summary.as_mut() {
+            return output_summary.add_output(recipient.group()?, output);
+        }
+
+        let OutputIndexes {
+            review_message,
+            alph_amount,
+            address,
+            address_label,
+            multi_sig_threshold,
+            token,
+        } = self.prepare_output(output, &recipient, output_index, output_size)?;
+        self.buffer.flush()?;
+        let buffer = &self.buffer;
+        let review_message = get_str_from_range(buffer, review_message)?;
+        let mut fields: [Field; MAX_OUTPUT_FIELD_SIZE] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        let mut field_size = 0;
+        if self.ui.output_index_as_field() {
+            fields[field_size] = Field {
+                name: "Transaction Output",
+                value: review_message,
+            };
+            field_size += 1;
+        }
+
+        if let Some(TokenIndexes {
+            has_token_metadata,
+            token_id,
+            token_amount,
+        }) = token
+        {
+            let amount_name = if has_token_metadata {
+                "Token Amount"
+            } else {
+                "Raw Token Amount"
+            };
+            fields[field_size] = Field {
+                name: "Token ID",
+                value: get_str_from_range(buffer, token_id)?,
+            };
+            fields[field_size + 1] = Field {
+                name: amount_name,
+                value: get_str_from_range(buffer, token_amount)?,
+            };
+            field_size += 2;
+        }
+
+        fields[field_size] = Field {
+            name: "Amount",
+            value: get_str_from_range(buffer, alph_amount)?,
+        };
+        field_size += 1;
+
+        let address = get_str_from_range(buffer, address)?;
+        match address_label {
+            Some(address_label) => {
+                fields[field_size] = Field {
+                    name: "To",
+                    value: get_str_from_range(buffer, address_label)?,
+                };
+                fields[field_size + 1] = Field {
+                    name: "Address",
+                    value: address,
+                };
+                field_size += 2;
+            }
+            None => {
+                fields[field_size] = Field {
+                    name: "To",
+                    value: address,
+                };
+                field_size += 1;
+            }
+        }
+        if let Some(multi_sig_threshold) = multi_sig_threshold {
+            fields[field_size] = Field {
+                name: "Multisig",
+                value: get_str_from_range(buffer, multi_sig_threshold)?,
+            };
+            field_size += 1;
+        }
+        self.ui
+            .review_fields(&fields[..field_size], review_message)?;
+        if let LockupScript::P2MPKH(_) = &output.lockup_script {
+            self.review_multi_sig_keys(temp_data, device_address)?;
+        }
+        Ok(())
+    }
+
+    // The P2MPKH decoder writes the encoded lockup script to the temp data, the address of each key
+    // is displayed on its own page, and the key of the device is marked
+    fn review_multi_sig_keys(
+        &mut self,
+        encoded_lockup_script: &[u8],
+        device_address: &Address,
+    ) -> Result<(), ErrorCode> {
+        let mut temp_data = SkipTempData;
+        // Skip the lockup script type
+        let mut buffer = Buffer::new(&encoded_lockup_script[1..], &mut temp_data);
+        let key_size = decode_item::<U16>(&mut buffer)?.inner as usize;
+        for key_index in 0..key_size {
+            let public_key_hash = decode_item::<Hash>(&mut buffer)?;
+            let address = Address::p2pkh(public_key_hash.0);
+            let is_device_key = address == *device_address;
+            let mut address_bytes = [0u8; HASH_ADDRESS_SIZE];
+            let address_str = address.render(&mut address_bytes)?;
+
+            let prefix = b"Multisig key #";
+            let mut review_message = [0u8; 24];
+            review_message[..prefix.len()].copy_from_slice(prefix);
+            let index_length = I32::unsafe_from(key_index + 1)
+                .to_str(&mut review_message[prefix.len()..])
+                .ok_or(ErrorCode::Overflow)?
+                .len();
+            let review_message = bytes_to_string(&review_message[..(prefix.len() + index_length)])?;
+            let fields = [
+                Field {
+                    name: "Address",
+                    value: bytes_to_string(address_str)?,
+                },
+                Field {
+                    name: "Owner",
+                    value: "This device",
+                },
+            ];
+            let field_size = if is_device_key { 2 } else { 1 };
+            self.ui
+                .review_fields(&fields[..field_size], review_message)?;
+        }
+        Ok(())
+    }
+
+    // Review the transaction details
+    pub fn review_tx_details(
+        &mut self,
+        unsigned_tx: &UnsignedTx,
+        device_address: &Address,
+        temp_data: &[u8],
+    ) -> Result<(), ErrorCode> {
+        match unsigned_tx {
+            UnsignedTx::NetworkId(network_id) => self.check_network_id(network_id.0),
+            UnsignedTx::ScriptOpt(script) => {
+                let result = if script.inner.is_some() {
+                    self.review_script_hash().and_then(|_| self.review_script())
+                } else {
+                    Ok(())
+                };
+                self.reset_buffer(self.token_metadata_length);
+                result
+            }
+            UnsignedTx::TxFee(tx_fee) => {
+                let fee = tx_fee.inner.get();
+                if fee.is_none() {
+                    return Err(ErrorCode::Overflow);
+                }
+                self.tx_fee = Some(fee.as_ref().unwrap().clone());
+                self.gas_amount = tx_fee.inner.gas_amount.inner;
+                self.gas_price = Some(tx_fee.inner.gas_price.clone());
+                Ok(())
+            }
+            UnsignedTx::Inputs(inputs) => {
+                if let Some(current_input) = inputs.get_current_item() {
+                    self.review_input(
+                        current_input,
+                        inputs.current_index as usize,
+                        inputs.size(),
+                        device_address,
+                        temp_data,
+                    )
+                } else {
+                    Ok(())
+                }
+            }
+            UnsignedTx::FixedOutputs(outputs) => {
+                if let Some(current_output) = outputs.get_current_item() {
+                    let output_index = outputs.current_index as usize;
+                    let output_size = outputs.size();
+                    if output_index == 0 {
+                        self.start_review()?;
+                        self.init_output_summary(output_size)?;
+                    }
+                    let result = self
+                        .review_output(
+                            current_output,
+                            output_index,
+                            output_size,
+                            device_address,
+                            temp_data,
+                        )
+                        .and_then(|_| {
+                            if output_index == output_size - 1 {
+                                self.review_output_summary()
+                            } else {
+                                Ok(())
+                            }
+                        });
+                    self.reset_buffer(self.token_metadata_length);
+                    result
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // The outputs are summed up if the tx has more outputs than the threshold in the settings,
+    // and the user chooses not to review the outputs one by one
+    fn init_output_summary(&mut self, output_size: usize) -> Result<(), ErrorCode> {
+        self.output_summary = match self.storage.output_summary_threshold() {
+            Some(threshold) if output_size > threshold => {
+                if self.ui.ask_review_outputs(output_size)? {
+                    self.review_change_outputs = true;
+                    None
+                } else {
+                    Some(OutputSummary::default())
+                }
+            }
+            _ => None,
+        };
+        Ok(())
+    }
+
+    // Review the ALPH amount sent to each group, then the amount of each token
+    fn review_output_summary(&mut self) -> Result<(), ErrorCode> {
+        let output_summary = match self.output_summary.take() {
+            // The tx is a self-transfer if all the outputs are sent to the device address
+            Some(output_summary) if output_summary.output_size() > 0 => output_summary,
+            _ => return Ok(()),
+        };
+
+        let output_size_from_index = self.buffer.get_index();
+        let output_size_to_index = self.write_number(output_summary.output_size())?;
+        let mut alph_amounts: [Option<(usize, usize)>; GROUP_NUM as usize] =
+            [None; GROUP_NUM as usize];
+        for (group, amount) in output_summary.alph_amounts().iter().enumerate() {
+            if *amount != 0 {
+                let from_index = self.buffer.get_index();
+                let to_index = self.write_alph_amount(&U256::from_u128(*amount))?;
+                alph_amounts[group] = Some((from_index, to_index));
+            }
+        }
+        self.buffer.flush()?;
+        let mut fields: [Field; 1 + GROUP_NUM as usize] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        fields[0] = Field {
+            name: "Outputs",
+            value: get_str_from_range(
+                &self.buffer,
+                (output_size_from_index, output_size_to_index),
+            )?,
+        };
+        let mut field_size = 1;
+        for (group, range) in alph_amounts.iter().enumerate() {
+            if let Some(range) = range {
+                fields[field_size] = Field {
+                    name: GROUP_FIELD_NAMES[group],
+                    value: get_str_from_range(&self.buffer, *range)?,
+                };
+                field_size += 1;
+            }
+        }
+        self.ui
+            .review_fields(&fields[..field_size], "Output summary")?;
+
+        for (token_id, amount) in output_summary.tokens() {
+            self.reset_buffer(self.token_metadata_length);
+            let TokenIndexes {
+                has_token_metadata,
+                token_id,
+                token_amount,
+            } = self.prepare_token(&Hash::from_bytes(*token_id), &U256::from_u128(*amount))?;
+            self.buffer.flush()?;
+            let fields = [
+                Field {
+                    name: "Token ID",
+                    value: get_str_from_range(&self.buffer, token_id)?,
+                },
+                Field {
+                    name: if has_token_metadata {
+                        "Token Amount"
+                    } else {
+                        "Raw Token Amount"
+                    },
+                    value: get_str_from_range(&self.buffer, token_amount)?,
+                },
+            ];
+            self.ui.review_fields(&fields, "Token summary")?;
+        }
+        Ok(())
+    }
+
+    // Review the rest transaction details and approve it
+    // The tx ID is displayed on the approval screen if the user enabled it in the settings
+    pub fn approve_tx(&mut self, tx_id: &Blake2bHash) -> Result<(), ErrorCode> {
+        assert!(self.tx_fee.is_some());
+        let amount_format = self.storage.amount_format();
+        let mut amount_output = [0u8; 64];
+        let amount_str = self
+            .tx_fee
+            .as_ref()
+            .unwrap()
+            .to_alph_with_format(&mut amount_output, &amount_format)
+            .ok_or(ErrorCode::Overflow)?;
+        let value = bytes_to_string(amount_str)?;
+        let fee_field = Field {
+            name: "Fees",
+            value,
+        };
+        let mut fields: [Field; MAX_APPROVE_FIELD_SIZE] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        fields[0] = fee_field;
+        let mut field_size = 1;
+
+        let mut gas_amount_output = [0u8; 11];
+        let mut gas_price_output = [0u8; 64];
+        if self.storage.is_expert_mode_enabled() {
+            assert!(self.gas_price.is_some());
+            let gas_amount_str = I32::from(self.gas_amount)
+                .to_str(&mut gas_amount_output)
+                .ok_or(ErrorCode::Overflow)?;
+            let gas_price_str = self
+                .gas_price
+                .as_ref()
+                .unwrap()
+                .to_alph_with_format(&mut gas_price_output, &amount_format)
+                .ok_or(ErrorCode::Overflow)?;
+            fields[field_size] = Field {
+                name: "Gas Amount",
+                value: bytes_to_string(gas_amount_str)?,
+            };
+            fields[field_size + 1] = Field {
+                name: "Gas Price",
+                value: bytes_to_string(gas_price_str)?,
+            };
+            field_size += 2;
+        }
+
+        let tx_id_hex: [u8; 64] = utils::to_hex(tx_id).unwrap();
+        if self.storage.is_tx_id_display_enabled() {
+            fields[field_size] = Field {
+                name: "Tx ID",
+                value: bytes_to_string(&tx_id_hex)?,
+            };
+            field_size += 1;
+        }
+        let fields = &fields[..field_size];
+        if self.next_output_index == FIRST_OUTPUT_INDEX {
+            return self.ui.review_self_transfer(fields);
+        }
+        self.ui.finish_review(fields)
+    }
+
+    // Reject the tx if the user restricts signing to another network in the settings
+    fn check_network_id(&self, network_id: u8) -> Result<(), ErrorCode> {
+        match self.storage.allowed_network_id() {
+            Some(allowed_network_id) if allowed_network_id != network_id => {
+                Err(ErrorCode::InvalidNetworkId)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_blind_signing(&mut self) -> Result<(), ErrorCode> {
+        if self.storage.is_blind_signing_enabled() {
+            return Ok(());
+        }
+        self.ui.warning_blind_signing_disabled();
+        Err(ErrorCode::BlindSigningDisabled)
+    }
+}
+
+// Check the output against the dust rule of the full node, the ALPH amount must not be
+// less than the dust amount and the token amount must not be zero
+fn is_valid_output(output: &AssetOutput) -> bool {
+    let is_dust = output
+        .amount
+        .to_u128()
+        .is_some_and(|amount| amount < DUST_UTXO_AMOUNT);
+    // Asset output has at most one token
+    let has_zero_token_amount = output
+        .tokens
+        .get_current_item()
+        .is_some_and(|token| token.amount.is_zero());
+    !is_dust && !has_zero_token_amount
+}
+
+// Decode an item of the bytes written to the temp data
+fn decode_item<T: Default + RawDecoder>(
+    buffer: &mut Buffer<'_, SkipTempData>,
+) -> Result<T, ErrorCode> {
+    let mut decoder = new_decoder::<T>();
+    if decoder.decode(buffer)?.is_none() {
+        return Err(ErrorCode::TxDecodingInvalidSize);
+    }
+    Ok(decoder.inner)
+}
+
+fn get_str_from_range<F: NvmStorage<NVM_DATA_SIZE>>(
+    buffer: &ReviewBuffer<F>,
+    range: (usize, usize),
+) -> Result<&str, ErrorCode> {
+    let bytes = buffer.read(range.0, range.1);
+    bytes_to_string(bytes)
+}
+
+// Output indexes for review
+// The indexes are used to get the values from the buffer
+// The values are then used to display the transaction details
+// The transaction details are then reviewed by the user
+pub struct OutputIndexes {
+    pub review_message: (usize, usize),
+    pub alph_amount: (usize, usize),
+    pub address: (usize, usize),
+    pub address_label: Option<(usize, usize)>,
+    pub multi_sig_threshold: Option<(usize, usize)>,
+    pub token: Option<TokenIndexes>,
+}
+
+// Token indexes for review
+// The indexes are used to get the values from the buffer
+// The values are then used to display the token details
+// The token details are then reviewed by the user
+pub struct TokenIndexes {
+    pub has_token_metadata: bool,
+    pub token_id: (usize, usize),
+    pub token_amount: (usize, usize),
+}
+
+#[inline]
+fn get_token_symbol_bytes(bytes: &[u8]) -> &[u8] {
+    let mut index = 0;
+    while index < bytes.len() && bytes[index] != 0 {
+        index += 1;
+    }
+    &bytes[..index]
+}
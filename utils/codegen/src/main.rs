@@ -0,0 +1,448 @@
+// Generate `utils/src/types/instr.rs` from the opcode table in `opcodes.txt`
+// Run `cargo run` to update the generated file, or `cargo run -- --check` to check that it is up to date
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::process::{exit, Command, Stdio};
+
+const OPCODE_TABLE: &str = "opcodes.txt";
+const GENERATED_FILE: &str = "../src/types/instr.rs";
+
+// The operand types supported by the decoder, with an encoded value used in the generated tests
+// and the check of the decoded operand `{v}`
+const OPERAND_TYPES: &[(&str, &str, &str)] = &[
+    ("Byte", "7f", "{v}.0 == 0x7f"),
+    ("I32", "80010000", "{v}.inner == 65536"),
+    (
+        "I256",
+        "dc3d43c5d96c11b5ef8d581dd462b8d3ae8e11b9ab7726acc27fe28deee2a60f5a",
+        "{v}.0.bytes[..] == hex_to_bytes(\"dc3d43c5d96c11b5ef8d581dd462b8d3ae8e11b9ab7726acc27fe28deee2a60f5a\").unwrap()[..]",
+    ),
+    (
+        "U256",
+        "dc9303ae010e913964a4478bad3ceea341a1d76f140085f3d4c674e1a10678d74f",
+        "{v}.0.bytes[..] == hex_to_bytes(\"dc9303ae010e913964a4478bad3ceea341a1d76f140085f3d4c674e1a10678d74f\").unwrap()[..]",
+    ),
+    ("ByteString", "00", "{v}.length.inner == 0"),
+    (
+        "LockupScript",
+        "000000000000000000000000000000000000000000000000000000000000000000",
+        "*{v} == LockupScript::P2PKH(Hash::from_bytes([0u8; 32]))",
+    ),
+    ("AVector<ByteString>", "00", "{v}.is_empty()"),
+    ("MethodSelector", "00000000", "{v}.0 == 0"),
+];
+
+struct Opcode {
+    code: u8,
+    name: String,
+    operands: Vec<String>,
+}
+
+impl Opcode {
+    fn variant(&self) -> String {
+        if self.operands.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}({})", self.name, self.operands.join(", "))
+        }
+    }
+
+    fn pattern(&self, ignore_operands: bool) -> String {
+        if self.operands.is_empty() {
+            return format!("Self::{}", self.name);
+        }
+        let operands: Vec<String> = (0..self.operands.len())
+            .map(|index| {
+                if ignore_operands {
+                    String::from("_")
+                } else {
+                    format!("v{}", index)
+                }
+            })
+            .collect();
+        format!("Self::{}({})", self.name, operands.join(", "))
+    }
+
+    fn default_value(&self) -> String {
+        if self.operands.is_empty() {
+            return format!("Self::{}", self.name);
+        }
+        let defaults: Vec<String> = self
+            .operands
+            .iter()
+            .map(|tpe| {
+                let base = tpe.split('<').next().unwrap();
+                format!("{}::default()", base)
+            })
+            .collect();
+        format!("Self::{}({})", self.name, defaults.join(", "))
+    }
+
+    fn encoded_sample(&self) -> String {
+        let mut hex = format!("{:02x}", self.code);
+        for tpe in &self.operands {
+            hex.push_str(operand_sample(tpe).unwrap());
+        }
+        hex
+    }
+}
+
+fn operand_sample(tpe: &str) -> Option<&'static str> {
+    OPERAND_TYPES
+        .iter()
+        .find(|(name, _, _)| *name == tpe)
+        .map(|(_, sample, _)| *sample)
+}
+
+fn operand_check(tpe: &str, operand: &str) -> String {
+    let (_, _, check) = OPERAND_TYPES
+        .iter()
+        .find(|(name, _, _)| *name == tpe)
+        .unwrap();
+    check.replace("{v}", operand)
+}
+
+fn parse_table(table: &str) -> Result<Vec<Opcode>, String> {
+    let mut opcodes: Vec<Opcode> = Vec::new();
+    let mut names = HashSet::new();
+    for (index, line) in table.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut columns = line.split_whitespace();
+        let code = columns
+            .next()
+            .unwrap()
+            .parse::<u8>()
+            .map_err(|_| format!("line {}: invalid byte code", line_number))?;
+        let name = columns
+            .next()
+            .ok_or(format!("line {}: missing instruction name", line_number))?
+            .to_string();
+        let operands: Vec<String> = columns.map(String::from).collect();
+
+        if let Some(last) = opcodes.last() {
+            if last.code >= code {
+                return Err(format!(
+                    "line {}: the byte codes must be in ascending order",
+                    line_number
+                ));
+            }
+        }
+        if name == "Unknown" || !names.insert(name.clone()) {
+            return Err(format!(
+                "line {}: duplicated instruction name {}",
+                line_number, name
+            ));
+        }
+        if let Some(tpe) = operands.iter().find(|tpe| operand_sample(tpe).is_none()) {
+            return Err(format!(
+                "line {}: unsupported operand type {}",
+                line_number, tpe
+            ));
+        }
+        opcodes.push(Opcode {
+            code,
+            name,
+            operands,
+        });
+    }
+    Ok(opcodes)
+}
+
+fn generate_enum(out: &mut String, opcodes: &[Opcode]) {
+    out.push_str("#[cfg_attr(test, derive(Debug, PartialEq))]\npub enum Instr {\n");
+    for opcode in opcodes {
+        writeln!(out, "{},", opcode.variant()).unwrap();
+    }
+    out.push_str("Unknown,\n}\n");
+    out.push_str(
+        "impl Reset for Instr {\nfn reset(&mut self) {\n*self = Self::Unknown;\n}\n}\n\
+         impl Default for Instr {\nfn default() -> Self {\nSelf::Unknown\n}\n}\n",
+    );
+}
+
+fn generate_methods(out: &mut String, opcodes: &[Opcode]) {
+    out.push_str("impl Instr {\nfn from_type(tpe: u8) -> Option<Self> {\nmatch tpe {\n");
+    for opcode in opcodes {
+        writeln!(out, "{} => Some({}),", opcode.code, opcode.default_value()).unwrap();
+    }
+    out.push_str("_ => None,\n}\n}\n");
+
+    out.push_str("pub fn get_type(&self) -> Option<u8> {\nmatch self {\n");
+    for opcode in opcodes {
+        writeln!(out, "{} => Some({}),", opcode.pattern(true), opcode.code).unwrap();
+    }
+    out.push_str("Self::Unknown => None,\n}\n}\n");
+
+    out.push_str("pub fn mnemonic(&self) -> &'static str {\nmatch self {\n");
+    for opcode in opcodes {
+        writeln!(out, "{} => \"{}\",", opcode.pattern(true), opcode.name).unwrap();
+    }
+    out.push_str("Self::Unknown => \"Unknown\",\n}\n}\n}\n");
+}
+
+fn generate_encoder(out: &mut String, opcodes: &[Opcode]) {
+    out.push_str(
+        "impl Encoder for Instr {\n\
+         fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {\n\
+         match self.get_type() {\n\
+         Some(tpe) => write_bytes(writer, &[tpe])?,\n\
+         None => return Err(EncodeError::InvalidData),\n\
+         }\n\
+         match self {\n",
+    );
+    for opcode in opcodes.iter().filter(|opcode| !opcode.operands.is_empty()) {
+        let size = opcode.operands.len();
+        let encoded = if size == 1 {
+            String::from("v0.encode(writer)")
+        } else {
+            let mut statements: Vec<String> = (0..(size - 1))
+                .map(|index| format!("v{}.encode(writer)?;", index))
+                .collect();
+            statements.push(format!("v{}.encode(writer)", size - 1));
+            format!("{{\n{}\n}}", statements.join("\n"))
+        };
+        writeln!(out, "{} => {},", opcode.pattern(false), encoded).unwrap();
+    }
+    out.push_str("_ => Ok(()),\n}\n}\n}\n");
+}
+
+fn generate_decoder(out: &mut String, opcodes: &[Opcode]) {
+    let with_operands: Vec<&Opcode> = opcodes
+        .iter()
+        .filter(|opcode| !opcode.operands.is_empty())
+        .collect();
+
+    out.push_str("impl RawDecoder for Instr {\nfn step_size(&self) -> u16 {\nmatch self {\n");
+    for opcode in &with_operands {
+        let steps: Vec<String> = (0..opcode.operands.len())
+            .map(|index| format!("v{}.step_size()", index))
+            .collect();
+        writeln!(out, "{} => {},", opcode.pattern(false), steps.join(" + ")).unwrap();
+    }
+    out.push_str("_ => 1,\n}\n}\n");
+
+    out.push_str(
+        "fn decode<W: Writable>(\n\
+         &mut self,\n\
+         buffer: &mut Buffer<'_, W>,\n\
+         stage: &DecodeStage,\n\
+         ) -> DecodeResult<DecodeStage> {\n\
+         if buffer.is_empty() {\n\
+         return Ok(DecodeStage { ..*stage });\n\
+         }\n\
+         if let Self::Unknown = self {\n\
+         let tpe = buffer.consume_byte().unwrap();\n\
+         let result = Self::from_type(tpe);\n\
+         if let Some(instr) = result {\n\
+         *self = instr;\n\
+         } else {\n\
+         *self = Instr::Unknown;\n\
+         }\n\
+         };\n\
+         match self {\n",
+    );
+    for opcode in &with_operands {
+        let size = opcode.operands.len();
+        let decoded = if size == 1 {
+            String::from("v0.decode(buffer, stage)")
+        } else {
+            // Decode the operands one by one, the stage step tells which operand is being decoded
+            let mut branches = String::new();
+            let mut steps: Vec<String> = Vec::new();
+            for index in 0..(size - 1) {
+                steps.push(format!("v{}.step_size()", index));
+                let keyword = if index == 0 { "if" } else { "} else if" };
+                write!(
+                    branches,
+                    "{} stage.step < {} {{\nv{}.decode(buffer, stage)\n",
+                    keyword,
+                    steps.join(" + "),
+                    index
+                )
+                .unwrap();
+            }
+            write!(
+                branches,
+                "}} else {{\nv{}.decode(buffer, stage)\n}}",
+                size - 1
+            )
+            .unwrap();
+            format!("{{\n{}\n}}", branches)
+        };
+        writeln!(out, "{} => {},", opcode.pattern(false), decoded).unwrap();
+    }
+    out.push_str(
+        "Self::Unknown => Ok(DecodeStage::COMPLETE), // skip unknown instr\n\
+         _ => Ok(DecodeStage::COMPLETE),\n}\n}\n}\n",
+    );
+}
+
+fn generate_tests(out: &mut String, opcodes: &[Opcode]) {
+    out.push_str(
+        "\n#[cfg(test)]\npub mod tests {\n\
+         extern crate std;\n\
+         use super::Instr;\n\
+         use crate::buffer::Buffer;\n\
+         use crate::encode::tests::encode_to_vec;\n\
+         use crate::encode::{EncodeError, Encoder};\n\
+         use crate::types::u256::tests::hex_to_bytes;\n\
+         use crate::types::{Hash, LockupScript};\n\
+         use crate::{decode::*, TempData};\n\
+         use std::mem::discriminant;\n\
+         use std::vec::Vec;\n\n\
+         #[test]\nfn decode_instr() {\n\
+         let mut temp_data = TempData::new();\n\n\
+         // The byte code, the mnemonic and an encoded instruction\n\
+         const ALL_INSTRS: &[(u8, &str, &str)] = &[\n",
+    );
+    for opcode in opcodes {
+        writeln!(
+            out,
+            "({}, \"{}\", \"{}\"),",
+            opcode.code,
+            opcode.name,
+            opcode.encoded_sample()
+        )
+        .unwrap();
+    }
+    out.push_str(
+        "];\n\n\
+         for &(code, mnemonic, hex) in ALL_INSTRS {\n\
+         let bytes = hex_to_bytes(hex).unwrap();\n\
+         let mut decoder = new_decoder::<Instr>();\n\
+         let mut buffer = Buffer::new(&bytes, &mut temp_data);\n\
+         let decoded_instr = decoder.decode(&mut buffer).unwrap().unwrap();\n\
+         assert!(buffer.is_empty());\n\
+         assert_eq!(decoded_instr.get_type(), Some(code));\n\
+         assert_eq!(decoded_instr.mnemonic(), mnemonic);\n\
+         assert_eq!(encode_to_vec(decoded_instr), bytes);\n\n\
+         let instr = Instr::from_type(code);\n\
+         assert!(instr.is_some());\n\
+         assert_eq!(\n\
+         discriminant(instr.as_ref().unwrap()),\n\
+         discriminant(decoded_instr)\n\
+         );\n\
+         match decoded_instr {\n",
+    );
+    for opcode in opcodes.iter().filter(|opcode| !opcode.operands.is_empty()) {
+        let checks: Vec<String> = opcode
+            .operands
+            .iter()
+            .enumerate()
+            .map(|(index, tpe)| format!("assert!({});", operand_check(tpe, &format!("v{}", index))))
+            .collect();
+        writeln!(
+            out,
+            "{} => {{\n{}\n}}",
+            opcode.pattern(false).replacen("Self::", "Instr::", 1),
+            checks.join("\n")
+        )
+        .unwrap();
+    }
+    out.push_str(
+        "_ => assert!(instr.as_ref() == Some(decoded_instr)),\n\
+         }\n\
+         }\n\
+         }\n\n\
+         #[test]\nfn decode_unknown_instr() {\n\
+         let mut temp_data = TempData::new();\n\
+         let mut decoder = new_decoder::<Instr>();\n\
+         let mut buffer = Buffer::new(&[255], &mut temp_data);\n\
+         let instr = decoder.decode(&mut buffer).unwrap().unwrap();\n\
+         assert!(*instr == Instr::Unknown);\n\
+         assert_eq!(instr.mnemonic(), \"Unknown\");\n\
+         assert_eq!(instr.encode(&mut Vec::new()), Err(EncodeError::InvalidData));\n\
+         }\n\
+         }\n",
+    );
+}
+
+fn generate(opcodes: &[Opcode]) -> String {
+    let mut out = String::from(
+        "// auto-generated by `utils/codegen` from `utils/codegen/opcodes.txt`, do not edit\n\
+         use super::*;\n\
+         use crate::buffer::{Buffer, Writable};\n\
+         use crate::decode::*;\n\
+         use crate::encode::*;\n\
+         use crate::types::method_selector::MethodSelector;\n",
+    );
+    generate_enum(&mut out, opcodes);
+    generate_methods(&mut out, opcodes);
+    generate_encoder(&mut out, opcodes);
+    generate_decoder(&mut out, opcodes);
+    generate_tests(&mut out, opcodes);
+    out
+}
+
+fn rustfmt(code: &str) -> String {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run rustfmt");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(code.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "failed to format the generated code"
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn main() {
+    let check = std::env::args().any(|arg| arg == "--check");
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let table = std::fs::read_to_string(dir.join(OPCODE_TABLE)).unwrap();
+    let opcodes = parse_table(&table).unwrap_or_else(|error| {
+        eprintln!("Invalid opcode table: {}", error);
+        exit(1);
+    });
+    let generated = rustfmt(&generate(&opcodes));
+    let path = dir.join(GENERATED_FILE);
+    if check {
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        if current != generated {
+            eprintln!(
+                "{} is out of date, run `cargo run` in utils/codegen",
+                GENERATED_FILE
+            );
+            exit(1);
+        }
+    } else {
+        std::fs::write(&path, generated).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table() {
+        let opcodes =
+            parse_table("# comment\n0 CallLocal Byte\n\n2 Return\n210 CreateMapEntry Byte Byte\n")
+                .unwrap();
+        assert_eq!(opcodes.len(), 3);
+        assert_eq!(opcodes[2].variant(), "CreateMapEntry(Byte, Byte)");
+        assert_eq!(opcodes[2].encoded_sample(), "d27f7f");
+        assert_eq!(opcodes[1].default_value(), "Self::Return");
+
+        assert!(parse_table("2 Return\n1 Pop\n").is_err());
+        assert!(parse_table("1 Return\n2 Return\n").is_err());
+        assert!(parse_table("1 Unknown\n").is_err());
+        assert!(parse_table("1 Return U64\n").is_err());
+        assert!(parse_table("256 Return\n").is_err());
+    }
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use utils_fuzz::{check_streaming_decode, ChunkedTx};
+
+fuzz_target!(|input: ChunkedTx| {
+    check_streaming_decode(&input);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use utils_fuzz::check_model_decode;
+
+fuzz_target!(|data: &[u8]| {
+    check_model_decode(data);
+});
@@ -0,0 +1,225 @@
+// Decoding harness shared by the fuzz targets, it drives the tx decoder the same way as the app
+use arbitrary::Arbitrary;
+use utils::buffer::Buffer;
+use utils::decode::*;
+use utils::encode::{EncodeError, Encoder};
+use utils::model::{ModelError, UnsignedTxModel};
+use utils::types::UnsignedTx;
+
+// The app receives the tx in APDU chunks of at most 255 bytes
+pub const MAX_CHUNK_SIZE: usize = 255;
+
+#[derive(Arbitrary, Debug)]
+pub struct ChunkedTx {
+    // The sizes of the chunks, they are reused in order until all the tx bytes are sent
+    pub chunk_sizes: Vec<u8>,
+    pub tx: Vec<u8>,
+}
+
+impl ChunkedTx {
+    pub fn chunks(&self) -> Vec<&[u8]> {
+        let mut chunks = Vec::new();
+        let mut sizes = self
+            .chunk_sizes
+            .iter()
+            .map(|size| (*size as usize).max(1))
+            .cycle();
+        let mut from_index = 0;
+        while from_index < self.tx.len() {
+            let size = sizes.next().unwrap_or(MAX_CHUNK_SIZE);
+            let to_index = (from_index + size).min(self.tx.len());
+            chunks.push(&self.tx[from_index..to_index]);
+            from_index = to_index;
+        }
+        chunks
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    // The number of bytes consumed by the tx
+    Complete(usize),
+    Incomplete,
+    Failed {
+        error: DecodeError,
+        offset: usize,
+        path: DecodePath,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DecodeTrace {
+    // The decode path and the encoded value of each decoded tx detail, in the order they are reviewed
+    pub details: Vec<(DecodePath, Result<Vec<u8>, EncodeError>)>,
+    pub temp_data: Vec<u8>,
+    pub outcome: Outcome,
+}
+
+// Encode the tx detail reviewed after `step` returns `Ok(true)`, vectors only keep the current item
+fn encode_tx_detail(tx: &UnsignedTx) -> Result<Vec<u8>, EncodeError> {
+    let mut bytes = Vec::new();
+    match tx {
+        UnsignedTx::Inputs(inputs) => inputs.get_current_item().encode(&mut bytes)?,
+        UnsignedTx::FixedOutputs(outputs) => outputs.get_current_item().encode(&mut bytes)?,
+        _ => tx.encode(&mut bytes)?,
+    };
+    Ok(bytes)
+}
+
+fn check_stage_invariants(decoder: &StreamingDecoder<UnsignedTx>) {
+    let step_size = decoder.inner.step_size();
+    assert!(step_size > 0, "the step size must not be zero");
+    assert!(
+        decoder.stage.is_complete() || decoder.stage.step < step_size,
+        "invalid stage {:?}, step size {}",
+        decoder.stage,
+        step_size
+    );
+}
+
+// Decode the chunks with the same loop as `SignTxContext::decode_tx`, and check the decoder invariants after each step
+pub fn decode_tx(chunks: &[&[u8]]) -> DecodeTrace {
+    let mut temp_data = Vec::new();
+    let mut details = Vec::new();
+    let mut decoder = new_decoder::<UnsignedTx>();
+    let mut chunk_offset = 0;
+    for chunk in chunks {
+        let mut buffer = Buffer::new(chunk, &mut temp_data);
+        loop {
+            let from_index = buffer.get_index();
+            let result = decoder.step(&mut buffer);
+            let to_index = buffer.get_index();
+            assert!(to_index <= chunk.len());
+            match result {
+                Ok(true) => {
+                    assert!(to_index > from_index, "no bytes consumed by the step");
+                    check_stage_invariants(&decoder);
+                    details.push((
+                        decoder.inner.decode_path(),
+                        encode_tx_detail(&decoder.inner),
+                    ));
+                    if decoder.inner.is_complete() {
+                        return DecodeTrace {
+                            details,
+                            temp_data,
+                            outcome: Outcome::Complete(chunk_offset + to_index),
+                        };
+                    }
+                    decoder.inner.next_step();
+                    decoder.reset_stage();
+                    check_stage_invariants(&decoder);
+                }
+                Ok(false) => {
+                    assert!(
+                        buffer.is_empty(),
+                        "the decoder stops before the end of the chunk"
+                    );
+                    check_stage_invariants(&decoder);
+                    break;
+                }
+                Err(error) => {
+                    return DecodeTrace {
+                        details,
+                        temp_data,
+                        outcome: Outcome::Failed {
+                            error,
+                            offset: chunk_offset + to_index,
+                            path: decoder.inner.decode_path(),
+                        },
+                    };
+                }
+            }
+        }
+        chunk_offset += chunk.len();
+    }
+    DecodeTrace {
+        details,
+        temp_data,
+        outcome: Outcome::Incomplete,
+    }
+}
+
+// Decoding the tx in chunks must give the same result as decoding all the bytes at once
+pub fn check_streaming_decode(input: &ChunkedTx) -> DecodeTrace {
+    let one_shot = decode_tx(&[&input.tx]);
+    let streaming = decode_tx(&input.chunks());
+    assert_eq!(one_shot, streaming);
+    one_shot
+}
+
+// The host model must accept exactly the txs accepted by the device decoder.
+// The decoder accepts non-canonical compact integers, e.g. `U16` reads `0x3f` as 63,
+// so the encoded model is checked by decoding it again instead of comparing the bytes
+pub fn check_model_decode(tx: &[u8]) {
+    let trace = decode_tx(&[tx]);
+    match UnsignedTxModel::from_bytes(tx) {
+        Ok(model) => {
+            assert_eq!(trace.outcome, Outcome::Complete(tx.len()));
+            let mut bytes = Vec::new();
+            model.encode(&mut bytes).unwrap();
+            assert_eq!(UnsignedTxModel::from_bytes(&bytes), Ok(model));
+        }
+        Err(ModelError::TrailingBytes(size)) => {
+            assert_eq!(trace.outcome, Outcome::Complete(tx.len() - size))
+        }
+        Err(ModelError::UnexpectedEnd) => assert_eq!(trace.outcome, Outcome::Incomplete),
+        Err(ModelError::Decode {
+            error,
+            offset,
+            path,
+        }) => assert_eq!(
+            trace.outcome,
+            Outcome::Failed {
+                error,
+                offset,
+                path
+            }
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A tx with a script, from the unsigned tx tests
+    const SCRIPT_TX: &str = "0002010101030002000f144020923e3a6a5136338eed4fe14d96f219b192cea5c0c38a00b24186dc377b6b8b5c13016413006417011700150070f425d78af36c5c555b99098089da6a7ba34af9128e6e5d2dff1d457422a42d13c4016345785d8a0000a214409f0101409b01010000001cd38d0b363615036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d14008c0c2f0c7b15036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d1400d1a2140a5f5f6d61705f5f305f5f0c40440c0db1d20300b281d015036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d1400d1a20c0cce00d41e406a2f16001601ad1880030d40c1174876e80001b6b24f4b6b75d1147849306d23c316f1403d5af88be1d8a99a40c2f98c2ca146bfd938240003566752c71be4772a5c3a9d613cdf5e2dbe249cd6af46909c8272a91fe42c715100";
+
+    fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_boundaries() {
+        let tx = hex_to_bytes(SCRIPT_TX);
+        for size in 1..=MAX_CHUNK_SIZE {
+            let input = ChunkedTx {
+                chunk_sizes: vec![size as u8],
+                tx: tx.clone(),
+            };
+            let trace = check_streaming_decode(&input);
+            assert_eq!(trace.outcome, Outcome::Complete(tx.len()));
+        }
+        check_model_decode(&tx);
+    }
+
+    #[test]
+    fn test_mutated_txs() {
+        let tx = hex_to_bytes(SCRIPT_TX);
+        for index in 0..tx.len() {
+            for byte in [0x00, 0x01, 0x3f, 0x40, 0x80, 0xc0, 0xff] {
+                let mut mutated = tx.clone();
+                mutated[index] = byte;
+                check_model_decode(&mutated);
+                check_model_decode(&mutated[..index]);
+                check_streaming_decode(&ChunkedTx {
+                    chunk_sizes: vec![1, 7, 255, 3],
+                    tx: mutated,
+                });
+            }
+        }
+    }
+}
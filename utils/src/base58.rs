@@ -1,57 +1,272 @@
-// from: https://github.com/Nullus157/bs58-rs/blob/main/src/encode.rs
+// The encoding is adapted from: https://github.com/Nullus157/bs58-rs/blob/main/src/encode.rs
+// The digits are accumulated in limbs of `LIMB_DIGITS` base58 digits instead of one digit per byte
+use crate::buffer::Writable;
+
 pub const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
 
-pub fn base58_encode_inputs<'a>(inputs: &[&[u8]], output: &'a mut [u8]) -> Option<&'a [u8]> {
-    let mut index = 0;
-    for &input in inputs {
-        for &val in input {
-            let mut carry = val as usize;
-            for byte in &mut output[..index] {
-                carry += (*byte as usize) << 8;
-                *byte = (carry % 58) as u8;
-                carry /= 58;
+// 58^5 < 2^32, so a limb fits in u32 and a limb multiplied by 256 fits in u64
+const LIMB_DIGITS: usize = 5;
+const LIMB_BASE: u64 = 58 * 58 * 58 * 58 * 58;
+
+// The number of limbs to encode a string of up to `encoded_length` digits
+pub const fn limb_size(encoded_length: usize) -> usize {
+    encoded_length.div_ceil(LIMB_DIGITS)
+}
+
+// A random-access store of the limbs, the least significant limb is at index 0
+pub trait LimbStore {
+    // The maximum number of limbs
+    fn capacity(&self) -> usize;
+
+    fn get(&self, index: usize) -> u32;
+
+    fn set(&mut self, index: usize, limb: u32);
+}
+
+impl LimbStore for [u32] {
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, index: usize) -> u32 {
+        self[index]
+    }
+
+    fn set(&mut self, index: usize, limb: u32) {
+        self[index] = limb;
+    }
+}
+
+// The base58 encoder, which accepts the input in chunks and writes the encoded string to any writer
+pub struct Base58Encoder<'a, S: LimbStore + ?Sized> {
+    limbs: &'a mut S,
+    limb_size: usize,
+    leading_zeros: usize,
+    is_leading: bool,
+}
+
+impl<'a, S: LimbStore + ?Sized> Base58Encoder<'a, S> {
+    pub fn new(limbs: &'a mut S) -> Self {
+        Self {
+            limbs,
+            limb_size: 0,
+            leading_zeros: 0,
+            is_leading: true,
+        }
+    }
+
+    // Returns false if the limb store is full
+    pub fn update(&mut self, input: &[u8]) -> bool {
+        for &byte in input {
+            // The leading zero bytes are encoded as leading '1's
+            if self.is_leading && byte == 0 {
+                self.leading_zeros += 1;
+                continue;
+            }
+            self.is_leading = false;
+
+            let mut carry = byte as u64;
+            for index in 0..self.limb_size {
+                carry += (self.limbs.get(index) as u64) << 8;
+                self.limbs.set(index, (carry % LIMB_BASE) as u32);
+                carry /= LIMB_BASE;
             }
             while carry > 0 {
-                if index == output.len() {
-                    return None;
+                if self.limb_size == self.limbs.capacity() {
+                    return false;
                 }
-                output[index] = (carry % 58) as u8;
-                index += 1;
-                carry /= 58;
+                self.limbs.set(self.limb_size, (carry % LIMB_BASE) as u32);
+                self.limb_size += 1;
+                carry /= LIMB_BASE;
+            }
+        }
+        true
+    }
+
+    // The length of the encoded string
+    pub fn encoded_length(&self) -> usize {
+        if self.limb_size == 0 {
+            return self.leading_zeros;
+        }
+        let top_limb = self.limbs.get(self.limb_size - 1);
+        self.leading_zeros + (self.limb_size - 1) * LIMB_DIGITS + digit_size(top_limb)
+    }
+
+    // Write the encoded string from the most significant digit, returns false if the writer is full
+    pub fn finalize<W: Writable>(&self, writer: &mut W) -> bool {
+        if !write_leading_ones(writer, self.leading_zeros) {
+            return false;
+        }
+        for index in (0..self.limb_size).rev() {
+            let limb = self.limbs.get(index);
+            if !write_limb(writer, limb, index == self.limb_size - 1) {
+                return false;
             }
         }
+        true
     }
+}
 
-    'outer: for &input in inputs {
-        for &val in input {
-            if val != 0 {
-                break 'outer;
+impl<S: LimbStore + Writable + ?Sized> Base58Encoder<'_, S> {
+    // Write the encoded string to the limb store itself, which keeps the limbs after the encoded string
+    // A limb is read before its digits are written, and the digits of a limb are longer than the limb,
+    // so the unread limbs are not overwritten if the store writes the string before the limbs
+    pub fn finalize_in_place(&mut self) -> bool {
+        if !write_leading_ones(self.limbs, self.leading_zeros) {
+            return false;
+        }
+        for index in (0..self.limb_size).rev() {
+            let limb = self.limbs.get(index);
+            if !write_limb(self.limbs, limb, index == self.limb_size - 1) {
+                return false;
             }
+        }
+        true
+    }
+}
+
+#[inline]
+fn write_leading_ones<W: Writable + ?Sized>(writer: &mut W, mut size: usize) -> bool {
+    const ONES: [u8; 8] = [ALPHABET[0]; 8];
+    while size > 0 {
+        let length = core::cmp::min(size, ONES.len());
+        if !writer.write(&ONES[..length]) {
+            return false;
+        }
+        size -= length;
+    }
+    true
+}
+
+#[inline]
+fn write_limb<W: Writable + ?Sized>(writer: &mut W, limb: u32, is_top_limb: bool) -> bool {
+    let mut value = limb;
+    let mut digits = [0u8; LIMB_DIGITS];
+    for digit in digits.iter_mut().rev() {
+        *digit = ALPHABET[(value % 58) as usize];
+        value /= 58;
+    }
+    // The leading zero digits of the most significant limb are skipped
+    let from_index = if is_top_limb {
+        LIMB_DIGITS - digit_size(limb)
+    } else {
+        0
+    };
+    writer.write(&digits[from_index..])
+}
+
+#[inline]
+fn digit_size(mut limb: u32) -> usize {
+    let mut size = 0;
+    while limb > 0 {
+        size += 1;
+        limb /= 58;
+    }
+    size
+}
+
+struct SliceWriter<'a> {
+    output: &'a mut [u8],
+    index: usize,
+}
+
+impl Writable for SliceWriter<'_> {
+    fn write(&mut self, bytes: &[u8]) -> bool {
+        let to_index = self.index + bytes.len();
+        if to_index > self.output.len() {
+            return false;
+        }
+        self.output[self.index..to_index].copy_from_slice(bytes);
+        self.index = to_index;
+        true
+    }
+}
+
+// The limbs are provided by the caller, see `limb_size`
+pub fn base58_encode_inputs<'a, S: LimbStore + ?Sized>(
+    inputs: &[&[u8]],
+    limbs: &mut S,
+    output: &'a mut [u8],
+) -> Option<&'a [u8]> {
+    let mut encoder = Base58Encoder::new(limbs);
+    for input in inputs {
+        if !encoder.update(input) {
+            return None;
+        }
+    }
+    let mut writer = SliceWriter { output, index: 0 };
+    if !encoder.finalize(&mut writer) {
+        return None;
+    }
+    let SliceWriter { output, index } = writer;
+    Some(&output[..index])
+}
+
+pub fn base58_encode<'a, S: LimbStore + ?Sized>(
+    input: &[u8],
+    limbs: &mut S,
+    output: &'a mut [u8],
+) -> Option<&'a [u8]> {
+    base58_encode_inputs(&[input], limbs, output)
+}
+
+const INVALID_DIGIT: u8 = 0xff;
+// The base58 digit of each ASCII character
+const DIGITS: [u8; 128] = {
+    let mut digits = [INVALID_DIGIT; 128];
+    let mut index = 0;
+    while index < ALPHABET.len() {
+        digits[ALPHABET[index] as usize] = index as u8;
+        index += 1;
+    }
+    digits
+};
+
+// Returns None if the input has a character outside the alphabet or the output is too small
+pub fn base58_decode<'a>(input: &[u8], output: &'a mut [u8]) -> Option<&'a [u8]> {
+    let mut index = 0;
+    for &char in input {
+        let digit = *DIGITS.get(char as usize)?;
+        if digit == INVALID_DIGIT {
+            return None;
+        }
+        let mut carry = digit as usize;
+        for byte in &mut output[..index] {
+            carry += (*byte as usize) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
             if index == output.len() {
                 return None;
             }
-            output[index] = 0;
+            output[index] = (carry & 0xff) as u8;
             index += 1;
+            carry >>= 8;
         }
     }
 
-    for val in &mut output[..index] {
-        *val = ALPHABET[*val as usize];
+    // The leading '1's are decoded as leading zero bytes
+    for &char in input {
+        if char != ALPHABET[0] {
+            break;
+        }
+        if index == output.len() {
+            return None;
+        }
+        output[index] = 0;
+        index += 1;
     }
 
     output[..index].reverse();
     Some(&output[..index])
 }
 
-pub fn base58_encode<'a>(input: &[u8], output: &'a mut [u8]) -> Option<&'a [u8]> {
-    base58_encode_inputs(&[input], output)
-}
-
 #[cfg(test)]
 mod tests {
     extern crate alloc;
     extern crate std;
-    use super::base58_encode;
+    use super::{base58_decode, base58_encode, limb_size, Base58Encoder, LimbStore, ALPHABET};
     use crate::{base58::base58_encode_inputs, types::u256::tests::hex_to_bytes};
     use alloc::str::from_utf8;
     use std::vec;
@@ -102,17 +317,124 @@ mod tests {
         ];
         for (bytes, str) in cases {
             let mut output = [0; 150];
-            let result = base58_encode(bytes, &mut output);
+            let mut limbs = [0u32; limb_size(150)];
+            let result = base58_encode(bytes, &mut limbs[..], &mut output);
             assert!(result.is_some());
             let expected = from_utf8(result.unwrap()).unwrap();
             assert_eq!(*expected, *str);
 
             let input_slices: Vec<Vec<u8>> = bytes.iter().map(|&byte| vec![byte]).collect();
             let inputs: Vec<&[u8]> = input_slices.iter().map(|v| v.as_slice()).collect();
-            let result = base58_encode_inputs(&inputs, &mut output);
+            let result = base58_encode_inputs(&inputs, &mut limbs[..], &mut output);
             assert!(result.is_some());
             let expected = from_utf8(result.unwrap()).unwrap();
             assert_eq!(*expected, *str);
+
+            let mut decoded = [0; 150];
+            assert_eq!(base58_decode(str.as_bytes(), &mut decoded), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn test_base58_decode_invalid_input() {
+        let mut output = [0; 32];
+        for str in ["0", "O", "I", "l", "ZiC+", "Zi Ca", "Zi\u{e9}Ca"] {
+            assert_eq!(base58_decode(str.as_bytes(), &mut output), None);
         }
+        assert_eq!(base58_decode(b"ZiCa", &mut output[..2]), None);
+        assert_eq!(base58_decode(b"11ZiCa", &mut output[..4]), None);
+        assert_eq!(
+            base58_decode(b"11ZiCa", &mut output[..5]),
+            Some(&b"\0\0abc"[..])
+        );
+    }
+
+    // The digit by digit encoding, which is used as the reference
+    fn reference_encode(input: &[u8]) -> Vec<u8> {
+        let mut digits: Vec<u8> = Vec::new();
+        for &byte in input {
+            let mut carry = byte as usize;
+            for digit in digits.iter_mut() {
+                carry += (*digit as usize) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+            while carry > 0 {
+                digits.push((carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+        let leading_zeros = input.iter().take_while(|&&byte| byte == 0).count();
+        digits.extend(vec![0; leading_zeros]);
+        digits
+            .iter()
+            .rev()
+            .map(|&digit| ALPHABET[digit as usize])
+            .collect()
+    }
+
+    // A limb store that is not a slice, e.g. a store backed by the flash
+    struct VecStore(Vec<u32>);
+
+    impl LimbStore for VecStore {
+        fn capacity(&self) -> usize {
+            self.0.len()
+        }
+
+        fn get(&self, index: usize) -> u32 {
+            self.0[index]
+        }
+
+        fn set(&mut self, index: usize, limb: u32) {
+            self.0[index] = limb;
+        }
+    }
+
+    #[test]
+    fn test_base58_encoder() {
+        let mut seed = 1u32;
+        for size in 0..300 {
+            let input: Vec<u8> = (0..size)
+                .map(|index| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    // the first bytes of some inputs are zeros
+                    if size % 3 == 0 && index < size / 10 {
+                        0
+                    } else {
+                        (seed >> 16) as u8
+                    }
+                })
+                .collect();
+            let expected = reference_encode(&input);
+
+            let mut store = VecStore(vec![0; 512]);
+            let mut encoder = Base58Encoder::new(&mut store);
+            for chunk in input.chunks(7) {
+                assert!(encoder.update(chunk));
+            }
+            assert_eq!(encoder.encoded_length(), expected.len());
+            let mut output = Vec::new();
+            assert!(encoder.finalize(&mut output));
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_base58_encoder_overflow() {
+        let input = [0xff; 40];
+        let mut limbs = [0u32; 10];
+        let mut encoder = Base58Encoder::new(&mut limbs[..]);
+        assert!(!encoder.update(&input));
+
+        let mut limbs = [0u32; limb_size(54)];
+        let mut output = [0u8; 54];
+        assert!(base58_encode(&input, &mut limbs[..], &mut output).is_none());
+        let mut output = [0u8; 55];
+        assert_eq!(
+            base58_encode(&input, &mut limbs[..], &mut output)
+                .unwrap()
+                .len(),
+            55
+        );
     }
 }
@@ -4,6 +4,15 @@ pub trait Writable {
     fn write(&mut self, bytes: &[u8]) -> bool;
 }
 
+// The temp data of a decoder whose input bytes are already available, so nothing is recorded
+pub struct SkipTempData;
+
+impl Writable for SkipTempData {
+    fn write(&mut self, _bytes: &[u8]) -> bool {
+        true
+    }
+}
+
 pub struct Buffer<'a, W> {
     index: usize,
     data: &'a [u8],
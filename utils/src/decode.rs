@@ -1,6 +1,8 @@
 use crate::buffer::{Buffer, Writable};
+use crate::types::I32;
 
-#[cfg_attr(test, derive(Debug, PartialEq))]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
 pub enum DecodeError {
     InvalidSize,
     InvalidData,
@@ -10,7 +12,7 @@ pub enum DecodeError {
 
 pub type DecodeResult<T> = Result<T, DecodeError>;
 
-#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 #[derive(Default, PartialEq)]
 pub struct DecodeStage {
     pub step: u16,
@@ -35,6 +37,50 @@ impl DecodeStage {
     }
 }
 
+// The location of the data being decoded, e.g. `FixedOutputs[3].lockup_script`
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct DecodePath {
+    pub section: &'static str,
+    pub index: Option<u16>,
+    pub field: Option<&'static str>,
+}
+
+impl DecodePath {
+    pub const fn new(section: &'static str) -> Self {
+        DecodePath {
+            section,
+            index: None,
+            field: None,
+        }
+    }
+
+    pub fn to_str<'a>(&self, output: &'a mut [u8]) -> Option<&'a [u8]> {
+        let mut length = write_str(output, 0, self.section.as_bytes())?;
+        if let Some(index) = self.index {
+            length = write_str(output, length, b"[")?;
+            let mut digits = [0u8; 5];
+            let digits = I32::from(index as i32).to_str(&mut digits)?;
+            length = write_str(output, length, digits)?;
+            length = write_str(output, length, b"]")?;
+        }
+        if let Some(field) = self.field {
+            length = write_str(output, length, b".")?;
+            length = write_str(output, length, field.as_bytes())?;
+        }
+        Some(&output[..length])
+    }
+}
+
+fn write_str(output: &mut [u8], from_index: usize, bytes: &[u8]) -> Option<usize> {
+    let to_index = from_index + bytes.len();
+    if to_index > output.len() {
+        return None;
+    }
+    output[from_index..to_index].copy_from_slice(bytes);
+    Some(to_index)
+}
+
 pub trait RawDecoder: Sized {
     fn step_size(&self) -> u16;
 
@@ -197,3 +243,38 @@ impl<T1: RawDecoder, T2: RawDecoder> RawDecoder for (T1, T2) {
 pub fn new_decoder<T: Default + RawDecoder>() -> StreamingDecoder<T> {
     StreamingDecoder::<T>::default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DecodePath;
+
+    #[test]
+    fn test_decode_path_to_str() {
+        let mut output = [0u8; 32];
+        let path = DecodePath::new("Version");
+        assert_eq!(path.to_str(&mut output).unwrap(), b"Version");
+
+        let path = DecodePath {
+            index: Some(3),
+            field: Some("lockup_script"),
+            ..DecodePath::new("FixedOutputs")
+        };
+        assert_eq!(
+            path.to_str(&mut output).unwrap(),
+            b"FixedOutputs[3].lockup_script"
+        );
+
+        let path = DecodePath {
+            index: Some(u16::MAX),
+            ..DecodePath::new("Inputs")
+        };
+        assert_eq!(path.to_str(&mut output).unwrap(), b"Inputs[65535]");
+
+        let path = DecodePath {
+            field: Some("size"),
+            ..DecodePath::new("Inputs")
+        };
+        assert_eq!(path.to_str(&mut output).unwrap(), b"Inputs.size");
+        assert!(path.to_str(&mut output[..10]).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::buffer::Writable;
+use crate::decode::StreamingDecoder;
+use crate::types::compact_integer::encode_i32;
+
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum EncodeError {
+    // The writer has no space left
+    Overflow,
+    // The value does not keep all the data required for encoding, e.g. the items of an `AVector`
+    IncompleteData,
+    InvalidData,
+}
+
+pub type EncodeResult<T> = Result<T, EncodeError>;
+
+// Write the canonical Alephium serialization of the value
+pub trait Encoder {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()>;
+}
+
+#[inline]
+pub fn write_bytes<W: Writable>(writer: &mut W, bytes: &[u8]) -> EncodeResult<()> {
+    if writer.write(bytes) {
+        Ok(())
+    } else {
+        Err(EncodeError::Overflow)
+    }
+}
+
+#[inline]
+pub fn write_compact_i32<W: Writable>(writer: &mut W, value: i32) -> EncodeResult<()> {
+    let (bytes, length) = encode_i32(value);
+    write_bytes(writer, &bytes[..length])
+}
+
+pub fn write_size<W: Writable>(writer: &mut W, size: usize) -> EncodeResult<()> {
+    // The vector size is decoded as `U16`
+    if size > (u16::MAX as usize) {
+        return Err(EncodeError::InvalidData);
+    }
+    write_compact_i32(writer, size as i32)
+}
+
+impl<T: Encoder + ?Sized> Encoder for &T {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        (**self).encode(writer)
+    }
+}
+
+impl<T: Encoder> Encoder for Option<T> {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self {
+            None => write_bytes(writer, &[0]),
+            Some(value) => {
+                write_bytes(writer, &[1])?;
+                value.encode(writer)
+            }
+        }
+    }
+}
+
+impl<T: Encoder> Encoder for StreamingDecoder<T> {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.inner.encode(writer)
+    }
+}
+
+// Slices are encoded as `AVector`
+impl<T: Encoder> Encoder for [T] {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_size(writer, self.len())?;
+        for item in self {
+            item.encode(writer)?;
+        }
+        Ok(())
+    }
+}
+
+// Tuples are encoded by concatenating the encoded fields, which is how structs are serialized
+macro_rules! tuple_encoder {
+    ($($name:ident : $index:tt),+) => {
+        impl<$($name: Encoder),+> Encoder for ($($name,)+) {
+            fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+                $(self.$index.encode(writer)?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+tuple_encoder!(T0: 0, T1: 1);
+tuple_encoder!(T0: 0, T1: 1, T2: 2);
+tuple_encoder!(T0: 0, T1: 1, T2: 2, T3: 3);
+tuple_encoder!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4);
+tuple_encoder!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5);
+tuple_encoder!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6);
+
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+#[cfg(any(test, feature = "std"))]
+impl Writable for std::vec::Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) -> bool {
+        self.extend_from_slice(bytes);
+        true
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::decode::{new_decoder, Decoder, RawDecoder};
+    use crate::TempData;
+    use std::vec::Vec;
+
+    pub fn encode_to_vec<T: Encoder + ?Sized>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes).unwrap();
+        bytes
+    }
+
+    // Decode the bytes and check that encoding the decoded value gives the same bytes
+    pub fn check_round_trip<T: Default + RawDecoder + Encoder>(bytes: &[u8]) {
+        let mut temp_data = TempData::new();
+        let mut buffer = Buffer::new(bytes, &mut temp_data);
+        let mut decoder = new_decoder::<T>();
+        let decoded = decoder.decode(&mut buffer).unwrap().unwrap();
+        assert!(buffer.is_empty());
+        assert_eq!(encode_to_vec(decoded), bytes);
+    }
+
+    struct FixedWriter {
+        data: [u8; 4],
+        size: usize,
+    }
+
+    impl Writable for FixedWriter {
+        fn write(&mut self, bytes: &[u8]) -> bool {
+            if self.size + bytes.len() > self.data.len() {
+                return false;
+            }
+            self.data[self.size..(self.size + bytes.len())].copy_from_slice(bytes);
+            self.size += bytes.len();
+            true
+        }
+    }
+
+    #[test]
+    fn test_encode_compact_i32() {
+        let cases: &[(i32, &[u8])] = &[
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (0x1f, &[0x1f]),
+            (-1, &[0x3f]),
+            (-0x20, &[0x20]),
+            (0x20, &[0x40, 0x20]),
+            (0x1fff, &[0x5f, 0xff]),
+            (-0x2000, &[0x60, 0x00]),
+            (0x2000, &[0x80, 0x00, 0x20, 0x00]),
+            (0x1fffffff, &[0x9f, 0xff, 0xff, 0xff]),
+            (-0x20000000, &[0xa0, 0x00, 0x00, 0x00]),
+            (0x20000000, &[0xc0, 0x20, 0x00, 0x00, 0x00]),
+            (i32::MAX, &[0xc0, 0x7f, 0xff, 0xff, 0xff]),
+            (i32::MIN, &[0xc0, 0x80, 0x00, 0x00, 0x00]),
+        ];
+        for (value, expected) in cases {
+            let mut bytes = Vec::new();
+            write_compact_i32(&mut bytes, *value).unwrap();
+            assert_eq!(&bytes, expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_overflow() {
+        let mut writer = FixedWriter {
+            data: [0; 4],
+            size: 0,
+        };
+        assert_eq!(write_bytes(&mut writer, &[0; 4]), Ok(()));
+        assert_eq!(write_bytes(&mut writer, &[0]), Err(EncodeError::Overflow));
+        assert_eq!(
+            write_size(&mut writer, 0x10000),
+            Err(EncodeError::InvalidData)
+        );
+    }
+}
@@ -1,8 +1,11 @@
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod base58;
 pub mod buffer;
 pub mod decode;
+pub mod encode;
+#[cfg(feature = "std")]
+pub mod model;
 #[cfg(test)]
 pub mod temp_data;
 pub mod types;
@@ -0,0 +1,820 @@
+// An owned transaction model for host tools, built with the same streaming decoders used by the device
+extern crate std;
+
+use std::format;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::base58::Base58Encoder;
+use crate::buffer::{Buffer, Writable};
+use crate::decode::*;
+use crate::encode::*;
+use crate::types::unlock_script::PublicKeyWithIndex;
+use crate::types::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModelError {
+    // The tx is rejected by the decoder, the offset is the number of bytes consumed when decoding failed
+    Decode {
+        error: DecodeError,
+        offset: usize,
+        path: DecodePath,
+    },
+    // The tx is truncated
+    UnexpectedEnd,
+    // The tx is complete but there are remaining bytes
+    TrailingBytes(usize),
+}
+
+pub type ModelResult<T> = Result<T, ModelError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsignedTxModel {
+    pub version: u8,
+    pub network_id: u8,
+    pub script: Option<ScriptModel>,
+    pub gas_amount: i32,
+    pub gas_price: U256,
+    pub inputs: Vec<TxInputModel>,
+    pub fixed_outputs: Vec<AssetOutputModel>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptModel {
+    pub methods: Vec<MethodModel>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodModel {
+    pub is_public: u8,
+    pub asset_modifier: u8,
+    pub args_length: u16,
+    pub locals_length: u16,
+    pub return_length: u16,
+    pub instrs: Vec<InstrModel>,
+}
+
+// The operands are kept as encoded bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstrModel {
+    pub code: u8,
+    pub operands: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInputModel {
+    pub hint: [u8; 4],
+    pub key: [u8; 32],
+    pub unlock_script: UnlockScriptModel,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnlockScriptModel {
+    P2PKH([u8; 33]),
+    P2MPKH(Vec<([u8; 33], u16)>),
+    P2SH {
+        script: ScriptModel,
+        params: Vec<ValModel>,
+    },
+    SameAsPrevious,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockupScriptModel {
+    P2PKH([u8; 32]),
+    P2MPKH {
+        public_key_hashes: Vec<[u8; 32]>,
+        m: u16,
+    },
+    P2SH([u8; 32]),
+    P2C([u8; 32]),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValModel {
+    Bool(bool),
+    I256(I256),
+    U256(U256),
+    ByteVec(Vec<u8>),
+    Address(LockupScriptModel),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenModel {
+    pub id: [u8; 32],
+    pub amount: U256,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetOutputModel {
+    pub amount: U256,
+    pub lockup_script: LockupScriptModel,
+    pub lock_time: u64,
+    pub tokens: Vec<TokenModel>,
+    pub additional_data: Vec<u8>,
+}
+
+// Decodes values one by one and keeps the encoded bytes of each value,
+// the encoded bytes are used to recover the data skipped by the streaming decoders
+struct Parser<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    temp_data: Vec<u8>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Parser {
+            bytes,
+            index: 0,
+            temp_data: Vec::new(),
+        }
+    }
+
+    fn decode<T: Default + RawDecoder>(&mut self) -> ModelResult<(T, &'a [u8])> {
+        let data = &self.bytes[self.index..];
+        let mut decoder = new_decoder::<T>();
+        let mut buffer = Buffer::new(data, &mut self.temp_data);
+        let result = decoder.decode(&mut buffer).map(|result| result.is_some());
+        let consumed = buffer.get_index();
+        self.temp_data.clear();
+        match result {
+            Ok(true) => {
+                self.index += consumed;
+                Ok((decoder.inner, &data[..consumed]))
+            }
+            Ok(false) => Err(ModelError::UnexpectedEnd),
+            Err(error) => Err(ModelError::Decode {
+                error,
+                offset: self.index + consumed,
+                path: DecodePath::default(),
+            }),
+        }
+    }
+
+    fn decode_vec<M>(
+        &mut self,
+        mut decode_item: impl FnMut(&mut Self) -> ModelResult<M>,
+    ) -> ModelResult<Vec<M>> {
+        let (size, _) = self.decode::<U16>()?;
+        (0..size.inner).map(|_| decode_item(self)).collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.index == self.bytes.len()
+    }
+}
+
+fn invalid_data() -> ModelError {
+    ModelError::Decode {
+        error: DecodeError::InvalidData,
+        offset: 0,
+        path: DecodePath::default(),
+    }
+}
+
+fn byte_string_data(encoded: &[u8]) -> ModelResult<Vec<u8>> {
+    let mut parser = Parser::new(encoded);
+    let (_, prefix) = parser.decode::<I32>()?;
+    Ok(encoded[prefix.len()..].to_vec())
+}
+
+fn parse_script(parser: &mut Parser) -> ModelResult<ScriptModel> {
+    let methods = parser.decode_vec(|parser| {
+        let (is_public, _) = parser.decode::<Byte>()?;
+        let (asset_modifier, _) = parser.decode::<Byte>()?;
+        let (args_length, _) = parser.decode::<U16>()?;
+        let (locals_length, _) = parser.decode::<U16>()?;
+        let (return_length, _) = parser.decode::<U16>()?;
+        let instrs = parser.decode_vec(|parser| {
+            let (_, encoded) = parser.decode::<Instr>()?;
+            Ok(InstrModel {
+                code: encoded[0],
+                operands: encoded[1..].to_vec(),
+            })
+        })?;
+        Ok(MethodModel {
+            is_public: is_public.0,
+            asset_modifier: asset_modifier.0,
+            args_length: args_length.inner,
+            locals_length: locals_length.inner,
+            return_length: return_length.inner,
+            instrs,
+        })
+    })?;
+    Ok(ScriptModel { methods })
+}
+
+fn parse_lockup_script(parser: &mut Parser) -> ModelResult<LockupScriptModel> {
+    let (lockup_script, encoded) = parser.decode::<LockupScript>()?;
+    to_lockup_script_model(lockup_script, encoded)
+}
+
+fn to_lockup_script_model(
+    lockup_script: LockupScript,
+    encoded: &[u8],
+) -> ModelResult<LockupScriptModel> {
+    match lockup_script {
+        LockupScript::P2PKH(hash) => Ok(LockupScriptModel::P2PKH(hash.0)),
+        LockupScript::P2MPKH(_) => {
+            let mut parser = Parser::new(&encoded[1..]);
+            let public_key_hashes =
+                parser.decode_vec(|parser| Ok(parser.decode::<Hash>()?.0 .0))?;
+            let (m, _) = parser.decode::<U16>()?;
+            Ok(LockupScriptModel::P2MPKH {
+                public_key_hashes,
+                m: m.inner,
+            })
+        }
+        LockupScript::P2SH(hash) => Ok(LockupScriptModel::P2SH(hash.0)),
+        LockupScript::P2C(hash) => Ok(LockupScriptModel::P2C(hash.0)),
+        LockupScript::Unknown => Err(invalid_data()),
+    }
+}
+
+fn parse_val(parser: &mut Parser) -> ModelResult<ValModel> {
+    let (val, encoded) = parser.decode::<Val>()?;
+    match val {
+        Val::Bool(v) => Ok(ValModel::Bool(v.0)),
+        Val::I256(v) => Ok(ValModel::I256(v)),
+        Val::U256(v) => Ok(ValModel::U256(v)),
+        Val::ByteVec(_) => Ok(ValModel::ByteVec(byte_string_data(&encoded[1..])?)),
+        Val::Address(v) => Ok(ValModel::Address(to_lockup_script_model(v, &encoded[1..])?)),
+        Val::Unknown => Err(invalid_data()),
+    }
+}
+
+fn parse_input(parser: &mut Parser) -> ModelResult<TxInputModel> {
+    let (hint, _) = parser.decode::<Hint>()?;
+    let (key, _) = parser.decode::<Hash>()?;
+    let (unlock_script, encoded) = parser.decode::<UnlockScript>()?;
+    let unlock_script = match unlock_script {
+        UnlockScript::P2PKH(public_key) => UnlockScriptModel::P2PKH(public_key.0),
+        UnlockScript::P2MPKH(_) => {
+            let mut parser = Parser::new(&encoded[1..]);
+            let public_keys = parser.decode_vec(|parser| {
+                let (key, _) = parser.decode::<PublicKeyWithIndex>()?;
+                Ok((key.public_key.0, key.index.inner))
+            })?;
+            UnlockScriptModel::P2MPKH(public_keys)
+        }
+        UnlockScript::P2SH(_) => {
+            let mut parser = Parser::new(&encoded[1..]);
+            let script = parse_script(&mut parser)?;
+            let params = parser.decode_vec(parse_val)?;
+            UnlockScriptModel::P2SH { script, params }
+        }
+        UnlockScript::SameAsPrevious => UnlockScriptModel::SameAsPrevious,
+        UnlockScript::Unknown => return Err(invalid_data()),
+    };
+    Ok(TxInputModel {
+        hint: hint.0,
+        key: key.0,
+        unlock_script,
+    })
+}
+
+fn parse_output(parser: &mut Parser) -> ModelResult<AssetOutputModel> {
+    let (amount, _) = parser.decode::<U256>()?;
+    let lockup_script = parse_lockup_script(parser)?;
+    let (lock_time, _) = parser.decode::<TimeStamp>()?;
+    let tokens = parser.decode_vec(|parser| {
+        let (token, _) = parser.decode::<Token>()?;
+        Ok(TokenModel {
+            id: token.id.0,
+            amount: token.amount,
+        })
+    })?;
+    let (_, encoded) = parser.decode::<ByteString>()?;
+    Ok(AssetOutputModel {
+        amount,
+        lockup_script,
+        lock_time: lock_time.0,
+        tokens,
+        additional_data: byte_string_data(encoded)?,
+    })
+}
+
+// Run the device decoder over the whole tx so that the errors are reported with the decoder path
+fn validate(bytes: &[u8]) -> ModelResult<()> {
+    let mut temp_data: Vec<u8> = Vec::new();
+    let mut decoder = new_decoder::<UnsignedTx>();
+    let mut buffer = Buffer::new(bytes, &mut temp_data);
+    loop {
+        match decoder.step(&mut buffer) {
+            Ok(true) => {
+                if decoder.inner.is_complete() {
+                    break;
+                }
+                decoder.inner.next_step();
+                decoder.reset_stage();
+            }
+            Ok(false) => return Err(ModelError::UnexpectedEnd),
+            Err(error) => {
+                return Err(ModelError::Decode {
+                    error,
+                    offset: buffer.get_index(),
+                    path: decoder.inner.decode_path(),
+                })
+            }
+        }
+    }
+    if buffer.is_empty() {
+        Ok(())
+    } else {
+        Err(ModelError::TrailingBytes(buffer.len()))
+    }
+}
+
+impl UnsignedTxModel {
+    pub fn from_bytes(bytes: &[u8]) -> ModelResult<Self> {
+        validate(bytes)?;
+
+        let mut parser = Parser::new(bytes);
+        let (version, _) = parser.decode::<Byte>()?;
+        let (network_id, _) = parser.decode::<Byte>()?;
+        let (script_flag, _) = parser.decode::<Byte>()?;
+        let script = match script_flag.0 {
+            0 => None,
+            1 => Some(parse_script(&mut parser)?),
+            _ => return Err(invalid_data()),
+        };
+        let (gas_amount, _) = parser.decode::<I32>()?;
+        let (gas_price, _) = parser.decode::<U256>()?;
+        let inputs = parser.decode_vec(parse_input)?;
+        let fixed_outputs = parser.decode_vec(parse_output)?;
+        assert!(parser.is_empty());
+        Ok(UnsignedTxModel {
+            version: version.0,
+            network_id: network_id.0,
+            script,
+            gas_amount: gas_amount.inner,
+            gas_price,
+            inputs,
+            fixed_outputs,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let script = match &self.script {
+            None => String::from("null"),
+            Some(script) => script.to_json(),
+        };
+        format!(
+            "{{\"version\":{},\"networkId\":{},\"script\":{},\"gasAmount\":{},\"gasPrice\":\"{}\",\"inputs\":[{}],\"fixedOutputs\":[{}]}}",
+            self.version,
+            self.network_id,
+            script,
+            self.gas_amount,
+            u256_to_string(&self.gas_price),
+            join(self.inputs.iter().map(|input| input.to_json())),
+            join(self.fixed_outputs.iter().map(|output| output.to_json())),
+        )
+    }
+}
+
+impl ScriptModel {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"methods\":[{}]}}",
+            join(self.methods.iter().map(|method| method.to_json()))
+        )
+    }
+}
+
+impl MethodModel {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"isPublic\":{},\"assetModifier\":{},\"argsLength\":{},\"localsLength\":{},\"returnLength\":{},\"instrs\":[{}]}}",
+            self.is_public,
+            self.asset_modifier,
+            self.args_length,
+            self.locals_length,
+            self.return_length,
+            join(self.instrs.iter().map(|instr| instr.to_json())),
+        )
+    }
+}
+
+impl InstrModel {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"code\":{},\"operands\":\"{}\"}}",
+            self.code,
+            to_hex_string(&self.operands)
+        )
+    }
+}
+
+impl TxInputModel {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"hint\":\"{}\",\"key\":\"{}\",\"unlockScript\":{}}}",
+            to_hex_string(&self.hint),
+            to_hex_string(&self.key),
+            self.unlock_script.to_json()
+        )
+    }
+}
+
+impl UnlockScriptModel {
+    pub fn to_json(&self) -> String {
+        match self {
+            Self::P2PKH(public_key) => format!(
+                "{{\"type\":\"P2PKH\",\"publicKey\":\"{}\"}}",
+                to_hex_string(public_key)
+            ),
+            Self::P2MPKH(public_keys) => format!(
+                "{{\"type\":\"P2MPKH\",\"publicKeys\":[{}]}}",
+                join(public_keys.iter().map(|(public_key, index)| format!(
+                    "{{\"publicKey\":\"{}\",\"index\":{}}}",
+                    to_hex_string(public_key),
+                    index
+                )))
+            ),
+            Self::P2SH { script, params } => format!(
+                "{{\"type\":\"P2SH\",\"script\":{},\"params\":[{}]}}",
+                script.to_json(),
+                join(params.iter().map(|param| param.to_json()))
+            ),
+            Self::SameAsPrevious => String::from("{\"type\":\"SameAsPrevious\"}"),
+        }
+    }
+}
+
+impl LockupScriptModel {
+    pub fn get_type(&self) -> u8 {
+        match self {
+            Self::P2PKH(_) => 0,
+            Self::P2MPKH { .. } => 1,
+            Self::P2SH(_) => 2,
+            Self::P2C(_) => 3,
+        }
+    }
+
+    pub fn to_address(&self) -> String {
+        let mut encoded = Vec::new();
+        self.encode(&mut encoded).unwrap();
+        // The limbs are allocated on the heap, so multisig addresses can be arbitrarily long
+        let mut limbs = std::vec![0u32; encoded.len()];
+        let mut encoder = Base58Encoder::new(&mut limbs[..]);
+        assert!(encoder.update(&encoded));
+        let mut address = Vec::new();
+        assert!(encoder.finalize(&mut address));
+        String::from_utf8(address).unwrap()
+    }
+
+    pub fn to_json(&self) -> String {
+        let type_name = match self {
+            Self::P2PKH(_) => "P2PKH",
+            Self::P2MPKH { .. } => "P2MPKH",
+            Self::P2SH(_) => "P2SH",
+            Self::P2C(_) => "P2C",
+        };
+        match self {
+            Self::P2MPKH {
+                public_key_hashes,
+                m,
+            } => format!(
+                "{{\"type\":\"{}\",\"address\":\"{}\",\"publicKeyHashes\":[{}],\"m\":{}}}",
+                type_name,
+                self.to_address(),
+                join(
+                    public_key_hashes
+                        .iter()
+                        .map(|hash| format!("\"{}\"", to_hex_string(hash)))
+                ),
+                m
+            ),
+            _ => format!(
+                "{{\"type\":\"{}\",\"address\":\"{}\"}}",
+                type_name,
+                self.to_address()
+            ),
+        }
+    }
+}
+
+impl ValModel {
+    pub fn to_json(&self) -> String {
+        match self {
+            Self::Bool(v) => format!("{{\"type\":\"Bool\",\"value\":{}}}", v),
+            Self::I256(v) => format!(
+                "{{\"type\":\"I256\",\"encoded\":\"{}\"}}",
+                to_hex_string(&v.0.bytes[..v.0.get_length()])
+            ),
+            Self::U256(v) => format!("{{\"type\":\"U256\",\"value\":\"{}\"}}", u256_to_string(v)),
+            Self::ByteVec(v) => format!(
+                "{{\"type\":\"ByteVec\",\"value\":\"{}\"}}",
+                to_hex_string(v)
+            ),
+            Self::Address(v) => format!("{{\"type\":\"Address\",\"value\":{}}}", v.to_json()),
+        }
+    }
+}
+
+impl AssetOutputModel {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"amount\":\"{}\",\"lockupScript\":{},\"lockTime\":{},\"tokens\":[{}],\"additionalData\":\"{}\"}}",
+            u256_to_string(&self.amount),
+            self.lockup_script.to_json(),
+            self.lock_time,
+            join(self.tokens.iter().map(|token| format!(
+                "{{\"id\":\"{}\",\"amount\":\"{}\"}}",
+                to_hex_string(&token.id),
+                u256_to_string(&token.amount)
+            ))),
+            to_hex_string(&self.additional_data)
+        )
+    }
+}
+
+impl Encoder for UnsignedTxModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &[self.version, self.network_id])?;
+        self.script.encode(writer)?;
+        write_compact_i32(writer, self.gas_amount)?;
+        self.gas_price.encode(writer)?;
+        self.inputs.encode(writer)?;
+        self.fixed_outputs.encode(writer)
+    }
+}
+
+impl Encoder for ScriptModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.methods.encode(writer)
+    }
+}
+
+impl Encoder for MethodModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &[self.is_public, self.asset_modifier])?;
+        write_compact_i32(writer, self.args_length as i32)?;
+        write_compact_i32(writer, self.locals_length as i32)?;
+        write_compact_i32(writer, self.return_length as i32)?;
+        self.instrs.encode(writer)
+    }
+}
+
+impl Encoder for InstrModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &[self.code])?;
+        write_bytes(writer, &self.operands)
+    }
+}
+
+impl Encoder for TxInputModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &self.hint)?;
+        write_bytes(writer, &self.key)?;
+        self.unlock_script.encode(writer)
+    }
+}
+
+impl Encoder for UnlockScriptModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self {
+            Self::P2PKH(public_key) => {
+                write_bytes(writer, &[0])?;
+                write_bytes(writer, public_key)
+            }
+            Self::P2MPKH(public_keys) => {
+                write_bytes(writer, &[1])?;
+                write_size(writer, public_keys.len())?;
+                for (public_key, index) in public_keys {
+                    write_bytes(writer, public_key)?;
+                    write_compact_i32(writer, *index as i32)?;
+                }
+                Ok(())
+            }
+            Self::P2SH { script, params } => {
+                write_bytes(writer, &[2])?;
+                script.encode(writer)?;
+                params.encode(writer)
+            }
+            Self::SameAsPrevious => write_bytes(writer, &[3]),
+        }
+    }
+}
+
+impl Encoder for LockupScriptModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &[self.get_type()])?;
+        match self {
+            Self::P2PKH(hash) | Self::P2SH(hash) | Self::P2C(hash) => write_bytes(writer, hash),
+            Self::P2MPKH {
+                public_key_hashes,
+                m,
+            } => {
+                write_size(writer, public_key_hashes.len())?;
+                for hash in public_key_hashes {
+                    write_bytes(writer, hash)?;
+                }
+                write_compact_i32(writer, *m as i32)
+            }
+        }
+    }
+}
+
+impl Encoder for ValModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self {
+            Self::Bool(v) => write_bytes(writer, &[0, *v as u8]),
+            Self::I256(v) => (Byte(1), v).encode(writer),
+            Self::U256(v) => (Byte(2), v).encode(writer),
+            Self::ByteVec(v) => (Byte(3), ByteVec(v)).encode(writer),
+            Self::Address(v) => (Byte(4), v).encode(writer),
+        }
+    }
+}
+
+impl Encoder for TokenModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &self.id)?;
+        self.amount.encode(writer)
+    }
+}
+
+impl Encoder for AssetOutputModel {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.amount.encode(writer)?;
+        self.lockup_script.encode(writer)?;
+        write_bytes(writer, &self.lock_time.to_be_bytes())?;
+        self.tokens.encode(writer)?;
+        ByteVec(&self.additional_data).encode(writer)
+    }
+}
+
+impl<T: Encoder> Encoder for Vec<T> {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.as_slice().encode(writer)
+    }
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(",")
+}
+
+fn to_hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn u256_to_string(value: &U256) -> String {
+    let mut output = [0u8; 78];
+    let str = value.to_str(&mut output).unwrap();
+    String::from_utf8(str.to_vec()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::tests::encode_to_vec;
+    use crate::types::u256::tests::hex_to_bytes;
+
+    // The script tx from the unsigned tx tests
+    const SCRIPT_TX: &str = "0002010101030002000f144020923e3a6a5136338eed4fe14d96f219b192cea5c0c38a00b24186dc377b6b8b5c13016413006417011700150070f425d78af36c5c555b99098089da6a7ba34af9128e6e5d2dff1d457422a42d13c4016345785d8a0000a214409f0101409b01010000001cd38d0b363615036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d14008c0c2f0c7b15036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d1400d1a2140a5f5f6d61705f5f305f5f0c40440c0db1d20300b281d015036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d1400d1a20c0cce00d41e406a2f16001601ad1880030d40c1174876e80001b6b24f4b6b75d1147849306d23c316f1403d5af88be1d8a99a40c2f98c2ca146bfd938240003566752c71be4772a5c3a9d613cdf5e2dbe249cd6af46909c8272a91fe42c715100";
+
+    fn p2pkh_output(amount: u32, hash: [u8; 32], tokens: Vec<TokenModel>) -> AssetOutputModel {
+        AssetOutputModel {
+            amount: U256::from_encoded_bytes(&encode_to_vec(&I32::from(amount as i32))),
+            lockup_script: LockupScriptModel::P2PKH(hash),
+            lock_time: 0,
+            tokens,
+            additional_data: Vec::new(),
+        }
+    }
+
+    fn test_tx() -> UnsignedTxModel {
+        UnsignedTxModel {
+            version: 0,
+            network_id: 1,
+            script: None,
+            gas_amount: 20000,
+            gas_price: U256::from_encoded_bytes(&[0xc1, 0x17, 0x48, 0x76, 0xe8, 0x00]),
+            inputs: std::vec![
+                TxInputModel {
+                    hint: [1; 4],
+                    key: [2; 32],
+                    unlock_script: UnlockScriptModel::P2MPKH(std::vec![([3; 33], 0), ([4; 33], 2)]),
+                },
+                TxInputModel {
+                    hint: [5; 4],
+                    key: [6; 32],
+                    unlock_script: UnlockScriptModel::SameAsPrevious,
+                },
+            ],
+            fixed_outputs: std::vec![
+                p2pkh_output(10, [7; 32], Vec::new()),
+                p2pkh_output(
+                    20,
+                    [8; 32],
+                    std::vec![
+                        TokenModel {
+                            id: [9; 32],
+                            amount: U256::from_encoded_bytes(&[1]),
+                        },
+                        TokenModel {
+                            id: [10; 32],
+                            amount: U256::from_encoded_bytes(&[2]),
+                        },
+                    ],
+                ),
+                AssetOutputModel {
+                    lockup_script: LockupScriptModel::P2MPKH {
+                        public_key_hashes: std::vec![[11; 32], [12; 32], [13; 32]],
+                        m: 2,
+                    },
+                    additional_data: std::vec![0xab; 40],
+                    ..p2pkh_output(30, [0; 32], Vec::new())
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_model_round_trip() {
+        let tx = test_tx();
+        let bytes = encode_to_vec(&tx);
+        assert_eq!(UnsignedTxModel::from_bytes(&bytes), Ok(tx));
+
+        let bytes = hex_to_bytes(SCRIPT_TX).unwrap();
+        let tx = UnsignedTxModel::from_bytes(&bytes).unwrap();
+        assert_eq!(tx.network_id, 2);
+        assert_eq!(tx.script.as_ref().unwrap().methods.len(), 1);
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(tx.fixed_outputs.len(), 0);
+        assert_eq!(encode_to_vec(&tx), bytes);
+    }
+
+    #[test]
+    fn test_model_errors() {
+        let bytes = encode_to_vec(&test_tx());
+        assert_eq!(
+            UnsignedTxModel::from_bytes(&bytes[..(bytes.len() - 1)]),
+            Err(ModelError::UnexpectedEnd)
+        );
+
+        let mut trailing_bytes = bytes.clone();
+        trailing_bytes.extend([0, 0]);
+        assert_eq!(
+            UnsignedTxModel::from_bytes(&trailing_bytes),
+            Err(ModelError::TrailingBytes(2))
+        );
+
+        let tx = UnsignedTxModel {
+            fixed_outputs: Vec::new(),
+            ..test_tx()
+        };
+        let mut invalid_bytes = encode_to_vec(&tx);
+        invalid_bytes.pop(); // remove the size of the outputs
+        invalid_bytes.extend([1, 10, 9]); // 1 output, amount 10, invalid lockup script type
+        match UnsignedTxModel::from_bytes(&invalid_bytes) {
+            Err(ModelError::Decode {
+                error,
+                offset,
+                path,
+            }) => {
+                assert_eq!(error, DecodeError::InvalidData);
+                assert_eq!(offset, invalid_bytes.len());
+                assert_eq!(
+                    path,
+                    DecodePath {
+                        index: Some(0),
+                        field: Some("lockup_script"),
+                        ..DecodePath::new("FixedOutputs")
+                    }
+                );
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_model_to_json() {
+        let tx = UnsignedTxModel {
+            inputs: std::vec![TxInputModel {
+                hint: [1; 4],
+                key: [2; 32],
+                unlock_script: UnlockScriptModel::P2PKH([3; 33]),
+            }],
+            fixed_outputs: std::vec![p2pkh_output(
+                10,
+                [0; 32],
+                std::vec![TokenModel {
+                    id: [0xff; 32],
+                    amount: U256::from_encoded_bytes(&[1]),
+                }]
+            )],
+            ..test_tx()
+        };
+        let json = tx.to_json();
+        assert!(json.starts_with(
+            "{\"version\":0,\"networkId\":1,\"script\":null,\"gasAmount\":20000,\"gasPrice\":\"100000000000\",\"inputs\":[{\"hint\":\"01010101\""
+        ));
+        assert!(json.contains("\"unlockScript\":{\"type\":\"P2PKH\",\"publicKey\":\"0303"));
+        assert!(json.ends_with(
+            "\"fixedOutputs\":[{\"amount\":\"10\",\"lockupScript\":{\"type\":\"P2PKH\",\"address\":\"111111111111111111111111111111111\"},\"lockTime\":0,\"tokens\":[{\"id\":\"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\",\"amount\":\"1\"}],\"additionalData\":\"\"}]}"
+        ));
+    }
+}
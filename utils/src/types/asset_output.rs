@@ -1,6 +1,7 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -22,6 +23,29 @@ impl Reset for AssetOutput {
     }
 }
 
+impl AssetOutput {
+    pub fn field_name(&self, stage: &DecodeStage) -> Option<&'static str> {
+        match stage.step {
+            0 => Some("amount"),
+            1 => Some("lockup_script"),
+            2 => Some("lock_time"),
+            step if step > 2 && step <= (2 + self.tokens.step_size()) => Some("tokens"),
+            step if step < self.step_size() => Some("additional_data"),
+            _ => None,
+        }
+    }
+}
+
+impl Encoder for AssetOutput {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.amount.encode(writer)?;
+        self.lockup_script.encode(writer)?;
+        self.lock_time.encode(writer)?;
+        self.tokens.encode(writer)?;
+        self.additional_data.encode(writer)
+    }
+}
+
 impl RawDecoder for AssetOutput {
     fn step_size(&self) -> u16 {
         3 + self.tokens.step_size() + self.additional_data.step_size()
@@ -3,6 +3,7 @@ use core::cmp;
 use super::U16;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug))]
 pub struct AVector<T> {
@@ -48,6 +49,15 @@ impl<T> AVector<T> {
         }
     }
 
+    // The index and the decoder of the item being decoded, `None` if the size has not been decoded
+    pub fn get_decoding_item(&self) -> Option<(u16, &StreamingDecoder<T>)> {
+        if self.total_size_decoded() && !self.is_empty() {
+            Some((self.current_index as u16, &self.current_item))
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn size(&self) -> usize {
         self.total_size.inner as usize
@@ -85,6 +95,18 @@ impl<T: Default + RawDecoder> Default for AVector<T> {
     }
 }
 
+// The decoder only keeps the current item, so only empty vectors can be encoded
+// Use slices to encode vectors with items
+impl<T> Encoder for AVector<T> {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        if self.total_size_decoded() && self.is_empty() {
+            self.total_size.encode(writer)
+        } else {
+            Err(EncodeError::IncompleteData)
+        }
+    }
+}
+
 impl<T: Reset + RawDecoder> RawDecoder for AVector<T> {
     fn step_size(&self) -> u16 {
         if self.total_size_decoded() {
@@ -1,10 +1,11 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 use crate::types::compact_integer::*;
 
 const ENCODED_SIZE: usize = 33;
 
-#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 #[derive(Clone)]
 pub struct BigInt {
     pub bytes: [u8; ENCODED_SIZE],
@@ -50,6 +51,16 @@ impl BigInt {
     }
 }
 
+impl Encoder for BigInt {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        let length = self.get_length();
+        if length > ENCODED_SIZE {
+            return Err(EncodeError::InvalidData);
+        }
+        write_bytes(writer, &self.bytes[..length])
+    }
+}
+
 impl RawDecoder for BigInt {
     fn step_size(&self) -> u16 {
         1
@@ -70,6 +81,9 @@ impl RawDecoder for BigInt {
             stage.index
         };
         let length = self.get_length();
+        if length > ENCODED_SIZE {
+            return Err(DecodeError::InvalidSize);
+        }
         let mut idx = 0;
         while !buffer.is_empty() && idx < (length - (from_index as usize)) {
             self.bytes[(from_index as usize) + idx] = buffer.consume_byte().unwrap();
@@ -1,9 +1,10 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
-pub struct Bool(bool);
+pub struct Bool(pub bool);
 
 impl Reset for Bool {
     fn reset(&mut self) {
@@ -11,6 +12,12 @@ impl Reset for Bool {
     }
 }
 
+impl Encoder for Bool {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &[self.0 as u8])
+    }
+}
+
 impl RawDecoder for Bool {
     fn step_size(&self) -> u16 {
         1
@@ -1,5 +1,6 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -11,6 +12,12 @@ impl Reset for Byte {
     }
 }
 
+impl Encoder for Byte {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_bytes(writer, &[self.0])
+    }
+}
+
 impl RawDecoder for Byte {
     fn step_size(&self) -> u16 {
         1
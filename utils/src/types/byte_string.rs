@@ -1,5 +1,6 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 use super::I32;
 
@@ -50,6 +51,30 @@ impl ByteString {
     }
 }
 
+// The decoder skips the bytes, so only empty byte strings can be encoded
+impl Encoder for ByteString {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        if self.total_size_decoded() && self.size() == 0 {
+            self.length.encode(writer)
+        } else {
+            Err(EncodeError::IncompleteData)
+        }
+    }
+}
+
+// The bytes are encoded as `ByteString`
+pub struct ByteVec<'a>(pub &'a [u8]);
+
+impl<'a> Encoder for ByteVec<'a> {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        if self.0.len() > (i32::MAX as usize) {
+            return Err(EncodeError::InvalidData);
+        }
+        write_compact_i32(writer, self.0.len() as i32)?;
+        write_bytes(writer, self.0)
+    }
+}
+
 impl RawDecoder for ByteString {
     fn step_size(&self) -> u16 {
         1
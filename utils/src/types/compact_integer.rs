@@ -25,3 +25,19 @@ pub fn decode_length(byte: u8) -> usize {
         ((value & MASK_MODE) + 4 + 1) as usize
     }
 }
+
+// Encode the value with the signed compact encoding, returns the encoded bytes and the length
+pub fn encode_i32(value: i32) -> ([u8; 5], usize) {
+    let bytes = value.to_be_bytes();
+    if (-0x20..0x20).contains(&value) {
+        ([bytes[3] & (MASK_MODE as u8), 0, 0, 0, 0], 1)
+    } else if (-0x2000..0x2000).contains(&value) {
+        let first_byte = (bytes[2] & (MASK_MODE as u8)) | TWO_BYTE_PREFIX;
+        ([first_byte, bytes[3], 0, 0, 0], 2)
+    } else if (-0x20000000..0x20000000).contains(&value) {
+        let first_byte = (bytes[0] & (MASK_MODE as u8)) | FOUR_BYTE_PREFIX;
+        ([first_byte, bytes[1], bytes[2], bytes[3], 0], 4)
+    } else {
+        ([MASK_REST as u8, bytes[0], bytes[1], bytes[2], bytes[3]], 5)
+    }
+}
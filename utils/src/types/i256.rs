@@ -1,9 +1,10 @@
 use super::BigInt;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
-#[cfg_attr(test, derive(Debug))]
-#[derive(Default, PartialEq)]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Default, PartialEq, Clone)]
 pub struct I256(pub BigInt);
 
 impl Reset for I256 {
@@ -12,6 +13,12 @@ impl Reset for I256 {
     }
 }
 
+impl Encoder for I256 {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.0.encode(writer)
+    }
+}
+
 impl RawDecoder for I256 {
     fn step_size(&self) -> u16 {
         1
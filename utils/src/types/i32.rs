@@ -1,5 +1,6 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 use crate::types::compact_integer::*;
 
 use super::reset;
@@ -149,6 +150,12 @@ impl PartialEq for I32 {
     }
 }
 
+impl Encoder for I32 {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_compact_i32(writer, self.inner)
+    }
+}
+
 impl RawDecoder for I32 {
     fn step_size(&self) -> u16 {
         1
@@ -196,6 +203,7 @@ pub mod tests {
     extern crate alloc;
     extern crate std;
 
+    use crate::encode::tests::encode_to_vec;
     use crate::TempData;
 
     use super::*;
@@ -258,6 +266,8 @@ pub mod tests {
                 assert!(decoder.stage.is_complete())
             }
 
+            assert_eq!(encode_to_vec(&I32::from(item.1)), bytes);
+
             let mut length: usize = 0;
             let mut decoder = new_decoder::<I32>();
 
@@ -279,6 +289,20 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_encode_i32() {
+        let mut rng = rand::thread_rng();
+        let mut temp_data = TempData::new();
+        for _ in 0..1000 {
+            let value: i32 = rng.gen();
+            let bytes = encode_to_vec(&I32::from(value));
+            let mut decoder = new_decoder::<I32>();
+            let mut buffer = Buffer::new(&bytes, &mut temp_data);
+            assert_eq!(decoder.decode(&mut buffer), Ok(Some(&I32::from(value))));
+            assert!(buffer.is_empty());
+        }
+    }
+
     #[test]
     fn test_to_str() {
         let cases = [
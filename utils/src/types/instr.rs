@@ -1,7 +1,8 @@
-// auto-generated, do not edit
+// auto-generated by `utils/codegen` from `utils/codegen/opcodes.txt`, do not edit
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 use crate::types::method_selector::MethodSelector;
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub enum Instr {
@@ -411,6 +412,436 @@ impl Instr {
             _ => None,
         }
     }
+    pub fn get_type(&self) -> Option<u8> {
+        match self {
+            Self::CallLocal(_) => Some(0),
+            Self::CallExternal(_) => Some(1),
+            Self::Return => Some(2),
+            Self::ConstTrue => Some(3),
+            Self::ConstFalse => Some(4),
+            Self::I256Const0 => Some(5),
+            Self::I256Const1 => Some(6),
+            Self::I256Const2 => Some(7),
+            Self::I256Const3 => Some(8),
+            Self::I256Const4 => Some(9),
+            Self::I256Const5 => Some(10),
+            Self::I256ConstN1 => Some(11),
+            Self::U256Const0 => Some(12),
+            Self::U256Const1 => Some(13),
+            Self::U256Const2 => Some(14),
+            Self::U256Const3 => Some(15),
+            Self::U256Const4 => Some(16),
+            Self::U256Const5 => Some(17),
+            Self::I256Const(_) => Some(18),
+            Self::U256Const(_) => Some(19),
+            Self::BytesConst(_) => Some(20),
+            Self::AddressConst(_) => Some(21),
+            Self::LoadLocal(_) => Some(22),
+            Self::StoreLocal(_) => Some(23),
+            Self::Pop => Some(24),
+            Self::BoolNot => Some(25),
+            Self::BoolAnd => Some(26),
+            Self::BoolOr => Some(27),
+            Self::BoolEq => Some(28),
+            Self::BoolNeq => Some(29),
+            Self::BoolToByteVec => Some(30),
+            Self::I256Add => Some(31),
+            Self::I256Sub => Some(32),
+            Self::I256Mul => Some(33),
+            Self::I256Div => Some(34),
+            Self::I256Mod => Some(35),
+            Self::I256Eq => Some(36),
+            Self::I256Neq => Some(37),
+            Self::I256Lt => Some(38),
+            Self::I256Le => Some(39),
+            Self::I256Gt => Some(40),
+            Self::I256Ge => Some(41),
+            Self::U256Add => Some(42),
+            Self::U256Sub => Some(43),
+            Self::U256Mul => Some(44),
+            Self::U256Div => Some(45),
+            Self::U256Mod => Some(46),
+            Self::U256Eq => Some(47),
+            Self::U256Neq => Some(48),
+            Self::U256Lt => Some(49),
+            Self::U256Le => Some(50),
+            Self::U256Gt => Some(51),
+            Self::U256Ge => Some(52),
+            Self::U256ModAdd => Some(53),
+            Self::U256ModSub => Some(54),
+            Self::U256ModMul => Some(55),
+            Self::U256BitAnd => Some(56),
+            Self::U256BitOr => Some(57),
+            Self::U256Xor => Some(58),
+            Self::U256SHL => Some(59),
+            Self::U256SHR => Some(60),
+            Self::I256ToU256 => Some(61),
+            Self::I256ToByteVec => Some(62),
+            Self::U256ToI256 => Some(63),
+            Self::U256ToByteVec => Some(64),
+            Self::ByteVecEq => Some(65),
+            Self::ByteVecNeq => Some(66),
+            Self::ByteVecSize => Some(67),
+            Self::ByteVecConcat => Some(68),
+            Self::AddressEq => Some(69),
+            Self::AddressNeq => Some(70),
+            Self::AddressToByteVec => Some(71),
+            Self::IsAssetAddress => Some(72),
+            Self::IsContractAddress => Some(73),
+            Self::Jump(_) => Some(74),
+            Self::IfTrue(_) => Some(75),
+            Self::IfFalse(_) => Some(76),
+            Self::Assert => Some(77),
+            Self::Blake2b => Some(78),
+            Self::Keccak256 => Some(79),
+            Self::Sha256 => Some(80),
+            Self::Sha3 => Some(81),
+            Self::VerifyTxSignature => Some(82),
+            Self::VerifySecP256K1 => Some(83),
+            Self::VerifyED25519 => Some(84),
+            Self::NetworkId => Some(85),
+            Self::BlockTimeStamp => Some(86),
+            Self::BlockTarget => Some(87),
+            Self::TxId => Some(88),
+            Self::TxInputAddressAt => Some(89),
+            Self::TxInputsSize => Some(90),
+            Self::VerifyAbsoluteLocktime => Some(91),
+            Self::VerifyRelativeLocktime => Some(92),
+            Self::Log1 => Some(93),
+            Self::Log2 => Some(94),
+            Self::Log3 => Some(95),
+            Self::Log4 => Some(96),
+            Self::Log5 => Some(97),
+            Self::ByteVecSlice => Some(98),
+            Self::ByteVecToAddress => Some(99),
+            Self::Encode => Some(100),
+            Self::Zeros => Some(101),
+            Self::U256To1Byte => Some(102),
+            Self::U256To2Byte => Some(103),
+            Self::U256To4Byte => Some(104),
+            Self::U256To8Byte => Some(105),
+            Self::U256To16Byte => Some(106),
+            Self::U256To32Byte => Some(107),
+            Self::U256From1Byte => Some(108),
+            Self::U256From2Byte => Some(109),
+            Self::U256From4Byte => Some(110),
+            Self::U256From8Byte => Some(111),
+            Self::U256From16Byte => Some(112),
+            Self::U256From32Byte => Some(113),
+            Self::EthEcRecover => Some(114),
+            Self::Log6 => Some(115),
+            Self::Log7 => Some(116),
+            Self::Log8 => Some(117),
+            Self::Log9 => Some(118),
+            Self::ContractIdToAddress => Some(119),
+            Self::LoadLocalByIndex => Some(120),
+            Self::StoreLocalByIndex => Some(121),
+            Self::Dup => Some(122),
+            Self::AssertWithErrorCode => Some(123),
+            Self::Swap => Some(124),
+            Self::BlockHash => Some(125),
+            Self::DEBUG(_) => Some(126),
+            Self::TxGasPrice => Some(127),
+            Self::TxGasAmount => Some(128),
+            Self::TxGasFee => Some(129),
+            Self::I256Exp => Some(130),
+            Self::U256Exp => Some(131),
+            Self::U256ModExp => Some(132),
+            Self::VerifyBIP340Schnorr => Some(133),
+            Self::GetSegregatedSignature => Some(134),
+            Self::MulModN => Some(135),
+            Self::AddModN => Some(136),
+            Self::U256ToString => Some(137),
+            Self::I256ToString => Some(138),
+            Self::BoolToString => Some(139),
+            Self::GroupOfAddress => Some(140),
+            Self::LoadMutField(_) => Some(160),
+            Self::StoreMutField(_) => Some(161),
+            Self::ApproveAlph => Some(162),
+            Self::ApproveToken => Some(163),
+            Self::AlphRemaining => Some(164),
+            Self::TokenRemaining => Some(165),
+            Self::IsPaying => Some(166),
+            Self::TransferAlph => Some(167),
+            Self::TransferAlphFromSelf => Some(168),
+            Self::TransferAlphToSelf => Some(169),
+            Self::TransferToken => Some(170),
+            Self::TransferTokenFromSelf => Some(171),
+            Self::TransferTokenToSelf => Some(172),
+            Self::CreateContract => Some(173),
+            Self::CreateContractWithToken => Some(174),
+            Self::CopyCreateContract => Some(175),
+            Self::DestroySelf => Some(176),
+            Self::SelfContractId => Some(177),
+            Self::SelfAddress => Some(178),
+            Self::CallerContractId => Some(179),
+            Self::CallerAddress => Some(180),
+            Self::IsCalledFromTxScript => Some(181),
+            Self::CallerInitialStateHash => Some(182),
+            Self::CallerCodeHash => Some(183),
+            Self::ContractInitialStateHash => Some(184),
+            Self::ContractCodeHash => Some(185),
+            Self::MigrateSimple => Some(186),
+            Self::MigrateWithFields => Some(187),
+            Self::CopyCreateContractWithToken => Some(188),
+            Self::BurnToken => Some(189),
+            Self::LockApprovedAssets => Some(190),
+            Self::CreateSubContract => Some(191),
+            Self::CreateSubContractWithToken => Some(192),
+            Self::CopyCreateSubContract => Some(193),
+            Self::CopyCreateSubContractWithToken => Some(194),
+            Self::LoadMutFieldByIndex => Some(195),
+            Self::StoreMutFieldByIndex => Some(196),
+            Self::ContractExists => Some(197),
+            Self::CreateContractAndTransferToken => Some(198),
+            Self::CopyCreateContractAndTransferToken => Some(199),
+            Self::CreateSubContractAndTransferToken => Some(200),
+            Self::CopyCreateSubContractAndTransferToken => Some(201),
+            Self::NullContractAddress => Some(202),
+            Self::SubContractId => Some(203),
+            Self::SubContractIdOf => Some(204),
+            Self::ALPHTokenId => Some(205),
+            Self::LoadImmField(_) => Some(206),
+            Self::LoadImmFieldByIndex => Some(207),
+            Self::PayGasFee => Some(208),
+            Self::MinimalContractDeposit => Some(209),
+            Self::CreateMapEntry(_, _) => Some(210),
+            Self::MethodSelector(_) => Some(211),
+            Self::CallExternalBySelector(_) => Some(212),
+            Self::Unknown => None,
+        }
+    }
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::CallLocal(_) => "CallLocal",
+            Self::CallExternal(_) => "CallExternal",
+            Self::Return => "Return",
+            Self::ConstTrue => "ConstTrue",
+            Self::ConstFalse => "ConstFalse",
+            Self::I256Const0 => "I256Const0",
+            Self::I256Const1 => "I256Const1",
+            Self::I256Const2 => "I256Const2",
+            Self::I256Const3 => "I256Const3",
+            Self::I256Const4 => "I256Const4",
+            Self::I256Const5 => "I256Const5",
+            Self::I256ConstN1 => "I256ConstN1",
+            Self::U256Const0 => "U256Const0",
+            Self::U256Const1 => "U256Const1",
+            Self::U256Const2 => "U256Const2",
+            Self::U256Const3 => "U256Const3",
+            Self::U256Const4 => "U256Const4",
+            Self::U256Const5 => "U256Const5",
+            Self::I256Const(_) => "I256Const",
+            Self::U256Const(_) => "U256Const",
+            Self::BytesConst(_) => "BytesConst",
+            Self::AddressConst(_) => "AddressConst",
+            Self::LoadLocal(_) => "LoadLocal",
+            Self::StoreLocal(_) => "StoreLocal",
+            Self::Pop => "Pop",
+            Self::BoolNot => "BoolNot",
+            Self::BoolAnd => "BoolAnd",
+            Self::BoolOr => "BoolOr",
+            Self::BoolEq => "BoolEq",
+            Self::BoolNeq => "BoolNeq",
+            Self::BoolToByteVec => "BoolToByteVec",
+            Self::I256Add => "I256Add",
+            Self::I256Sub => "I256Sub",
+            Self::I256Mul => "I256Mul",
+            Self::I256Div => "I256Div",
+            Self::I256Mod => "I256Mod",
+            Self::I256Eq => "I256Eq",
+            Self::I256Neq => "I256Neq",
+            Self::I256Lt => "I256Lt",
+            Self::I256Le => "I256Le",
+            Self::I256Gt => "I256Gt",
+            Self::I256Ge => "I256Ge",
+            Self::U256Add => "U256Add",
+            Self::U256Sub => "U256Sub",
+            Self::U256Mul => "U256Mul",
+            Self::U256Div => "U256Div",
+            Self::U256Mod => "U256Mod",
+            Self::U256Eq => "U256Eq",
+            Self::U256Neq => "U256Neq",
+            Self::U256Lt => "U256Lt",
+            Self::U256Le => "U256Le",
+            Self::U256Gt => "U256Gt",
+            Self::U256Ge => "U256Ge",
+            Self::U256ModAdd => "U256ModAdd",
+            Self::U256ModSub => "U256ModSub",
+            Self::U256ModMul => "U256ModMul",
+            Self::U256BitAnd => "U256BitAnd",
+            Self::U256BitOr => "U256BitOr",
+            Self::U256Xor => "U256Xor",
+            Self::U256SHL => "U256SHL",
+            Self::U256SHR => "U256SHR",
+            Self::I256ToU256 => "I256ToU256",
+            Self::I256ToByteVec => "I256ToByteVec",
+            Self::U256ToI256 => "U256ToI256",
+            Self::U256ToByteVec => "U256ToByteVec",
+            Self::ByteVecEq => "ByteVecEq",
+            Self::ByteVecNeq => "ByteVecNeq",
+            Self::ByteVecSize => "ByteVecSize",
+            Self::ByteVecConcat => "ByteVecConcat",
+            Self::AddressEq => "AddressEq",
+            Self::AddressNeq => "AddressNeq",
+            Self::AddressToByteVec => "AddressToByteVec",
+            Self::IsAssetAddress => "IsAssetAddress",
+            Self::IsContractAddress => "IsContractAddress",
+            Self::Jump(_) => "Jump",
+            Self::IfTrue(_) => "IfTrue",
+            Self::IfFalse(_) => "IfFalse",
+            Self::Assert => "Assert",
+            Self::Blake2b => "Blake2b",
+            Self::Keccak256 => "Keccak256",
+            Self::Sha256 => "Sha256",
+            Self::Sha3 => "Sha3",
+            Self::VerifyTxSignature => "VerifyTxSignature",
+            Self::VerifySecP256K1 => "VerifySecP256K1",
+            Self::VerifyED25519 => "VerifyED25519",
+            Self::NetworkId => "NetworkId",
+            Self::BlockTimeStamp => "BlockTimeStamp",
+            Self::BlockTarget => "BlockTarget",
+            Self::TxId => "TxId",
+            Self::TxInputAddressAt => "TxInputAddressAt",
+            Self::TxInputsSize => "TxInputsSize",
+            Self::VerifyAbsoluteLocktime => "VerifyAbsoluteLocktime",
+            Self::VerifyRelativeLocktime => "VerifyRelativeLocktime",
+            Self::Log1 => "Log1",
+            Self::Log2 => "Log2",
+            Self::Log3 => "Log3",
+            Self::Log4 => "Log4",
+            Self::Log5 => "Log5",
+            Self::ByteVecSlice => "ByteVecSlice",
+            Self::ByteVecToAddress => "ByteVecToAddress",
+            Self::Encode => "Encode",
+            Self::Zeros => "Zeros",
+            Self::U256To1Byte => "U256To1Byte",
+            Self::U256To2Byte => "U256To2Byte",
+            Self::U256To4Byte => "U256To4Byte",
+            Self::U256To8Byte => "U256To8Byte",
+            Self::U256To16Byte => "U256To16Byte",
+            Self::U256To32Byte => "U256To32Byte",
+            Self::U256From1Byte => "U256From1Byte",
+            Self::U256From2Byte => "U256From2Byte",
+            Self::U256From4Byte => "U256From4Byte",
+            Self::U256From8Byte => "U256From8Byte",
+            Self::U256From16Byte => "U256From16Byte",
+            Self::U256From32Byte => "U256From32Byte",
+            Self::EthEcRecover => "EthEcRecover",
+            Self::Log6 => "Log6",
+            Self::Log7 => "Log7",
+            Self::Log8 => "Log8",
+            Self::Log9 => "Log9",
+            Self::ContractIdToAddress => "ContractIdToAddress",
+            Self::LoadLocalByIndex => "LoadLocalByIndex",
+            Self::StoreLocalByIndex => "StoreLocalByIndex",
+            Self::Dup => "Dup",
+            Self::AssertWithErrorCode => "AssertWithErrorCode",
+            Self::Swap => "Swap",
+            Self::BlockHash => "BlockHash",
+            Self::DEBUG(_) => "DEBUG",
+            Self::TxGasPrice => "TxGasPrice",
+            Self::TxGasAmount => "TxGasAmount",
+            Self::TxGasFee => "TxGasFee",
+            Self::I256Exp => "I256Exp",
+            Self::U256Exp => "U256Exp",
+            Self::U256ModExp => "U256ModExp",
+            Self::VerifyBIP340Schnorr => "VerifyBIP340Schnorr",
+            Self::GetSegregatedSignature => "GetSegregatedSignature",
+            Self::MulModN => "MulModN",
+            Self::AddModN => "AddModN",
+            Self::U256ToString => "U256ToString",
+            Self::I256ToString => "I256ToString",
+            Self::BoolToString => "BoolToString",
+            Self::GroupOfAddress => "GroupOfAddress",
+            Self::LoadMutField(_) => "LoadMutField",
+            Self::StoreMutField(_) => "StoreMutField",
+            Self::ApproveAlph => "ApproveAlph",
+            Self::ApproveToken => "ApproveToken",
+            Self::AlphRemaining => "AlphRemaining",
+            Self::TokenRemaining => "TokenRemaining",
+            Self::IsPaying => "IsPaying",
+            Self::TransferAlph => "TransferAlph",
+            Self::TransferAlphFromSelf => "TransferAlphFromSelf",
+            Self::TransferAlphToSelf => "TransferAlphToSelf",
+            Self::TransferToken => "TransferToken",
+            Self::TransferTokenFromSelf => "TransferTokenFromSelf",
+            Self::TransferTokenToSelf => "TransferTokenToSelf",
+            Self::CreateContract => "CreateContract",
+            Self::CreateContractWithToken => "CreateContractWithToken",
+            Self::CopyCreateContract => "CopyCreateContract",
+            Self::DestroySelf => "DestroySelf",
+            Self::SelfContractId => "SelfContractId",
+            Self::SelfAddress => "SelfAddress",
+            Self::CallerContractId => "CallerContractId",
+            Self::CallerAddress => "CallerAddress",
+            Self::IsCalledFromTxScript => "IsCalledFromTxScript",
+            Self::CallerInitialStateHash => "CallerInitialStateHash",
+            Self::CallerCodeHash => "CallerCodeHash",
+            Self::ContractInitialStateHash => "ContractInitialStateHash",
+            Self::ContractCodeHash => "ContractCodeHash",
+            Self::MigrateSimple => "MigrateSimple",
+            Self::MigrateWithFields => "MigrateWithFields",
+            Self::CopyCreateContractWithToken => "CopyCreateContractWithToken",
+            Self::BurnToken => "BurnToken",
+            Self::LockApprovedAssets => "LockApprovedAssets",
+            Self::CreateSubContract => "CreateSubContract",
+            Self::CreateSubContractWithToken => "CreateSubContractWithToken",
+            Self::CopyCreateSubContract => "CopyCreateSubContract",
+            Self::CopyCreateSubContractWithToken => "CopyCreateSubContractWithToken",
+            Self::LoadMutFieldByIndex => "LoadMutFieldByIndex",
+            Self::StoreMutFieldByIndex => "StoreMutFieldByIndex",
+            Self::ContractExists => "ContractExists",
+            Self::CreateContractAndTransferToken => "CreateContractAndTransferToken",
+            Self::CopyCreateContractAndTransferToken => "CopyCreateContractAndTransferToken",
+            Self::CreateSubContractAndTransferToken => "CreateSubContractAndTransferToken",
+            Self::CopyCreateSubContractAndTransferToken => "CopyCreateSubContractAndTransferToken",
+            Self::NullContractAddress => "NullContractAddress",
+            Self::SubContractId => "SubContractId",
+            Self::SubContractIdOf => "SubContractIdOf",
+            Self::ALPHTokenId => "ALPHTokenId",
+            Self::LoadImmField(_) => "LoadImmField",
+            Self::LoadImmFieldByIndex => "LoadImmFieldByIndex",
+            Self::PayGasFee => "PayGasFee",
+            Self::MinimalContractDeposit => "MinimalContractDeposit",
+            Self::CreateMapEntry(_, _) => "CreateMapEntry",
+            Self::MethodSelector(_) => "MethodSelector",
+            Self::CallExternalBySelector(_) => "CallExternalBySelector",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+impl Encoder for Instr {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self.get_type() {
+            Some(tpe) => write_bytes(writer, &[tpe])?,
+            None => return Err(EncodeError::InvalidData),
+        }
+        match self {
+            Self::CallLocal(v0) => v0.encode(writer),
+            Self::CallExternal(v0) => v0.encode(writer),
+            Self::I256Const(v0) => v0.encode(writer),
+            Self::U256Const(v0) => v0.encode(writer),
+            Self::BytesConst(v0) => v0.encode(writer),
+            Self::AddressConst(v0) => v0.encode(writer),
+            Self::LoadLocal(v0) => v0.encode(writer),
+            Self::StoreLocal(v0) => v0.encode(writer),
+            Self::Jump(v0) => v0.encode(writer),
+            Self::IfTrue(v0) => v0.encode(writer),
+            Self::IfFalse(v0) => v0.encode(writer),
+            Self::DEBUG(v0) => v0.encode(writer),
+            Self::LoadMutField(v0) => v0.encode(writer),
+            Self::StoreMutField(v0) => v0.encode(writer),
+            Self::LoadImmField(v0) => v0.encode(writer),
+            Self::CreateMapEntry(v0, v1) => {
+                v0.encode(writer)?;
+                v1.encode(writer)
+            }
+            Self::MethodSelector(v0) => v0.encode(writer),
+            Self::CallExternalBySelector(v0) => v0.encode(writer),
+            _ => Ok(()),
+        }
+    }
 }
 impl RawDecoder for Instr {
     fn step_size(&self) -> u16 {
@@ -489,226 +920,237 @@ pub mod tests {
     extern crate std;
     use super::Instr;
     use crate::buffer::Buffer;
+    use crate::encode::tests::encode_to_vec;
+    use crate::encode::{EncodeError, Encoder};
     use crate::types::u256::tests::hex_to_bytes;
     use crate::types::{Hash, LockupScript};
     use crate::{decode::*, TempData};
     use std::mem::discriminant;
+    use std::vec::Vec;
 
     #[test]
     fn decode_instr() {
         let mut temp_data = TempData::new();
 
-        const ALL_INSTRS: &[(u8, &str)] = &[
-            (0, "0012"),
-            (1, "017f"),
-            (2, "02"),
-            (3, "03"),
-            (4, "04"),
-            (5, "05"),
-            (6, "06"),
-            (7, "07"),
-            (8, "08"),
-            (9, "09"),
-            (10, "0a"),
-            (11, "0b"),
-            (12, "0c"),
-            (13, "0d"),
-            (14, "0e"),
-            (15, "0f"),
-            (16, "10"),
-            (17, "11"),
+        // The byte code, the mnemonic and an encoded instruction
+        const ALL_INSTRS: &[(u8, &str, &str)] = &[
+            (0, "CallLocal", "007f"),
+            (1, "CallExternal", "017f"),
+            (2, "Return", "02"),
+            (3, "ConstTrue", "03"),
+            (4, "ConstFalse", "04"),
+            (5, "I256Const0", "05"),
+            (6, "I256Const1", "06"),
+            (7, "I256Const2", "07"),
+            (8, "I256Const3", "08"),
+            (9, "I256Const4", "09"),
+            (10, "I256Const5", "0a"),
+            (11, "I256ConstN1", "0b"),
+            (12, "U256Const0", "0c"),
+            (13, "U256Const1", "0d"),
+            (14, "U256Const2", "0e"),
+            (15, "U256Const3", "0f"),
+            (16, "U256Const4", "10"),
+            (17, "U256Const5", "11"),
             (
                 18,
+                "I256Const",
                 "12dc3d43c5d96c11b5ef8d581dd462b8d3ae8e11b9ab7726acc27fe28deee2a60f5a",
             ),
             (
                 19,
+                "U256Const",
                 "13dc9303ae010e913964a4478bad3ceea341a1d76f140085f3d4c674e1a10678d74f",
             ),
-            (20, "1400"),
+            (20, "BytesConst", "1400"),
             (
                 21,
+                "AddressConst",
                 "15000000000000000000000000000000000000000000000000000000000000000000",
             ),
-            (22, "1680"),
-            (23, "1700"),
-            (24, "18"),
-            (25, "19"),
-            (26, "1a"),
-            (27, "1b"),
-            (28, "1c"),
-            (29, "1d"),
-            (30, "1e"),
-            (31, "1f"),
-            (32, "20"),
-            (33, "21"),
-            (34, "22"),
-            (35, "23"),
-            (36, "24"),
-            (37, "25"),
-            (38, "26"),
-            (39, "27"),
-            (40, "28"),
-            (41, "29"),
-            (42, "2a"),
-            (43, "2b"),
-            (44, "2c"),
-            (45, "2d"),
-            (46, "2e"),
-            (47, "2f"),
-            (48, "30"),
-            (49, "31"),
-            (50, "32"),
-            (51, "33"),
-            (52, "34"),
-            (53, "35"),
-            (54, "36"),
-            (55, "37"),
-            (56, "38"),
-            (57, "39"),
-            (58, "3a"),
-            (59, "3b"),
-            (60, "3c"),
-            (61, "3d"),
-            (62, "3e"),
-            (63, "3f"),
-            (64, "40"),
-            (65, "41"),
-            (66, "42"),
-            (67, "43"),
-            (68, "44"),
-            (69, "45"),
-            (70, "46"),
-            (71, "47"),
-            (72, "48"),
-            (73, "49"),
-            (74, "4a00"),
-            (75, "4b80010000"),
-            (76, "4cbfff0000"),
-            (77, "4d"),
-            (78, "4e"),
-            (79, "4f"),
-            (80, "50"),
-            (81, "51"),
-            (82, "52"),
-            (83, "53"),
-            (84, "54"),
-            (85, "55"),
-            (86, "56"),
-            (87, "57"),
-            (88, "58"),
-            (89, "59"),
-            (90, "5a"),
-            (91, "5b"),
-            (92, "5c"),
-            (93, "5d"),
-            (94, "5e"),
-            (95, "5f"),
-            (96, "60"),
-            (97, "61"),
-            (98, "62"),
-            (99, "63"),
-            (100, "64"),
-            (101, "65"),
-            (102, "66"),
-            (103, "67"),
-            (104, "68"),
-            (105, "69"),
-            (106, "6a"),
-            (107, "6b"),
-            (108, "6c"),
-            (109, "6d"),
-            (110, "6e"),
-            (111, "6f"),
-            (112, "70"),
-            (113, "71"),
-            (114, "72"),
-            (115, "73"),
-            (116, "74"),
-            (117, "75"),
-            (118, "76"),
-            (119, "77"),
-            (120, "78"),
-            (121, "79"),
-            (122, "7a"),
-            (123, "7b"),
-            (124, "7c"),
-            (125, "7d"),
-            (126, "7e00"),
-            (127, "7f"),
-            (128, "80"),
-            (129, "81"),
-            (130, "82"),
-            (131, "83"),
-            (132, "84"),
-            (133, "85"),
-            (134, "86"),
-            (135, "87"),
-            (136, "88"),
-            (137, "89"),
-            (138, "8a"),
-            (139, "8b"),
-            (140, "8c"),
-            (160, "a07f"),
-            (161, "a180"),
-            (162, "a2"),
-            (163, "a3"),
-            (164, "a4"),
-            (165, "a5"),
-            (166, "a6"),
-            (167, "a7"),
-            (168, "a8"),
-            (169, "a9"),
-            (170, "aa"),
-            (171, "ab"),
-            (172, "ac"),
-            (173, "ad"),
-            (174, "ae"),
-            (175, "af"),
-            (176, "b0"),
-            (177, "b1"),
-            (178, "b2"),
-            (179, "b3"),
-            (180, "b4"),
-            (181, "b5"),
-            (182, "b6"),
-            (183, "b7"),
-            (184, "b8"),
-            (185, "b9"),
-            (186, "ba"),
-            (187, "bb"),
-            (188, "bc"),
-            (189, "bd"),
-            (190, "be"),
-            (191, "bf"),
-            (192, "c0"),
-            (193, "c1"),
-            (194, "c2"),
-            (195, "c3"),
-            (196, "c4"),
-            (197, "c5"),
-            (198, "c6"),
-            (199, "c7"),
-            (200, "c8"),
-            (201, "c9"),
-            (202, "ca"),
-            (203, "cb"),
-            (204, "cc"),
-            (205, "cd"),
-            (206, "ce00"),
-            (207, "cf"),
-            (208, "d0"),
-            (209, "d1"),
-            (210, "d27f7f"),
-            (211, "d300000000"),
-            (212, "d400000000"),
+            (22, "LoadLocal", "167f"),
+            (23, "StoreLocal", "177f"),
+            (24, "Pop", "18"),
+            (25, "BoolNot", "19"),
+            (26, "BoolAnd", "1a"),
+            (27, "BoolOr", "1b"),
+            (28, "BoolEq", "1c"),
+            (29, "BoolNeq", "1d"),
+            (30, "BoolToByteVec", "1e"),
+            (31, "I256Add", "1f"),
+            (32, "I256Sub", "20"),
+            (33, "I256Mul", "21"),
+            (34, "I256Div", "22"),
+            (35, "I256Mod", "23"),
+            (36, "I256Eq", "24"),
+            (37, "I256Neq", "25"),
+            (38, "I256Lt", "26"),
+            (39, "I256Le", "27"),
+            (40, "I256Gt", "28"),
+            (41, "I256Ge", "29"),
+            (42, "U256Add", "2a"),
+            (43, "U256Sub", "2b"),
+            (44, "U256Mul", "2c"),
+            (45, "U256Div", "2d"),
+            (46, "U256Mod", "2e"),
+            (47, "U256Eq", "2f"),
+            (48, "U256Neq", "30"),
+            (49, "U256Lt", "31"),
+            (50, "U256Le", "32"),
+            (51, "U256Gt", "33"),
+            (52, "U256Ge", "34"),
+            (53, "U256ModAdd", "35"),
+            (54, "U256ModSub", "36"),
+            (55, "U256ModMul", "37"),
+            (56, "U256BitAnd", "38"),
+            (57, "U256BitOr", "39"),
+            (58, "U256Xor", "3a"),
+            (59, "U256SHL", "3b"),
+            (60, "U256SHR", "3c"),
+            (61, "I256ToU256", "3d"),
+            (62, "I256ToByteVec", "3e"),
+            (63, "U256ToI256", "3f"),
+            (64, "U256ToByteVec", "40"),
+            (65, "ByteVecEq", "41"),
+            (66, "ByteVecNeq", "42"),
+            (67, "ByteVecSize", "43"),
+            (68, "ByteVecConcat", "44"),
+            (69, "AddressEq", "45"),
+            (70, "AddressNeq", "46"),
+            (71, "AddressToByteVec", "47"),
+            (72, "IsAssetAddress", "48"),
+            (73, "IsContractAddress", "49"),
+            (74, "Jump", "4a80010000"),
+            (75, "IfTrue", "4b80010000"),
+            (76, "IfFalse", "4c80010000"),
+            (77, "Assert", "4d"),
+            (78, "Blake2b", "4e"),
+            (79, "Keccak256", "4f"),
+            (80, "Sha256", "50"),
+            (81, "Sha3", "51"),
+            (82, "VerifyTxSignature", "52"),
+            (83, "VerifySecP256K1", "53"),
+            (84, "VerifyED25519", "54"),
+            (85, "NetworkId", "55"),
+            (86, "BlockTimeStamp", "56"),
+            (87, "BlockTarget", "57"),
+            (88, "TxId", "58"),
+            (89, "TxInputAddressAt", "59"),
+            (90, "TxInputsSize", "5a"),
+            (91, "VerifyAbsoluteLocktime", "5b"),
+            (92, "VerifyRelativeLocktime", "5c"),
+            (93, "Log1", "5d"),
+            (94, "Log2", "5e"),
+            (95, "Log3", "5f"),
+            (96, "Log4", "60"),
+            (97, "Log5", "61"),
+            (98, "ByteVecSlice", "62"),
+            (99, "ByteVecToAddress", "63"),
+            (100, "Encode", "64"),
+            (101, "Zeros", "65"),
+            (102, "U256To1Byte", "66"),
+            (103, "U256To2Byte", "67"),
+            (104, "U256To4Byte", "68"),
+            (105, "U256To8Byte", "69"),
+            (106, "U256To16Byte", "6a"),
+            (107, "U256To32Byte", "6b"),
+            (108, "U256From1Byte", "6c"),
+            (109, "U256From2Byte", "6d"),
+            (110, "U256From4Byte", "6e"),
+            (111, "U256From8Byte", "6f"),
+            (112, "U256From16Byte", "70"),
+            (113, "U256From32Byte", "71"),
+            (114, "EthEcRecover", "72"),
+            (115, "Log6", "73"),
+            (116, "Log7", "74"),
+            (117, "Log8", "75"),
+            (118, "Log9", "76"),
+            (119, "ContractIdToAddress", "77"),
+            (120, "LoadLocalByIndex", "78"),
+            (121, "StoreLocalByIndex", "79"),
+            (122, "Dup", "7a"),
+            (123, "AssertWithErrorCode", "7b"),
+            (124, "Swap", "7c"),
+            (125, "BlockHash", "7d"),
+            (126, "DEBUG", "7e00"),
+            (127, "TxGasPrice", "7f"),
+            (128, "TxGasAmount", "80"),
+            (129, "TxGasFee", "81"),
+            (130, "I256Exp", "82"),
+            (131, "U256Exp", "83"),
+            (132, "U256ModExp", "84"),
+            (133, "VerifyBIP340Schnorr", "85"),
+            (134, "GetSegregatedSignature", "86"),
+            (135, "MulModN", "87"),
+            (136, "AddModN", "88"),
+            (137, "U256ToString", "89"),
+            (138, "I256ToString", "8a"),
+            (139, "BoolToString", "8b"),
+            (140, "GroupOfAddress", "8c"),
+            (160, "LoadMutField", "a07f"),
+            (161, "StoreMutField", "a17f"),
+            (162, "ApproveAlph", "a2"),
+            (163, "ApproveToken", "a3"),
+            (164, "AlphRemaining", "a4"),
+            (165, "TokenRemaining", "a5"),
+            (166, "IsPaying", "a6"),
+            (167, "TransferAlph", "a7"),
+            (168, "TransferAlphFromSelf", "a8"),
+            (169, "TransferAlphToSelf", "a9"),
+            (170, "TransferToken", "aa"),
+            (171, "TransferTokenFromSelf", "ab"),
+            (172, "TransferTokenToSelf", "ac"),
+            (173, "CreateContract", "ad"),
+            (174, "CreateContractWithToken", "ae"),
+            (175, "CopyCreateContract", "af"),
+            (176, "DestroySelf", "b0"),
+            (177, "SelfContractId", "b1"),
+            (178, "SelfAddress", "b2"),
+            (179, "CallerContractId", "b3"),
+            (180, "CallerAddress", "b4"),
+            (181, "IsCalledFromTxScript", "b5"),
+            (182, "CallerInitialStateHash", "b6"),
+            (183, "CallerCodeHash", "b7"),
+            (184, "ContractInitialStateHash", "b8"),
+            (185, "ContractCodeHash", "b9"),
+            (186, "MigrateSimple", "ba"),
+            (187, "MigrateWithFields", "bb"),
+            (188, "CopyCreateContractWithToken", "bc"),
+            (189, "BurnToken", "bd"),
+            (190, "LockApprovedAssets", "be"),
+            (191, "CreateSubContract", "bf"),
+            (192, "CreateSubContractWithToken", "c0"),
+            (193, "CopyCreateSubContract", "c1"),
+            (194, "CopyCreateSubContractWithToken", "c2"),
+            (195, "LoadMutFieldByIndex", "c3"),
+            (196, "StoreMutFieldByIndex", "c4"),
+            (197, "ContractExists", "c5"),
+            (198, "CreateContractAndTransferToken", "c6"),
+            (199, "CopyCreateContractAndTransferToken", "c7"),
+            (200, "CreateSubContractAndTransferToken", "c8"),
+            (201, "CopyCreateSubContractAndTransferToken", "c9"),
+            (202, "NullContractAddress", "ca"),
+            (203, "SubContractId", "cb"),
+            (204, "SubContractIdOf", "cc"),
+            (205, "ALPHTokenId", "cd"),
+            (206, "LoadImmField", "ce7f"),
+            (207, "LoadImmFieldByIndex", "cf"),
+            (208, "PayGasFee", "d0"),
+            (209, "MinimalContractDeposit", "d1"),
+            (210, "CreateMapEntry", "d27f7f"),
+            (211, "MethodSelector", "d300000000"),
+            (212, "CallExternalBySelector", "d400000000"),
         ];
 
-        for &(code, hex) in ALL_INSTRS {
+        for &(code, mnemonic, hex) in ALL_INSTRS {
             let bytes = hex_to_bytes(hex).unwrap();
             let mut decoder = new_decoder::<Instr>();
             let mut buffer = Buffer::new(&bytes, &mut temp_data);
             let decoded_instr = decoder.decode(&mut buffer).unwrap().unwrap();
+            assert!(buffer.is_empty());
+            assert_eq!(decoded_instr.get_type(), Some(code));
+            assert_eq!(decoded_instr.mnemonic(), mnemonic);
+            assert_eq!(encode_to_vec(decoded_instr), bytes);
 
             let instr = Instr::from_type(code);
             assert!(instr.is_some());
@@ -717,36 +1159,62 @@ pub mod tests {
                 discriminant(decoded_instr)
             );
             match decoded_instr {
-                Instr::CallLocal(v) => assert!(v.0 == bytes[1]),
-                Instr::CallExternal(v) => assert!(v.0 == bytes[1]),
-                Instr::I256Const(v) => {
-                    assert!(bytes.len() == 34);
-                    assert!(v.0.bytes == &bytes[1..]);
+                Instr::CallLocal(v0) => {
+                    assert!(v0.0 == 0x7f);
+                }
+                Instr::CallExternal(v0) => {
+                    assert!(v0.0 == 0x7f);
+                }
+                Instr::I256Const(v0) => {
+                    assert!(v0.0.bytes[..] == hex_to_bytes("dc3d43c5d96c11b5ef8d581dd462b8d3ae8e11b9ab7726acc27fe28deee2a60f5a").unwrap()[..]);
+                }
+                Instr::U256Const(v0) => {
+                    assert!(v0.0.bytes[..] == hex_to_bytes("dc9303ae010e913964a4478bad3ceea341a1d76f140085f3d4c674e1a10678d74f").unwrap()[..]);
+                }
+                Instr::BytesConst(v0) => {
+                    assert!(v0.length.inner == 0);
+                }
+                Instr::AddressConst(v0) => {
+                    assert!(*v0 == LockupScript::P2PKH(Hash::from_bytes([0u8; 32])));
+                }
+                Instr::LoadLocal(v0) => {
+                    assert!(v0.0 == 0x7f);
+                }
+                Instr::StoreLocal(v0) => {
+                    assert!(v0.0 == 0x7f);
+                }
+                Instr::Jump(v0) => {
+                    assert!(v0.inner == 65536);
+                }
+                Instr::IfTrue(v0) => {
+                    assert!(v0.inner == 65536);
+                }
+                Instr::IfFalse(v0) => {
+                    assert!(v0.inner == 65536);
+                }
+                Instr::DEBUG(v0) => {
+                    assert!(v0.is_empty());
+                }
+                Instr::LoadMutField(v0) => {
+                    assert!(v0.0 == 0x7f);
+                }
+                Instr::StoreMutField(v0) => {
+                    assert!(v0.0 == 0x7f);
+                }
+                Instr::LoadImmField(v0) => {
+                    assert!(v0.0 == 0x7f);
                 }
-                Instr::U256Const(v) => {
-                    assert!(bytes.len() == 34);
-                    assert!(v.0.bytes == &bytes[1..]);
+                Instr::CreateMapEntry(v0, v1) => {
+                    assert!(v0.0 == 0x7f);
+                    assert!(v1.0 == 0x7f);
                 }
-                Instr::BytesConst(v) => {
-                    assert!(bytes.len() == 2);
-                    assert!(v.length.inner == 0);
+                Instr::MethodSelector(v0) => {
+                    assert!(v0.0 == 0);
                 }
-                Instr::AddressConst(v) => {
-                    assert!(*v == LockupScript::P2PKH(Hash::from_bytes([0u8; 32])));
+                Instr::CallExternalBySelector(v0) => {
+                    assert!(v0.0 == 0);
                 }
-                Instr::LoadLocal(v) => assert!(v.0 == bytes[1]),
-                Instr::StoreLocal(v) => assert!(v.0 == bytes[1]),
-                Instr::Jump(v) => assert!(v.inner == 0),
-                Instr::IfTrue(v) => assert!(v.inner == 65536),
-                Instr::IfFalse(v) => assert!(v.inner == -65536),
-                Instr::DEBUG(v) => assert!(v.is_empty()),
-                Instr::LoadMutField(v) => assert!(v.0 == bytes[1]),
-                Instr::StoreMutField(v) => assert!(v.0 == bytes[1]),
-                Instr::LoadImmField(v) => assert!(v.0 == bytes[1]),
-                Instr::CreateMapEntry(v0, v1) => assert!(v0.0 == bytes[1] && v1.0 == bytes[2]),
-                Instr::MethodSelector(v) => assert!(v.0 == 0),
-                Instr::CallExternalBySelector(v) => assert!(v.0 == 0),
-                instr => assert!(*decoded_instr == *instr),
+                _ => assert!(instr.as_ref() == Some(decoded_instr)),
             }
         }
     }
@@ -758,5 +1226,7 @@ pub mod tests {
         let mut buffer = Buffer::new(&[255], &mut temp_data);
         let instr = decoder.decode(&mut buffer).unwrap().unwrap();
         assert!(*instr == Instr::Unknown);
+        assert_eq!(instr.mnemonic(), "Unknown");
+        assert_eq!(instr.encode(&mut Vec::new()), Err(EncodeError::InvalidData));
     }
 }
@@ -1,6 +1,8 @@
 use super::{Byte32, Hash, U16};
-use crate::buffer::{Buffer, Writable};
+use crate::base58::base58_decode;
+use crate::buffer::{Buffer, SkipTempData, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -64,6 +66,13 @@ impl RawDecoder for P2MPKH {
     }
 }
 
+// The public key hashes are not kept by the decoder
+impl Encoder for P2MPKH {
+    fn encode<W: Writable>(&self, _writer: &mut W) -> EncodeResult<()> {
+        Err(EncodeError::IncompleteData)
+    }
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
 pub enum LockupScript {
@@ -101,6 +110,48 @@ impl LockupScript {
             _ => 0xff, // dead branch
         }
     }
+
+    // Parses a base58 address, the decoded bytes are written to `output` and returned together
+    // with the lockup script, since the P2MPKH lockup script does not keep the public key hashes
+    pub fn from_address_str<'a>(
+        address: &[u8],
+        output: &'a mut [u8],
+    ) -> DecodeResult<(LockupScript, &'a [u8])> {
+        let bytes = base58_decode(address, output).ok_or(DecodeError::InvalidData)?;
+        if bytes.is_empty() {
+            return Err(DecodeError::InvalidSize);
+        }
+        let mut temp_data = SkipTempData;
+        let mut buffer = Buffer::new(bytes, &mut temp_data);
+        let mut decoder = new_decoder::<LockupScript>();
+        if decoder.decode(&mut buffer)?.is_none() || !buffer.is_empty() {
+            return Err(DecodeError::InvalidSize);
+        }
+        if let LockupScript::P2MPKH(p2mpkh) = &decoder.inner {
+            let size = p2mpkh.inner.size.inner;
+            let m = p2mpkh.inner.m.inner;
+            if size == 0 || m == 0 || m > size {
+                return Err(DecodeError::InvalidData);
+            }
+        }
+        Ok((core::mem::take(&mut decoder.inner), bytes))
+    }
+}
+
+impl Encoder for LockupScript {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        if let LockupScript::Unknown = self {
+            return Err(EncodeError::InvalidData);
+        }
+        write_bytes(writer, &[self.get_type()])?;
+        match self {
+            LockupScript::P2PKH(hash) | LockupScript::P2SH(hash) | LockupScript::P2C(hash) => {
+                hash.encode(writer)
+            }
+            LockupScript::P2MPKH(p2mpkh) => p2mpkh.encode(writer),
+            LockupScript::Unknown => Err(EncodeError::InvalidData),
+        }
+    }
 }
 
 impl RawDecoder for LockupScript {
@@ -138,14 +189,18 @@ impl RawDecoder for LockupScript {
 mod tests {
     extern crate std;
 
+    use crate::base58::{base58_encode, limb_size};
     use crate::buffer::Buffer;
-    use crate::decode::{new_decoder, Decoder};
+    use crate::decode::{new_decoder, DecodeError, Decoder};
+    use crate::encode::tests::encode_to_vec;
+    use crate::encode::{EncodeError, Encoder};
     use crate::types::byte32::tests::gen_bytes;
     use crate::types::i32::tests::random_usize;
     use crate::types::u256::tests::hex_to_bytes;
     use crate::types::{Hash, LockupScript};
     use crate::TempData;
     use std::vec;
+    use std::vec::Vec;
 
     fn test(prefix: u8, ctor: fn(Hash) -> LockupScript) {
         for _ in 0..10 {
@@ -161,6 +216,7 @@ mod tests {
                 let result = decoder.decode(&mut buffer).unwrap();
                 assert_eq!(result, Some(&lockup_script));
             }
+            assert_eq!(encode_to_vec(&lockup_script), bytes);
 
             let mut length: usize = 0;
             let mut decoder = new_decoder::<LockupScript>();
@@ -208,6 +264,11 @@ mod tests {
             assert!(result.is_some());
             assert!(decoder.stage.is_complete());
             assert_eq!(temp_data.get(), &bytes);
+            // The public key hashes are skipped by the decoder
+            assert_eq!(
+                decoder.inner.encode(&mut Vec::new()),
+                Err(EncodeError::IncompleteData)
+            );
         }
 
         let mut temp_data = TempData::new();
@@ -230,4 +291,81 @@ mod tests {
             }
         }
     }
+
+    fn parse_address(bytes: &[u8]) -> Result<(LockupScript, Vec<u8>), DecodeError> {
+        let mut address = [0u8; 256];
+        let mut limbs = [0u32; limb_size(256)];
+        let address = base58_encode(bytes, &mut limbs[..], &mut address).unwrap();
+        let mut output = [0u8; 128];
+        LockupScript::from_address_str(address, &mut output)
+            .map(|(lockup_script, bytes)| (lockup_script, bytes.to_vec()))
+    }
+
+    fn test_address(prefix: u8, ctor: fn(Hash) -> LockupScript) {
+        let mut bytes = vec![prefix];
+        let hash_bytes = gen_bytes(32, 32);
+        bytes.extend(&hash_bytes);
+        let lockup_script = ctor(Hash::from_bytes(hash_bytes.as_slice().try_into().unwrap()));
+        assert_eq!(parse_address(&bytes), Ok((lockup_script, bytes)));
+    }
+
+    #[test]
+    fn test_from_address_str() {
+        test_address(0, LockupScript::P2PKH);
+        test_address(2, LockupScript::P2SH);
+        test_address(3, LockupScript::P2C);
+
+        let bytes = hex_to_bytes("0103a3cd757be03c7dac8d48bf79e2a7d6e735e018a9c054b99138c7b29738c437ecef51c98556924afa1cd1a8026c3d2d33ee1d491e1fe77c73a75a2d0129f061951dd2aa371711d1faea1c96d395f08eb94de1f388993e8be3f4609dc327ab513a02").unwrap();
+        let (lockup_script, decoded) = parse_address(&bytes).unwrap();
+        assert_eq!(decoded, bytes);
+        match lockup_script {
+            LockupScript::P2MPKH(p2mpkh) => {
+                assert_eq!(p2mpkh.inner.size.inner, 3);
+                assert_eq!(p2mpkh.inner.m.inner, 2);
+            }
+            _ => panic!("expected a P2MPKH lockup script"),
+        }
+    }
+
+    #[test]
+    fn test_from_address_str_invalid_input() {
+        let mut output = [0u8; 128];
+        assert_eq!(
+            LockupScript::from_address_str(b"", &mut output),
+            Err(DecodeError::InvalidSize)
+        );
+        assert_eq!(
+            LockupScript::from_address_str(
+                b"1DrDyTr9RpRsQnDnXo2YRiPzPW4ooHX5LLoqXrqfMrpQH0",
+                &mut output
+            ),
+            Err(DecodeError::InvalidData)
+        );
+
+        let mut bytes = vec![0u8];
+        bytes.extend(gen_bytes(32, 32));
+        // unknown lockup script type
+        let mut invalid_type = bytes.clone();
+        invalid_type[0] = 4;
+        assert_eq!(parse_address(&invalid_type), Err(DecodeError::InvalidData));
+        // truncated hash
+        assert_eq!(
+            parse_address(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::InvalidSize)
+        );
+        // trailing bytes
+        let mut trailing_bytes = bytes.clone();
+        trailing_bytes.push(0);
+        assert_eq!(
+            parse_address(&trailing_bytes),
+            Err(DecodeError::InvalidSize)
+        );
+
+        let p2mpkh_bytes = hex_to_bytes("0103a3cd757be03c7dac8d48bf79e2a7d6e735e018a9c054b99138c7b29738c437ecef51c98556924afa1cd1a8026c3d2d33ee1d491e1fe77c73a75a2d0129f061951dd2aa371711d1faea1c96d395f08eb94de1f388993e8be3f4609dc327ab513a02").unwrap();
+        for m in [0u8, 4u8] {
+            let mut invalid_m = p2mpkh_bytes.clone();
+            *invalid_m.last_mut().unwrap() = m;
+            assert_eq!(parse_address(&invalid_m), Err(DecodeError::InvalidData));
+        }
+    }
 }
@@ -24,6 +24,15 @@ macro_rules! fixed_size_bytes {
             }
         }
 
+        impl $crate::encode::Encoder for $struct_name {
+            fn encode<W: $crate::buffer::Writable>(
+                &self,
+                writer: &mut W,
+            ) -> $crate::encode::EncodeResult<()> {
+                $crate::encode::write_bytes(writer, &self.0)
+            }
+        }
+
         impl RawDecoder for $struct_name {
             fn step_size(&self) -> u16 {
                 1
@@ -71,6 +80,12 @@ macro_rules! fixed_size_integer {
             }
         }
 
+        impl $crate::encode::Encoder for $struct_name {
+            fn encode<W: Writable>(&self, writer: &mut W) -> $crate::encode::EncodeResult<()> {
+                $crate::encode::write_bytes(writer, &self.0.to_be_bytes())
+            }
+        }
+
         impl RawDecoder for $struct_name {
             fn step_size(&self) -> u16 {
                 1
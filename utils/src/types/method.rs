@@ -1,6 +1,7 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -24,6 +25,17 @@ impl Reset for Method {
     }
 }
 
+impl Encoder for Method {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.is_public.encode(writer)?;
+        self.asset_modifier.encode(writer)?;
+        self.args_length.encode(writer)?;
+        self.locals_length.encode(writer)?;
+        self.return_length.encode(writer)?;
+        self.instrs.encode(writer)
+    }
+}
+
 impl RawDecoder for Method {
     fn step_size(&self) -> u16 {
         5 + self.instrs.step_size()
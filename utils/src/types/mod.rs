@@ -3,7 +3,7 @@ pub mod avector;
 pub mod bool;
 pub mod byte;
 pub mod byte32;
-mod compact_integer;
+pub(crate) mod compact_integer;
 pub mod hint;
 pub mod i256;
 pub mod i32;
@@ -28,7 +28,7 @@ pub mod u16;
 
 pub use byte32::Byte32;
 pub use i256::I256;
-pub use u256::U256;
+pub use u256::{AlphUnit, AmountFormat, U256};
 
 pub use self::bool::Bool;
 pub use self::i32::I32;
@@ -37,7 +37,7 @@ pub use asset_output::AssetOutput;
 pub use avector::AVector;
 pub use bigint::BigInt;
 pub use byte::Byte;
-pub use byte_string::ByteString;
+pub use byte_string::{ByteString, ByteVec};
 pub use hint::Hint;
 pub use instr::Instr;
 pub use lockup_script::LockupScript;
@@ -1,6 +1,7 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -12,6 +13,12 @@ impl Reset for Script {
     }
 }
 
+impl Encoder for Script {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.0.encode(writer)
+    }
+}
+
 impl RawDecoder for Script {
     fn step_size(&self) -> u16 {
         self.0.step_size()
@@ -1,6 +1,7 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -22,6 +23,13 @@ impl Token {
     }
 }
 
+impl Encoder for Token {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.id.encode(writer)?;
+        self.amount.encode(writer)
+    }
+}
+
 impl RawDecoder for Token {
     fn step_size(&self) -> u16 {
         2
@@ -1,6 +1,7 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
@@ -18,6 +19,25 @@ impl Reset for TxInput {
     }
 }
 
+impl TxInput {
+    pub fn field_name(&self, stage: &DecodeStage) -> Option<&'static str> {
+        match stage.step {
+            0 => Some("hint"),
+            1 => Some("key"),
+            2 => Some("unlock_script"),
+            _ => None,
+        }
+    }
+}
+
+impl Encoder for TxInput {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.hint.encode(writer)?;
+        self.key.encode(writer)?;
+        self.unlock_script.encode(writer)
+    }
+}
+
 impl RawDecoder for TxInput {
     fn step_size(&self) -> u16 {
         3
@@ -1,5 +1,6 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 use crate::types::compact_integer::*;
 
 #[cfg_attr(test, derive(Debug))]
@@ -36,6 +37,12 @@ impl PartialEq for U16 {
     }
 }
 
+impl Encoder for U16 {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        write_compact_i32(writer, self.inner as i32)
+    }
+}
+
 impl RawDecoder for U16 {
     fn step_size(&self) -> u16 {
         1
@@ -1,10 +1,11 @@
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 use crate::types::compact_integer::*;
 
 use super::{reset, BigInt};
 
-#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 #[derive(Default, PartialEq, Clone)]
 pub struct U256(pub BigInt);
 
@@ -14,6 +15,36 @@ impl Reset for U256 {
     }
 }
 
+impl Encoder for U256 {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.0.encode(writer)
+    }
+}
+
+// The unit of the ALPH amounts
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Default, PartialEq, Clone, Copy)]
+pub enum AlphUnit {
+    #[default]
+    Alph,
+    // The raw amount without decimals, 1 ALPH = 10^18 attoALPH
+    AttoAlph,
+}
+
+// How the amounts are displayed, the default format prints the exact amount without grouping
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Default, PartialEq, Clone, Copy)]
+pub struct AmountFormat {
+    // Separate the groups of three integer digits with ','
+    pub thousands_separator: bool,
+    // The decimals are counted from the decimal point, or from the first non-zero decimal if the
+    // integer part is zero, the amount is prefixed with `APPROX_MARKER` if decimals are dropped
+    pub max_significant_decimals: Option<usize>,
+    pub alph_unit: AlphUnit,
+}
+
+pub const APPROX_MARKER: &[u8] = "\u{2248}".as_bytes();
+
 fn trim(dest: &[u8]) -> &[u8] {
     let mut index = dest.len() - 1;
     while index != 0 {
@@ -30,6 +61,65 @@ fn trim(dest: &[u8]) -> &[u8] {
     }
 }
 
+// Apply the format to the decimal string at the start of the output, returns the formatted length
+fn format_decimal_str(output: &mut [u8], length: usize, format: &AmountFormat) -> Option<usize> {
+    let mut length = length;
+    let mut is_truncated = false;
+    if let Some(max_decimals) = format.max_significant_decimals {
+        if let Some(point_index) = output[..length].iter().position(|&c| c == b'.') {
+            let decimals = &output[(point_index + 1)..length];
+            let from_index = if output[..point_index] == *b"0" {
+                // The trimmed decimals always have a non-zero digit
+                point_index + 1 + decimals.iter().position(|&c| c != b'0').unwrap_or(0)
+            } else {
+                point_index + 1
+            };
+            let to_index = from_index + max_decimals;
+            if to_index < length {
+                // The last decimal is not zero, so the truncated amount is always inexact
+                is_truncated = true;
+                length = trim(&output[..to_index]).len();
+            }
+        }
+    }
+
+    if format.thousands_separator {
+        let integer_length = output[..length]
+            .iter()
+            .position(|&c| c == b'.')
+            .unwrap_or(length);
+        let separator_size = (integer_length - 1) / 3;
+        if length + separator_size > output.len() {
+            return None;
+        }
+        output.copy_within(integer_length..length, integer_length + separator_size);
+        // Move the integer digits from the last one, so no digit is overwritten before it is moved
+        let mut to_index = integer_length + separator_size;
+        let mut group_size = 0;
+        for from_index in (0..integer_length).rev() {
+            if group_size == 3 {
+                to_index -= 1;
+                output[to_index] = b',';
+                group_size = 0;
+            }
+            to_index -= 1;
+            output[to_index] = output[from_index];
+            group_size += 1;
+        }
+        length += separator_size;
+    }
+
+    if is_truncated {
+        if length + APPROX_MARKER.len() > output.len() {
+            return None;
+        }
+        output.copy_within(0..length, APPROX_MARKER.len());
+        output[..APPROX_MARKER.len()].copy_from_slice(APPROX_MARKER);
+        length += APPROX_MARKER.len();
+    }
+    Some(length)
+}
+
 impl U256 {
     const ALPH_DECIMALS: usize = 18;
 
@@ -69,7 +159,7 @@ impl U256 {
             U256::from_encoded_bytes(&[((n >> 8) + 0x40) as u8, n as u8])
         } else if n < (0x40 << 24) {
             U256::from_encoded_bytes(&[
-                ((n >> 24) + 0x40) as u8,
+                ((n >> 24) + 0x80) as u8,
                 (n >> 16) as u8,
                 (n >> 8) as u8,
                 n as u8,
@@ -79,6 +169,11 @@ impl U256 {
         }
     }
 
+    #[inline]
+    pub fn from_u128(value: u128) -> U256 {
+        Self::encode_u128(value)
+    }
+
     fn encode_u128(value: u128) -> U256 {
         if value < (0x40 << 24) {
             U256::encode_fixed_bytes(value as u32)
@@ -161,12 +256,18 @@ impl U256 {
         }
 
         if str_length > decimals {
+            if str_length == output.len() {
+                return None;
+            }
             let decimal_index = str_length - decimals;
             output.copy_within(decimal_index..str_length, decimal_index + 1);
             output[decimal_index] = b'.';
             return Some(trim(&output[..(decimal_index + decimals + 1)]));
         }
 
+        if decimals + 2 > output.len() {
+            return None;
+        }
         let pad_size = decimals - str_length;
         output.copy_within(0..str_length, 2 + pad_size);
         for (i, element) in output.iter_mut().enumerate().take(2 + pad_size) {
@@ -199,6 +300,35 @@ impl U256 {
         let total_size = prefix.len() + str.len();
         Some(&output[..total_size])
     }
+
+    pub fn to_str_with_format<'a>(
+        &self,
+        output: &'a mut [u8],
+        decimals: usize,
+        format: &AmountFormat,
+    ) -> Option<&'a [u8]> {
+        let length = self.to_str_with_decimals(output, decimals)?.len();
+        let length = format_decimal_str(output, length, format)?;
+        Some(&output[..length])
+    }
+
+    pub fn to_alph_with_format<'a>(
+        &self,
+        output: &'a mut [u8],
+        format: &AmountFormat,
+    ) -> Option<&'a [u8]> {
+        let (prefix, decimals): (&[u8], usize) = match format.alph_unit {
+            AlphUnit::Alph => (b"ALPH ", Self::ALPH_DECIMALS),
+            AlphUnit::AttoAlph => (b"attoALPH ", 0),
+        };
+        if output.len() < prefix.len() {
+            return None;
+        }
+        output[..prefix.len()].copy_from_slice(prefix);
+        let str = self.to_str_with_format(&mut output[prefix.len()..], decimals, format)?;
+        let total_size = prefix.len() + str.len();
+        Some(&output[..total_size])
+    }
 }
 
 impl RawDecoder for U256 {
@@ -221,7 +351,8 @@ pub mod tests {
     extern crate std;
 
     use crate::buffer::Buffer;
-    use crate::types::u256::U256;
+    use crate::encode::tests::check_round_trip;
+    use crate::types::u256::{AlphUnit, AmountFormat, U256};
     use crate::{decode::*, TempData};
     use alloc::borrow::ToOwned;
     use core::str::from_utf8;
@@ -364,6 +495,29 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_invalid_size() {
+        let mut temp_data = TempData::new();
+        // The encoded length is larger than 33 bytes
+        for first_byte in [0xdd, 0xff] {
+            let mut bytes = std::vec![first_byte];
+            bytes.extend([0u8; 70]);
+            let mut decoder = new_decoder::<U256>();
+            let mut buffer = Buffer::new(&bytes, &mut temp_data);
+            assert_eq!(
+                decoder.decode(&mut buffer).err(),
+                Some(DecodeError::InvalidSize)
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_u256() {
+        for item in get_test_vector() {
+            check_round_trip::<U256>(&item.0);
+        }
+    }
+
     #[test]
     fn test_multiply() {
         let min_gas_price = u128::pow(10, 11);
@@ -445,4 +599,82 @@ pub mod tests {
         let result = u256.to_str(&mut output);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_to_str_with_format() {
+        let grouped = AmountFormat {
+            thousands_separator: true,
+            ..AmountFormat::default()
+        };
+        let rounded = AmountFormat {
+            max_significant_decimals: Some(2),
+            ..AmountFormat::default()
+        };
+        let cases = [
+            (100, 0, grouped, "100"),
+            (1000, 0, grouped, "1,000"),
+            (123456, 0, grouped, "123,456"),
+            (123456000000, 0, grouped, "123,456,000,000"),
+            (1234567000, 3, grouped, "1,234,567"),
+            (1234567891, 3, grouped, "1,234,567.891"),
+            (1234560000000, 12, rounded, "\u{2248}1.23"),
+            (1200000000000, 12, rounded, "1.2"),
+            (1200010000000, 12, rounded, "\u{2248}1.2"),
+            (123, 6, rounded, "\u{2248}0.00012"),
+            (12, 6, rounded, "0.000012"),
+            (
+                1234567891,
+                3,
+                AmountFormat {
+                    thousands_separator: true,
+                    max_significant_decimals: Some(1),
+                    ..AmountFormat::default()
+                },
+                "\u{2248}1,234,567.8",
+            ),
+        ];
+        for (number, decimals, format, expected) in cases {
+            let u256 = U256::encode_u128(number);
+            let mut output = [0u8; 32];
+            let result = u256.to_str_with_format(&mut output, decimals, &format);
+            assert_eq!(from_utf8(result.unwrap()).unwrap(), expected);
+        }
+
+        let mut output = [0u8; 5];
+        let u256 = U256::encode_u128(12345);
+        assert!(u256.to_str_with_format(&mut output, 0, &grouped).is_none());
+        let mut output = [0u8; 10];
+        let u256 = U256::encode_u128(1);
+        assert!(u256.to_str_with_format(&mut output, 18, &rounded).is_none());
+    }
+
+    #[test]
+    fn test_to_alph_with_format() {
+        let u256 = U256::encode_u128(1234567 * (_1000_NANO_ALPH as u128));
+        let mut expected = [0u8; 33];
+        let mut output = [0u8; 64];
+        assert_eq!(
+            u256.to_alph_with_format(&mut output, &AmountFormat::default()),
+            u256.to_alph(&mut expected)
+        );
+
+        let format = AmountFormat {
+            thousands_separator: true,
+            max_significant_decimals: Some(4),
+            alph_unit: AlphUnit::Alph,
+        };
+        let result = u256.to_alph_with_format(&mut output, &format).unwrap();
+        assert_eq!(from_utf8(result).unwrap(), "ALPH \u{2248}1.2345");
+
+        let format = AmountFormat {
+            thousands_separator: true,
+            max_significant_decimals: Some(4),
+            alph_unit: AlphUnit::AttoAlph,
+        };
+        let result = u256.to_alph_with_format(&mut output, &format).unwrap();
+        assert_eq!(
+            from_utf8(result).unwrap(),
+            "attoALPH 1,234,567,000,000,000,000"
+        );
+    }
 }
@@ -1,12 +1,13 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
 pub struct PublicKeyWithIndex {
-    public_key: PublicKey,
-    index: U16,
+    pub public_key: PublicKey,
+    pub index: U16,
 }
 
 impl Reset for PublicKeyWithIndex {
@@ -16,6 +17,13 @@ impl Reset for PublicKeyWithIndex {
     }
 }
 
+impl Encoder for PublicKeyWithIndex {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.public_key.encode(writer)?;
+        self.index.encode(writer)
+    }
+}
+
 impl RawDecoder for PublicKeyWithIndex {
     fn step_size(&self) -> u16 {
         2
@@ -38,9 +46,23 @@ impl RawDecoder for PublicKeyWithIndex {
 #[derive(Default)]
 pub struct P2SH(Script, AVector<Val>);
 
+impl P2SH {
+    // The number of arguments that unlock the script
+    pub fn args_size(&self) -> usize {
+        self.1.size()
+    }
+}
+
+impl Encoder for P2SH {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.0.encode(writer)?;
+        self.1.encode(writer)
+    }
+}
+
 impl RawDecoder for P2SH {
     fn step_size(&self) -> u16 {
-        self.0.step_size() + self.0.step_size()
+        self.0.step_size() + self.1.step_size()
     }
 
     fn decode<W: Writable>(
@@ -91,6 +113,27 @@ impl UnlockScript {
     }
 }
 
+impl Encoder for UnlockScript {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self {
+            UnlockScript::P2PKH(public_key) => {
+                write_bytes(writer, &[0])?;
+                public_key.encode(writer)
+            }
+            UnlockScript::P2MPKH(public_keys) => {
+                write_bytes(writer, &[1])?;
+                public_keys.encode(writer)
+            }
+            UnlockScript::P2SH(p2sh) => {
+                write_bytes(writer, &[2])?;
+                p2sh.encode(writer)
+            }
+            UnlockScript::SameAsPrevious => write_bytes(writer, &[3]),
+            UnlockScript::Unknown => Err(EncodeError::InvalidData),
+        }
+    }
+}
+
 impl RawDecoder for UnlockScript {
     fn step_size(&self) -> u16 {
         1
@@ -114,7 +157,15 @@ impl RawDecoder for UnlockScript {
         };
         match self {
             UnlockScript::P2PKH(public_key) => public_key.decode(buffer, stage),
-            UnlockScript::P2MPKH(keys) => keys.decode_children(buffer, stage),
+            UnlockScript::P2MPKH(keys) => {
+                // The decoder only keeps the last key, so the encoded keys are written to the temp data
+                // for the reviewer to find the device key among the signers
+                let from_index = buffer.get_index();
+                let result = keys.decode_children(buffer, stage);
+                let to_index = buffer.get_index();
+                buffer.write_bytes_to_temp_data(buffer.get_range(from_index, to_index))?;
+                result
+            }
             UnlockScript::P2SH(script) => script.decode_children(buffer, stage),
             UnlockScript::SameAsPrevious => Ok(DecodeStage::COMPLETE),
             UnlockScript::Unknown => Err(DecodeError::InternalError),
@@ -176,25 +227,36 @@ mod tests {
 
     #[test]
     fn test_decode_p2sh() {
-        let mut temp_data = TempData::new();
         let bytecode = hex_to_bytes("010100000000045814402000000000000000000000000000000000000000000000000000000000000000008685").unwrap();
-        let bytes = hex_to_bytes("0201010000000004581440200000000000000000000000000000000000000000000000000000000000000000868500").unwrap();
-
-        let mut length: usize = 0;
-        let mut decoder = new_decoder::<UnlockScript>();
-
-        while length < bytes.len() {
-            let remain = bytes.len() - length;
-            let size = random_usize(0, remain);
-            let mut buffer = Buffer::new(&bytes[length..(length + size)], &mut temp_data);
-            length += size;
-
-            let result = decoder.decode(&mut buffer).unwrap();
-            if length == bytes.len() {
-                assert_eq!(temp_data.get(), &bytecode);
-                assert!(decoder.stage.is_complete());
-            } else {
-                assert_eq!(result, None);
+        // the unlock arguments are empty, or two booleans
+        for (args, args_size) in [("00", 0), ("0200010001", 2)] {
+            let mut temp_data = TempData::new();
+            let mut bytes = vec![2u8];
+            bytes.extend(&bytecode);
+            bytes.extend(hex_to_bytes(args).unwrap());
+
+            let mut length: usize = 0;
+            let mut decoder = new_decoder::<UnlockScript>();
+
+            while length < bytes.len() {
+                let remain = bytes.len() - length;
+                let size = random_usize(0, remain);
+                let mut buffer = Buffer::new(&bytes[length..(length + size)], &mut temp_data);
+                length += size;
+
+                let result = decoder.decode(&mut buffer).unwrap();
+                if length == bytes.len() {
+                    assert_eq!(temp_data.get(), &bytecode);
+                    match result {
+                        Some(UnlockScript::P2SH(p2sh)) => {
+                            assert_eq!(p2sh.inner.args_size(), args_size)
+                        }
+                        _ => panic!("expected a P2SH unlock script"),
+                    }
+                    assert!(decoder.stage.is_complete());
+                } else {
+                    assert_eq!(result, None);
+                }
             }
         }
     }
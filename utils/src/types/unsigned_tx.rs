@@ -1,6 +1,7 @@
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
+use crate::encode::*;
 
 #[cfg_attr(test, derive(Debug))]
 pub enum UnsignedTx {
@@ -38,6 +39,41 @@ impl UnsignedTx {
             Self::FixedOutputs(_) => (),
         }
     }
+
+    // The path of the data being decoded, used to report where decoding failed
+    pub fn decode_path(&self) -> DecodePath {
+        match self {
+            Self::Version(_) => DecodePath::new("Version"),
+            Self::NetworkId(_) => DecodePath::new("NetworkId"),
+            Self::ScriptOpt(_) => DecodePath::new("ScriptOpt"),
+            Self::TxFee(tx_fee) => DecodePath {
+                field: tx_fee.inner.field_name(&tx_fee.stage),
+                ..DecodePath::new("TxFee")
+            },
+            Self::Inputs(inputs) => match inputs.get_decoding_item() {
+                Some((index, input)) => DecodePath {
+                    index: Some(index),
+                    field: input.inner.field_name(&input.stage),
+                    ..DecodePath::new("Inputs")
+                },
+                None => DecodePath {
+                    field: Some("size"),
+                    ..DecodePath::new("Inputs")
+                },
+            },
+            Self::FixedOutputs(outputs) => match outputs.get_decoding_item() {
+                Some((index, output)) => DecodePath {
+                    index: Some(index),
+                    field: output.inner.field_name(&output.stage),
+                    ..DecodePath::new("FixedOutputs")
+                },
+                None => DecodePath {
+                    field: Some("size"),
+                    ..DecodePath::new("FixedOutputs")
+                },
+            },
+        }
+    }
 }
 
 impl Default for UnsignedTx {
@@ -46,6 +82,20 @@ impl Default for UnsignedTx {
     }
 }
 
+// `UnsignedTx` is decoded section by section, only the current section is encoded
+impl Encoder for UnsignedTx {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self {
+            Self::Version(byte) => byte.encode(writer),
+            Self::NetworkId(byte) => byte.encode(writer),
+            Self::ScriptOpt(script) => script.encode(writer),
+            Self::TxFee(tx_fee) => tx_fee.encode(writer),
+            Self::Inputs(inputs) => inputs.encode(writer),
+            Self::FixedOutputs(outputs) => outputs.encode(writer),
+        }
+    }
+}
+
 impl RawDecoder for UnsignedTx {
     fn step_size(&self) -> u16 {
         match self {
@@ -90,6 +140,23 @@ impl Reset for TxFee {
     }
 }
 
+impl TxFee {
+    pub fn field_name(&self, stage: &DecodeStage) -> Option<&'static str> {
+        match stage.step {
+            0 => Some("gas_amount"),
+            1 => Some("gas_price"),
+            _ => None,
+        }
+    }
+}
+
+impl Encoder for TxFee {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        self.gas_amount.encode(writer)?;
+        self.gas_price.encode(writer)
+    }
+}
+
 impl RawDecoder for TxFee {
     fn step_size(&self) -> u16 {
         2
@@ -116,14 +183,21 @@ mod tests {
     use super::*;
     use crate::buffer::Buffer;
     use crate::decode::new_decoder;
+    use crate::encode::tests::encode_to_vec;
+    use crate::types::byte32::tests::gen_bytes;
     use crate::types::i32::tests::random_usize;
+    use crate::types::u256::tests::get_test_vector;
     use crate::types::u256::tests::hex_to_bytes;
-    use crate::types::{AVector, Hash, Hint, LockupScript, PublicKey, UnlockScript, I32, U256};
+    use crate::types::{
+        AVector, ByteVec, Hash, Hint, LockupScript, PublicKey, UnlockScript, I32, U256,
+    };
     use crate::TempData;
     use blake2::{Blake2b, Digest};
     use core::cmp::min;
     use digest::consts::U32;
     use num_bigint::BigUint;
+    use std::format;
+    use std::string::String;
     use std::vec::Vec;
 
     type Blake2b256 = Blake2b<U32>;
@@ -300,6 +374,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_path() {
+        // version, network id, script, gas amount, gas price, 0 inputs, 1 output with an invalid lockup script type
+        let encoded_tx =
+            hex_to_bytes("0000008000de1cc1174876e8000001c40de0b6b3a764000009").unwrap();
+        let mut temp_data = TempData::new();
+        let mut decoder = new_decoder::<UnsignedTx>();
+        let mut buffer = Buffer::new(&encoded_tx, &mut temp_data);
+        let error = loop {
+            match decoder.step(&mut buffer) {
+                Ok(true) => {
+                    decoder.inner.next_step();
+                    decoder.reset_stage();
+                }
+                Ok(false) => panic!("decoding should fail"),
+                Err(error) => break error,
+            }
+        };
+        assert_eq!(error, DecodeError::InvalidData);
+        assert_eq!(buffer.get_index(), encoded_tx.len());
+
+        let path = decoder.inner.decode_path();
+        let mut output = [0u8; 64];
+        assert_eq!(
+            path.to_str(&mut output).unwrap(),
+            b"FixedOutputs[0].lockup_script"
+        );
+
+        decoder.reset();
+        assert_eq!(decoder.inner.decode_path(), DecodePath::new("Version"));
+        let mut buffer = Buffer::new(&encoded_tx[..6], &mut temp_data);
+        while decoder.step(&mut buffer).unwrap() {
+            decoder.inner.next_step();
+            decoder.reset_stage();
+        }
+        let path = decoder.inner.decode_path();
+        assert_eq!(path.to_str(&mut output).unwrap(), b"TxFee.gas_amount");
+    }
+
     #[test]
     fn test_decode_transfer_alph_tx() {
         let tx_id_hex = "c53f150bceb13c6ca1c13fee897e688c0ef86c73ad8113edf444b7b15ecf438b";
@@ -550,4 +663,95 @@ mod tests {
             &all_outputs,
         );
     }
+
+    fn random_lockup_script() -> LockupScript {
+        let hash = Hash::from_bytes(gen_bytes(32, 32).as_slice().try_into().unwrap());
+        match random_usize(0, 2) {
+            0 => LockupScript::P2PKH(hash),
+            1 => LockupScript::P2SH(hash),
+            _ => LockupScript::P2C(hash),
+        }
+    }
+
+    fn random_u256() -> U256 {
+        let test_vector = get_test_vector();
+        let index = random_usize(0, test_vector.len() - 1);
+        U256::from_encoded_bytes(&test_vector[index].0)
+    }
+
+    #[test]
+    fn test_encode_random_tx() {
+        for _ in 0..10 {
+            let network_id = random_usize(0, 255) as u8;
+            let gas_amount = I32::from(random_usize(0, i32::MAX as usize) as i32);
+            let gas_price = random_u256();
+
+            let inputs: Vec<TxInput> = (0..random_usize(1, 5))
+                .map(|_| {
+                    let public_key = gen_bytes(33, 33);
+                    TxInput {
+                        hint: Hint::from_bytes(gen_bytes(4, 4).as_slice().try_into().unwrap()),
+                        key: Hash::from_bytes(gen_bytes(32, 32).as_slice().try_into().unwrap()),
+                        unlock_script: UnlockScript::P2PKH(PublicKey::from_bytes(
+                            public_key.as_slice().try_into().unwrap(),
+                        )),
+                    }
+                })
+                .collect();
+
+            let mut encoded_outputs = Vec::new();
+            let mut outputs = Vec::new();
+            let output_size = random_usize(1, 5);
+            encoded_outputs.extend(encode_to_vec(&I32::from(output_size as i32)));
+            for _ in 0..output_size {
+                let tokens: Vec<Token> = (0..random_usize(0, 3))
+                    .map(|_| Token {
+                        id: Hash::from_bytes(gen_bytes(32, 32).as_slice().try_into().unwrap()),
+                        amount: random_u256(),
+                    })
+                    .collect();
+                let additional_data = gen_bytes(0, 10);
+                let output = AssetOutput {
+                    amount: random_u256(),
+                    lockup_script: random_lockup_script(),
+                    lock_time: TimeStamp(rand::random()),
+                    tokens: AVector::default(),
+                    additional_data: ByteString {
+                        length: I32::from(additional_data.len() as i32),
+                        current_index: additional_data.len() as i32,
+                    },
+                };
+                encoded_outputs.extend(encode_to_vec(&(
+                    &output.amount,
+                    &output.lockup_script,
+                    &output.lock_time,
+                    tokens.as_slice(),
+                    ByteVec(&additional_data),
+                )));
+                // The decoder only keeps the last token
+                let tokens = match tokens.into_iter().last() {
+                    Some(token) => AVector::from_item(token),
+                    None => AVector::default(),
+                };
+                outputs.push(AssetOutput { tokens, ..output });
+            }
+
+            let mut encoded_tx = encode_to_vec(&(
+                Byte(0),
+                Byte(network_id),
+                None::<Script>,
+                (&gas_amount, &gas_price),
+                inputs.as_slice(),
+            ));
+            encoded_tx.extend(encoded_outputs);
+
+            let tx_id: String = Blake2b256::digest(&encoded_tx)
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            decode_and_check_tx(
+                &tx_id, encoded_tx, network_id, gas_amount, gas_price, false, &inputs, &outputs,
+            );
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::buffer::Writable;
 use crate::decode::*;
+use crate::encode::*;
 use crate::types::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -33,6 +34,19 @@ impl Val {
     }
 }
 
+impl Encoder for Val {
+    fn encode<W: Writable>(&self, writer: &mut W) -> EncodeResult<()> {
+        match self {
+            Val::Bool(v) => (Byte(0), v).encode(writer),
+            Val::I256(v) => (Byte(1), v).encode(writer),
+            Val::U256(v) => (Byte(2), v).encode(writer),
+            Val::ByteVec(v) => (Byte(3), v).encode(writer),
+            Val::Address(v) => (Byte(4), v).encode(writer),
+            Val::Unknown => Err(EncodeError::InvalidData),
+        }
+    }
+}
+
 impl RawDecoder for Val {
     fn step_size(&self) -> u16 {
         match self {